@@ -31,14 +31,10 @@ impl ConfirmationHandler {
         println!("   Parameters: {}", params_formatted);
         print!("\nDo you want to proceed? [y/N]: ");
 
-        io::stdout().flush().map_err(|e| {
-            crate::error::AppError::Storage(format!("Failed to flush stdout: {}", e))
-        })?;
+        io::stdout().flush()?;
 
         let mut input = String::new();
-        io::stdin().read_line(&mut input).map_err(|e| {
-            crate::error::AppError::Storage(format!("Failed to read user input: {}", e))
-        })?;
+        io::stdin().read_line(&mut input)?;
 
         let answer = input.trim().to_lowercase();
         Ok(answer == "y" || answer == "yes")
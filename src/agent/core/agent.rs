@@ -5,6 +5,8 @@ use crate::agent::streaming::{StreamingHandler, ConsoleStreamingHandler};
 use crate::agent::tool_executor::ToolExecutor;
 use crate::agent::ToolRegistry;
 use crate::error::AppResult;
+use crate::storage::Storage;
+use std::sync::Arc;
 
 #[cfg(feature = "ai")]
 use uuid::Uuid;
@@ -24,9 +26,9 @@ pub struct Agent {
 }
 
 impl Agent {
-    pub fn new() -> AppResult<Self> {
+    pub async fn new(storage: Arc<dyn Storage>) -> AppResult<Self> {
         let mut registry = ToolRegistry::new();
-        registry.auto_discover_tools()?;
+        registry.auto_discover_tools(storage).await?;
 
         // Load persistent memory
         let memory_path = ConversationMemory::get_memory_file_path();
@@ -44,9 +46,9 @@ impl Agent {
         })
     }
 
-    pub fn new_with_memory_limits(max_messages: usize, max_tokens: usize) -> AppResult<Self> {
+    pub async fn new_with_memory_limits(storage: Arc<dyn Storage>, max_messages: usize, max_tokens: usize) -> AppResult<Self> {
         let mut registry = ToolRegistry::new();
-        registry.auto_discover_tools()?;
+        registry.auto_discover_tools(storage).await?;
 
         // Load persistent memory with custom limits
         let memory_path = ConversationMemory::get_memory_file_path();
@@ -160,7 +162,7 @@ impl Agent {
     {
         // Send to Claude API
         let claude_client = self.claude_client.as_ref()
-            .ok_or_else(|| crate::error::AppError::Storage("Claude client not configured".to_string()))?;
+            .ok_or_else(|| crate::error::AppError::Config("Claude client not configured".to_string()))?;
 
         let assistant_response = claude_client.chat(prompt).await?;
 
@@ -196,7 +198,7 @@ impl Agent {
     async fn handle_iteration(&mut self, prompt: &str) -> AppResult<(String, bool)> {
         // Send to Claude API
         let claude_client = self.claude_client.as_ref()
-            .ok_or_else(|| crate::error::AppError::Storage("Claude client not configured".to_string()))?;
+            .ok_or_else(|| crate::error::AppError::Config("Claude client not configured".to_string()))?;
 
         let assistant_response = claude_client.chat(prompt).await?;
 
@@ -250,6 +252,11 @@ impl Agent {
         self.registry.get_enhanced_schemas()
     }
 
+    /// Stops external tool processes; call once the agent is no longer needed
+    pub async fn shutdown(&self) {
+        self.registry.shutdown().await;
+    }
+
     fn save_memory(&self) -> AppResult<()> {
         let memory_path = ConversationMemory::get_memory_file_path();
         self.memory.save_to_file(&memory_path)
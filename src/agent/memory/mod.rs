@@ -200,7 +200,7 @@ impl ConversationMemory {
                 .map_err(|e| crate::error::AppError::Storage(format!("Failed to read memory file: {}", e)))?;
 
             let mut memory: ConversationMemory = serde_json::from_str(&contents)
-                .map_err(|e| crate::error::AppError::Storage(format!("Failed to parse memory file: {}", e)))?;
+                .map_err(|e| crate::error::AppError::Corrupt(format!("Failed to parse memory file: {}", e)))?;
 
             // Update limits in case they changed
             memory.max_messages = max_messages;
@@ -229,10 +229,8 @@ impl ConversationMemory {
     }
 
     pub fn get_memory_file_path() -> PathBuf {
-        // Store in user data directory
-        let mut path = dirs::data_local_dir().unwrap_or_else(|| PathBuf::from("."));
-        path.push("aigenda");
-        path.push("conversation_memory.json");
-        path
+        // Kept with the notebook's notes
+        let data_dir = crate::storage::fs::FsStorage::default_data_dir().unwrap_or_else(|_| PathBuf::from("."));
+        data_dir.join("conversation_memory.json")
     }
 }
\ No newline at end of file
@@ -13,4 +13,4 @@ pub mod streaming;
 pub use core::agent::Agent;
 pub use registry::ToolRegistry;
 pub use tools::{Tool, AdvancedTool, ToolSchema, ToolCategory};
-pub use streaming::{StreamingHandler, ConsoleStreamingHandler};
\ No newline at end of file
+pub use streaming::{StreamingHandler, ConsoleStreamingHandler, JsonStreamingHandler, NdjsonStreamingHandler, AgentEvent};
\ No newline at end of file
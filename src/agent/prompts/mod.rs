@@ -1,5 +1,6 @@
 use crate::agent::memory::ConversationMemory;
 use crate::agent::ToolRegistry;
+use crate::clock::clock;
 use crate::error::AppResult;
 
 /// Handles dynamic prompt generation for different contexts
@@ -27,10 +28,19 @@ impl PromptGenerator {
             String::new()
         };
 
+        // The user's day, which can differ from the calendar date before the rollover hour
+        let now = clock().now();
+        let today = format!(
+            "Today is {} ({}); the user's local time is {}.\n",
+            clock().today().format("%Y-%m-%d"),
+            clock().today().format("%A"),
+            now.format("%H:%M %:z")
+        );
+
         let prompt = format!(
             r#"You are a helpful AI assistant with access to various tools for managing notes and tasks. Your personality should be conversational, helpful, and similar to Claude Code's style.
 
-{}Available Tools:
+{}{}Available Tools:
 {}
 
 {}Current User Request: {}
@@ -72,7 +82,7 @@ If you need to continue with more actions after seeing tool results, include phr
 
 Start by analyzing the request and explaining your approach.
 "#,
-            conversation_context, tools_description, recent_tools_hint, user_input
+            today, conversation_context, tools_description, recent_tools_hint, user_input
         );
 
         Ok(prompt)
@@ -2,6 +2,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use crate::agent::Tool;
 use crate::error::AppResult;
+use crate::storage::Storage;
 
 pub struct ToolRegistry {
     tools: HashMap<String, Arc<dyn Tool>>,
@@ -106,15 +107,26 @@ impl ToolRegistry {
             .collect()
     }
 
-    pub fn auto_discover_tools(&mut self) -> AppResult<()> {
+    /// Registers the built-in and external tools, all working on `storage`
+    pub async fn auto_discover_tools(&mut self, storage: Arc<dyn Storage>) -> AppResult<()> {
         // Register built-in notes tool
-        let notes_tool = Arc::new(crate::agent::tools::notes::NotesTool::new()?);
+        let notes_tool = Arc::new(crate::agent::tools::notes::NotesTool::new(storage.clone())?);
         self.register_tool(notes_tool);
 
-        // Register external tools
-        crate::agent::tools::external::register_all_external_tools(self)?;
+        // Register external tools, including those from configured MCP servers
+        let config = crate::config::Config::load()?;
+        crate::agent::tools::external::register_all_external_tools(self, &config, storage).await?;
 
         Ok(())
     }
 
+    /// Shuts down every tool, e.g. stopping spawned MCP servers
+    pub async fn shutdown(&self) {
+        for tool in self.tools.values() {
+            if let Err(e) = tool.shutdown().await {
+                eprintln!("⚠️  Failed to shut down tool {}: {}", tool.name(), e);
+            }
+        }
+    }
+
 }
\ No newline at end of file
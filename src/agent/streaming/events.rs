@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::AppError;
+
+/// One `StreamingHandler` callback, as emitted to programs driving the agent.
+///
+/// Serialized with a `type` tag, e.g. `{"type":"tool_call","tool":"notes",...}`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AgentEvent {
+    IterationStart {
+        iteration: usize,
+    },
+    LlmResponse {
+        text: String,
+    },
+    /// The agent waits for a matching `permission_response` before running the tool
+    PermissionRequest {
+        id: u64,
+        tool: String,
+        action: String,
+        parameters: Value,
+    },
+    PermissionResolved {
+        id: u64,
+        allowed: bool,
+    },
+    ToolCall {
+        tool: String,
+        action: String,
+        parameters: Value,
+    },
+    ToolResult {
+        tool: String,
+        action: String,
+        result: String,
+        success: bool,
+    },
+    IterationEnd {
+        iteration: usize,
+        result: String,
+    },
+    Done {
+        response: String,
+    },
+    Error {
+        kind: String,
+        code: i32,
+        message: String,
+    },
+}
+
+impl From<&AppError> for AgentEvent {
+    fn from(error: &AppError) -> Self {
+        AgentEvent::Error {
+            kind: error.kind().to_string(),
+            code: error.exit_code(),
+            message: error.to_string(),
+        }
+    }
+}
+
+/// Messages a driving program writes to the agent's stdin, one JSON object per line
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    PermissionResponse {
+        id: u64,
+        allow: bool,
+    },
+}
@@ -1,4 +1,14 @@
+mod events;
+mod ndjson;
+
+use serde_json::Value;
+
+use crate::cli::OutputFormat;
 use crate::error::AppResult;
+use crate::output::print_json;
+
+pub use events::{AgentEvent, ClientMessage};
+pub use ndjson::NdjsonStreamingHandler;
 
 /// Trait for handling streaming responses during agent execution
 pub trait StreamingHandler: Send + Sync {
@@ -66,14 +76,10 @@ impl StreamingHandler for ConsoleStreamingHandler {
         println!("   Parameters: {}", params_formatted);
         print!("\nDo you want to proceed? [y/N]: ");
 
-        io::stdout().flush().map_err(|e| {
-            crate::error::AppError::Storage(format!("Failed to flush stdout: {}", e))
-        })?;
+        io::stdout().flush()?;
 
         let mut input = String::new();
-        io::stdin().read_line(&mut input).map_err(|e| {
-            crate::error::AppError::Storage(format!("Failed to read user input: {}", e))
-        })?;
+        io::stdin().read_line(&mut input)?;
 
         let answer = input.trim().to_lowercase();
         Ok(answer == "y" || answer == "yes")
@@ -90,4 +96,93 @@ impl StreamingHandler for ConsoleStreamingHandler {
         println!("✨ Iteration {} completed.\n", iteration);
         Ok(())
     }
-}
\ No newline at end of file
+}
+/// Collects every callback as an `AgentEvent` and prints them as one JSON array
+/// once the run is over (`--output json`).
+///
+/// Stdout carries only the events, so the permission prompt goes to stderr.
+pub struct JsonStreamingHandler {
+    events: Vec<AgentEvent>,
+    next_permission_id: u64,
+}
+
+impl JsonStreamingHandler {
+    pub fn new() -> Self {
+        Self {
+            events: Vec::new(),
+            next_permission_id: 1,
+        }
+    }
+
+    /// Adds the final response and prints everything collected
+    pub fn finish(mut self, response: &str) -> AppResult<()> {
+        self.events.push(AgentEvent::Done { response: response.to_string() });
+        print_json(OutputFormat::Json, &self.events)
+    }
+}
+
+impl Default for JsonStreamingHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StreamingHandler for JsonStreamingHandler {
+    fn on_llm_response(&mut self, response: &str) -> AppResult<()> {
+        self.events.push(AgentEvent::LlmResponse { text: response.to_string() });
+        Ok(())
+    }
+
+    fn on_tool_about_to_execute(&mut self, tool_name: &str, action: &str, parameters: &Value) -> AppResult<()> {
+        self.events.push(AgentEvent::ToolCall {
+            tool: tool_name.to_string(),
+            action: action.to_string(),
+            parameters: parameters.clone(),
+        });
+        Ok(())
+    }
+
+    fn on_tool_executed(&mut self, tool_name: &str, action: &str, result: &str, success: bool) -> AppResult<()> {
+        self.events.push(AgentEvent::ToolResult {
+            tool: tool_name.to_string(),
+            action: action.to_string(),
+            result: result.to_string(),
+            success,
+        });
+        Ok(())
+    }
+
+    fn request_tool_permission(&mut self, tool_name: &str, action: &str, parameters: &Value) -> AppResult<bool> {
+        use std::io::{self, Write};
+
+        let id = self.next_permission_id;
+        self.next_permission_id += 1;
+        self.events.push(AgentEvent::PermissionRequest {
+            id,
+            tool: tool_name.to_string(),
+            action: action.to_string(),
+            parameters: parameters.clone(),
+        });
+
+        eprint!("Allow {} -> {} with {}? [y/N]: ", tool_name, action, parameters);
+        io::stderr().flush()?;
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+
+        let answer = input.trim().to_lowercase();
+        let allowed = answer == "y" || answer == "yes";
+        self.events.push(AgentEvent::PermissionResolved { id, allowed });
+        Ok(allowed)
+    }
+
+    fn on_iteration_start(&mut self, iteration: usize) -> AppResult<()> {
+        self.events.push(AgentEvent::IterationStart { iteration });
+        Ok(())
+    }
+
+    fn on_iteration_end(&mut self, iteration: usize, result: &str) -> AppResult<()> {
+        self.events.push(AgentEvent::IterationEnd { iteration, result: result.to_string() });
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+use serde_json::Value;
+use std::io::{BufRead, Write};
+
+use crate::error::{AppError, AppResult};
+use super::{AgentEvent, ClientMessage, StreamingHandler};
+
+/// Streams every callback as one JSON line on `out`, so editors and GUIs can run
+/// `aigenda ai --output ndjson` as a subprocess.
+///
+/// Permission is asked with a `permission_request` event; the agent then blocks until
+/// `{"type":"permission_response","id":N,"allow":true|false}` arrives on `input`.
+/// End of input denies the request.
+pub struct NdjsonStreamingHandler<W, R> {
+    out: W,
+    input: R,
+    next_permission_id: u64,
+}
+
+impl<W: Write + Send + Sync, R: BufRead + Send + Sync> NdjsonStreamingHandler<W, R> {
+    pub fn new(out: W, input: R) -> Self {
+        Self {
+            out,
+            input,
+            next_permission_id: 1,
+        }
+    }
+
+    pub fn emit(&mut self, event: &AgentEvent) -> AppResult<()> {
+        let mut line = serde_json::to_string(event)?;
+        line.push('\n');
+        self.out.write_all(line.as_bytes())?;
+        self.out.flush()?;
+        Ok(())
+    }
+
+    /// Reads lines until the response for `id` arrives; blank lines are skipped
+    fn read_permission_response(&mut self, id: u64) -> AppResult<bool> {
+        loop {
+            let mut line = String::new();
+            if self.input.read_line(&mut line)? == 0 {
+                return Ok(false);
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let message: ClientMessage = serde_json::from_str(line.trim())
+                .map_err(|e| AppError::InvalidParameters(format!("Invalid message on stdin: {}", e)))?;
+
+            match message {
+                ClientMessage::PermissionResponse { id: answered, allow } if answered == id => return Ok(allow),
+                ClientMessage::PermissionResponse { id: answered, .. } => {
+                    return Err(AppError::InvalidParameters(format!(
+                        "Permission response for request {} while waiting for {}", answered, id
+                    )));
+                }
+            }
+        }
+    }
+}
+
+impl<W: Write + Send + Sync, R: BufRead + Send + Sync> StreamingHandler for NdjsonStreamingHandler<W, R> {
+    fn on_llm_response(&mut self, response: &str) -> AppResult<()> {
+        self.emit(&AgentEvent::LlmResponse { text: response.to_string() })
+    }
+
+    fn on_tool_about_to_execute(&mut self, tool_name: &str, action: &str, parameters: &Value) -> AppResult<()> {
+        self.emit(&AgentEvent::ToolCall {
+            tool: tool_name.to_string(),
+            action: action.to_string(),
+            parameters: parameters.clone(),
+        })
+    }
+
+    fn on_tool_executed(&mut self, tool_name: &str, action: &str, result: &str, success: bool) -> AppResult<()> {
+        self.emit(&AgentEvent::ToolResult {
+            tool: tool_name.to_string(),
+            action: action.to_string(),
+            result: result.to_string(),
+            success,
+        })
+    }
+
+    fn request_tool_permission(&mut self, tool_name: &str, action: &str, parameters: &Value) -> AppResult<bool> {
+        let id = self.next_permission_id;
+        self.next_permission_id += 1;
+
+        self.emit(&AgentEvent::PermissionRequest {
+            id,
+            tool: tool_name.to_string(),
+            action: action.to_string(),
+            parameters: parameters.clone(),
+        })?;
+
+        let allowed = self.read_permission_response(id)?;
+        self.emit(&AgentEvent::PermissionResolved { id, allowed })?;
+        Ok(allowed)
+    }
+
+    fn on_iteration_start(&mut self, iteration: usize) -> AppResult<()> {
+        self.emit(&AgentEvent::IterationStart { iteration })
+    }
+
+    fn on_iteration_end(&mut self, iteration: usize, result: &str) -> AppResult<()> {
+        self.emit(&AgentEvent::IterationEnd { iteration, result: result.to_string() })
+    }
+}
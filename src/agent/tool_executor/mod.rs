@@ -118,10 +118,10 @@ impl ToolExecutor {
         registry: &ToolRegistry,
     ) -> AppResult<(ToolCall, ToolResult, String)> {
         let tool_name = call["tool"].as_str()
-            .ok_or_else(|| crate::error::AppError::Storage("Missing tool name".to_string()))?;
+            .ok_or_else(|| crate::error::AppError::InvalidParameters("Missing tool name".to_string()))?;
 
         let action = call["action"].as_str()
-            .ok_or_else(|| crate::error::AppError::Storage("Missing action".to_string()))?;
+            .ok_or_else(|| crate::error::AppError::InvalidParameters("Missing action".to_string()))?;
 
         let parameters = &call["parameters"];
 
@@ -159,7 +159,7 @@ impl ToolExecutor {
             let result_string = result?;
             Ok((tool_call, tool_result, result_string))
         } else {
-            Err(crate::error::AppError::Storage(format!("Unknown tool: {}", tool_name)))
+            Err(crate::error::AppError::ToolNotFound(tool_name.to_string()))
         }
     }
 }
\ No newline at end of file
@@ -47,11 +47,11 @@ impl Tool for ExampleTool {
             }
             "calculate" => {
                 let operation = parameters["operation"].as_str()
-                    .ok_or_else(|| crate::error::AppError::Storage("Missing operation".to_string()))?;
+                    .ok_or_else(|| crate::error::AppError::InvalidParameters("Missing operation".to_string()))?;
                 let a = parameters["a"].as_f64()
-                    .ok_or_else(|| crate::error::AppError::Storage("Missing or invalid number 'a'".to_string()))?;
+                    .ok_or_else(|| crate::error::AppError::InvalidParameters("Missing or invalid number 'a'".to_string()))?;
                 let b = parameters["b"].as_f64()
-                    .ok_or_else(|| crate::error::AppError::Storage("Missing or invalid number 'b'".to_string()))?;
+                    .ok_or_else(|| crate::error::AppError::InvalidParameters("Missing or invalid number 'b'".to_string()))?;
 
                 let result = match operation {
                     "add" => a + b,
@@ -59,16 +59,16 @@ impl Tool for ExampleTool {
                     "multiply" => a * b,
                     "divide" => {
                         if b == 0.0 {
-                            return Err(crate::error::AppError::Storage("Division by zero".to_string()));
+                            return Err(crate::error::AppError::InvalidParameters("Division by zero".to_string()));
                         }
                         a / b
                     }
-                    _ => return Err(crate::error::AppError::Storage(format!("Unknown operation: {}", operation))),
+                    _ => return Err(crate::error::AppError::InvalidParameters(format!("Unknown operation: {}", operation))),
                 };
 
                 Ok(format!("{} {} {} = {}", a, operation, b, result))
             }
-            _ => Err(crate::error::AppError::Storage(format!("Unknown action: {}", action)))
+            _ => Err(crate::error::AppError::InvalidParameters(format!("Unknown action: {}", action)))
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,128 @@
+// Tools provided by external MCP servers configured under `mcp_servers` in config.json.
+// Each remote tool becomes its own `Tool` named `<server>_<tool>` with a single `call` action.
+
+use async_trait::async_trait;
+use serde_json::Value;
+use std::sync::Arc;
+use crate::agent::tools::schema::parameters_from_json_schema;
+use crate::agent::tools::{ActionSchema, ParameterType, ReturnSchema, Tool, ToolAction, ToolCategory, ToolSchema};
+use crate::agent::ToolRegistry;
+use crate::config::Config;
+use crate::error::{AppError, AppResult};
+use crate::mcp::{client::NO_STATUS, protocol::tool_name, McpClient};
+
+pub struct McpTool {
+    client: Arc<McpClient>,
+    name: String,
+    remote_name: String,
+    description: String,
+    input_schema: Value,
+}
+
+impl McpTool {
+    pub fn new(client: Arc<McpClient>, definition: &Value) -> AppResult<Self> {
+        let remote_name = definition["name"].as_str()
+            .ok_or_else(|| AppError::Api {
+                status: NO_STATUS,
+                body: format!("MCP server '{}' listed a tool without a name", client.name()),
+            })?
+            .to_string();
+
+        Ok(Self {
+            name: tool_name(client.name(), &remote_name),
+            description: definition["description"].as_str().unwrap_or(&remote_name).to_string(),
+            input_schema: definition["inputSchema"].clone(),
+            remote_name,
+            client,
+        })
+    }
+}
+
+#[async_trait]
+impl Tool for McpTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::External
+    }
+
+    fn get_schema(&self) -> ToolSchema {
+        ToolSchema {
+            name: self.name.clone(),
+            description: format!("{} (via MCP server '{}')", self.description, self.client.name()),
+            category: ToolCategory::External,
+            actions: vec![ActionSchema {
+                name: "call".to_string(),
+                description: self.description.clone(),
+                parameters: parameters_from_json_schema(&self.input_schema),
+                returns: ReturnSchema {
+                    description: "Text content returned by the tool".to_string(),
+                    return_type: ParameterType::String { max_length: None },
+                    possible_errors: vec!["Tool error".to_string(), "Timeout".to_string()],
+                },
+            }],
+            examples: vec![],
+        }
+    }
+
+    fn actions(&self) -> Vec<ToolAction> {
+        let mut action = ToolAction::new("call", &self.description);
+        for param in parameters_from_json_schema(&self.input_schema) {
+            let param_type = match param.param_type {
+                ParameterType::Number { .. } | ParameterType::Integer { .. } => "number",
+                ParameterType::Boolean => "boolean",
+                ParameterType::Array { .. } => "array",
+                ParameterType::Object { .. } => "object",
+                _ => "string",
+            };
+            action = action.with_parameter(&param.name, &param.description, param.required, param_type);
+        }
+        vec![action]
+    }
+
+    async fn execute(&self, action: &str, parameters: &Value) -> AppResult<String> {
+        match action {
+            "call" => self.client.call_tool(&self.remote_name, parameters).await,
+            _ => Err(AppError::InvalidParameters(format!("Unknown action: {}", action)))
+        }
+    }
+
+    async fn shutdown(&self) -> AppResult<()> {
+        self.client.shutdown().await;
+        Ok(())
+    }
+}
+
+/// Spawns every configured server and registers its tools. A server that fails
+/// to start is reported and skipped so one broken entry doesn't disable the agent.
+pub async fn register_mcp_tools(registry: &mut ToolRegistry, config: &Config) {
+    for (name, server) in &config.mcp_servers {
+        if let Err(e) = register_server(registry, name, server).await {
+            eprintln!("⚠️  Skipping MCP server '{}': {}", name, e);
+        }
+    }
+}
+
+async fn register_server(registry: &mut ToolRegistry, name: &str, server: &crate::config::McpServerConfig) -> AppResult<()> {
+    let client = Arc::new(McpClient::spawn(name, server).await?);
+
+    let definitions = match client.list_tools().await {
+        Ok(definitions) => definitions,
+        Err(e) => {
+            client.shutdown().await;
+            return Err(e);
+        }
+    };
+
+    for definition in &definitions {
+        registry.register_tool(Arc::new(McpTool::new(client.clone(), definition)?));
+    }
+
+    Ok(())
+}
@@ -2,9 +2,21 @@
 // Each tool should implement the Tool trait and register itself
 
 use crate::agent::ToolRegistry;
+use crate::config::Config;
 use crate::error::AppResult;
+use crate::storage::Storage;
+use std::sync::Arc;
 
-pub fn register_all_external_tools(_registry: &mut ToolRegistry) -> AppResult<()> {
+pub mod mcp;
+pub mod plugin;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub async fn register_all_external_tools(
+    registry: &mut ToolRegistry,
+    config: &Config,
+    storage: Arc<dyn Storage>,
+) -> AppResult<()> {
     // External tools will be registered here automatically
     // This function is called during tool discovery
 
@@ -12,8 +24,17 @@ pub fn register_all_external_tools(_registry: &mut ToolRegistry) -> AppResult<()
     // let example_tool = Arc::new(example_external_tool::ExampleTool::new()?);
     // registry.register_tool(example_tool);
 
-    // For now, no external tools are registered by default
-    // Add your tools here following the pattern above
+    // Executable plugins dropped into the config `tools/` folder
+    plugin::register_plugin_tools(registry, &Config::plugins_dir()?, config).await;
+
+    // Sandboxed WASM tools, only allowed what their manifests grant
+    #[cfg(feature = "wasm")]
+    wasm::register_wasm_tools(registry, &Config::wasm_plugins_dir()?, storage);
+    #[cfg(not(feature = "wasm"))]
+    drop(storage);
+
+    // Tools from MCP servers configured by the user
+    mcp::register_mcp_tools(registry, config).await;
 
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,236 @@
+// Executable plugins discovered in the `tools/` config folder (`~/.config/aigenda/tools/`).
+//
+// Protocol:
+// - `plugin --schema` prints the plugin's `ToolSchema` as JSON
+// - `plugin` (no arguments) reads `{"action": ..., "parameters": {...}}` on stdin and
+//   prints `{"result": "..."}` or `{"error": "..."}` on stdout
+//
+// Plugins run with a cleared environment (only PATH/locale/temp plus their configured
+// `env`), a timeout, and their stderr is captured and reported when they fail.
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::time::timeout;
+use crate::agent::tools::{Tool, ToolAction, ToolCategory, ToolSchema};
+use crate::agent::ToolRegistry;
+use crate::config::{Config, PluginConfig};
+use crate::error::{AppError, AppResult};
+use crate::mcp::client::NO_STATUS;
+
+const DEFAULT_TIMEOUT_SECS: u64 = 10;
+const INHERITED_ENV: &[&str] = &["PATH", "LANG", "LC_ALL", "SYSTEMROOT", "TEMP", "TMP", "TMPDIR"];
+const MAX_STDERR_IN_ERRORS: usize = 2000;
+
+pub struct PluginTool {
+    path: PathBuf,
+    schema: ToolSchema,
+    timeout: Duration,
+    env: Vec<(String, String)>,
+}
+
+impl PluginTool {
+    /// Asks the executable for its schema
+    pub async fn load(path: &Path, config: &PluginConfig) -> AppResult<Self> {
+        let mut plugin = Self {
+            path: path.to_path_buf(),
+            schema: ToolSchema {
+                name: String::new(),
+                description: String::new(),
+                category: ToolCategory::External,
+                actions: Vec::new(),
+                examples: Vec::new(),
+            },
+            timeout: Duration::from_secs(config.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS)),
+            env: config.env.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+        };
+
+        let output = plugin.invoke(&["--schema"], None).await?;
+        plugin.schema = serde_json::from_slice(&output)
+            .map_err(|e| plugin_error(format!("Invalid schema from plugin {}: {}", path.display(), e)))?;
+
+        if plugin.schema.name.trim().is_empty() {
+            return Err(plugin_error(format!("Plugin {} has an empty tool name", path.display())));
+        }
+
+        Ok(plugin)
+    }
+
+    /// Runs the plugin once and returns its stdout
+    async fn invoke(&self, args: &[&str], input: Option<Vec<u8>>) -> AppResult<Vec<u8>> {
+        let mut command = Command::new(&self.path);
+        command
+            .args(args)
+            .env_clear()
+            .envs(INHERITED_ENV.iter().filter_map(|k| std::env::var(k).ok().map(|v| (*k, v))))
+            .envs(self.env.iter().map(|(k, v)| (k, v)))
+            .env("AIGENDA_PLUGIN", "1")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        let mut child = command
+            .spawn()
+            .map_err(|e| plugin_error(format!("Could not run plugin {}: {}", self.path.display(), e)))?;
+
+        // Feed stdin concurrently so a chatty plugin can't deadlock on a full stdout pipe
+        if let Some(mut stdin) = child.stdin.take() {
+            let input = input.unwrap_or_default();
+            tokio::spawn(async move {
+                let _ = stdin.write_all(&input).await;
+            });
+        }
+
+        // Dropping the timed-out future drops the child, which kills it
+        let output = timeout(self.timeout, child.wait_with_output())
+            .await
+            .map_err(|_| AppError::Network(format!(
+                "Plugin {} timed out after {}s", self.path.display(), self.timeout.as_secs()
+            )))??;
+
+        if !output.status.success() {
+            return Err(plugin_error(format!(
+                "Plugin {} failed ({}): {}",
+                self.path.display(),
+                output.status,
+                stderr_excerpt(&output.stderr)
+            )));
+        }
+
+        Ok(output.stdout)
+    }
+}
+
+#[async_trait]
+impl Tool for PluginTool {
+    fn name(&self) -> &str {
+        &self.schema.name
+    }
+
+    fn description(&self) -> &str {
+        &self.schema.description
+    }
+
+    fn category(&self) -> ToolCategory {
+        self.schema.category.clone()
+    }
+
+    fn get_schema(&self) -> ToolSchema {
+        self.schema.clone()
+    }
+
+    fn actions(&self) -> Vec<ToolAction> {
+        self.schema
+            .actions
+            .iter()
+            .map(|action| {
+                action.parameters.iter().fold(
+                    ToolAction::new(&action.name, &action.description),
+                    |tool_action, param| tool_action.with_parameter(
+                        &param.name,
+                        &param.description,
+                        param.required,
+                        param.param_type.to_json_schema()["type"].as_str().unwrap_or("string"),
+                    ),
+                )
+            })
+            .collect()
+    }
+
+    async fn execute(&self, action: &str, parameters: &Value) -> AppResult<String> {
+        if !self.schema.actions.iter().any(|a| a.name == action) {
+            return Err(AppError::InvalidParameters(format!("Unknown action: {}", action)));
+        }
+
+        let request = serde_json::to_vec(&json!({ "action": action, "parameters": parameters }))?;
+        let stdout = self.invoke(&[], Some(request)).await?;
+
+        let reply: Value = serde_json::from_slice(&stdout)
+            .map_err(|e| plugin_error(format!("Invalid reply from plugin {}: {}", self.schema.name, e)))?;
+
+        if let Some(error) = reply.get("error").filter(|e| !e.is_null()) {
+            return Err(plugin_error(error.as_str().map(str::to_string).unwrap_or_else(|| error.to_string())));
+        }
+
+        match reply.get("result") {
+            Some(Value::String(text)) => Ok(text.clone()),
+            Some(other) => Ok(other.to_string()),
+            None => Err(plugin_error(format!("Plugin {} replied without a result", self.schema.name))),
+        }
+    }
+}
+
+/// Loads every executable in `dir`. Plugins that fail to load, or that would shadow an
+/// already registered tool, are reported and skipped.
+pub async fn register_plugin_tools(registry: &mut ToolRegistry, dir: &Path, config: &Config) {
+    for path in discover_plugins(dir) {
+        let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default().to_string();
+        let plugin_config = config.plugins.get(&file_name).cloned().unwrap_or_default();
+        if plugin_config.disabled {
+            continue;
+        }
+
+        match PluginTool::load(&path, &plugin_config).await {
+            Ok(plugin) if registry.get_tool(plugin.name()).is_some() => {
+                eprintln!("⚠️  Skipping plugin {}: tool '{}' already exists", path.display(), plugin.name());
+            }
+            Ok(plugin) => registry.register_tool(Arc::new(plugin)),
+            Err(e) => eprintln!("⚠️  Skipping plugin {}: {}", path.display(), e),
+        }
+    }
+}
+
+/// Executable files in `dir`, sorted, ignoring hidden files
+pub fn discover_plugins(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut plugins: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_file())
+        .filter(|p| !p.file_name().and_then(|n| n.to_str()).unwrap_or(".").starts_with('.'))
+        .filter(|p| is_executable(p))
+        .collect();
+
+    plugins.sort();
+    plugins
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata().map(|m| m.permissions().mode() & 0o111 != 0).unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase()).as_deref(),
+        Some("exe" | "bat" | "cmd")
+    )
+}
+
+fn stderr_excerpt(stderr: &[u8]) -> String {
+    let text = String::from_utf8_lossy(stderr);
+    let text = text.trim();
+    if text.is_empty() {
+        return "no output on stderr".to_string();
+    }
+
+    // Keep the end, where the actual error usually is
+    let skip = text.chars().count().saturating_sub(MAX_STDERR_IN_ERRORS);
+    text.chars().skip(skip).collect()
+}
+
+/// A plugin that couldn't run or answered badly, reported like a failing API
+fn plugin_error(body: String) -> AppError {
+    AppError::Api { status: NO_STATUS, body }
+}
@@ -0,0 +1,382 @@
+// Sandboxed WASM tools from the `wasm/` config folder (`~/.config/aigenda/wasm/`).
+//
+// A plugin is `<name>.wasm` plus an optional `<name>.manifest.json`. The module gets no WASI,
+// so it has no filesystem, network, clock or environment access. The only host functions it
+// can import are the ones its manifest grants:
+//
+// - `aigenda.log(ptr, len)`: always available, collected and shown when the tool fails
+// - `aigenda.notes_read(ptr, len) -> i64`: `notes:read`, takes a `YYYY-MM-DD` date, replies with the `DayLog`
+// - `aigenda.notes_write(ptr, len) -> i64`: `notes:write`, takes a `DayLog` JSON and saves it
+//
+// Host replies use the same `{"result": ...}` / `{"error": ...}` shape as tool replies.
+//
+// The module must export `memory`, `alloc(len) -> ptr`, `schema() -> i64` returning its
+// `ToolSchema` JSON, and `execute(ptr, len) -> i64` which receives
+// `{"action": ..., "parameters": {...}}` and returns `{"result": "..."}` or `{"error": "..."}`.
+// Returned `i64`s pack a buffer as `(ptr << 32) | len`.
+
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use wasmi::{Caller, Config as EngineConfig, Engine, Extern, Instance, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+use crate::agent::tools::{Tool, ToolAction, ToolCategory, ToolSchema};
+use crate::agent::ToolRegistry;
+use crate::error::{AppError, AppResult};
+use crate::mcp::client::NO_STATUS;
+use crate::models::DayLog;
+use crate::storage::Storage;
+
+const HOST_MODULE: &str = "aigenda";
+const MANIFEST_SUFFIX: &str = ".manifest.json";
+const DEFAULT_FUEL: u64 = 100_000_000;
+const DEFAULT_MAX_MEMORY_MB: usize = 32;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capability {
+    #[serde(rename = "notes:read")]
+    NotesRead,
+    #[serde(rename = "notes:write")]
+    NotesWrite,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct WasmManifest {
+    /// Everything not listed here is denied
+    pub capabilities: Vec<Capability>,
+    /// Instruction budget per call (default 100M)
+    pub fuel: Option<u64>,
+    /// Linear memory cap in MiB (default 32)
+    pub max_memory_mb: Option<usize>,
+}
+
+impl WasmManifest {
+    pub fn load(path: &Path) -> AppResult<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)
+            .map_err(|e| AppError::Config(format!("Could not read manifest {}: {}", path.display(), e)))?;
+
+        serde_json::from_str(&content)
+            .map_err(|e| AppError::Config(format!("Could not parse manifest {}: {}", path.display(), e)))
+    }
+
+    fn grants(&self, capability: Capability) -> bool {
+        self.capabilities.contains(&capability)
+    }
+}
+
+struct HostState {
+    storage: Arc<dyn Storage>,
+    limits: StoreLimits,
+    log: Vec<String>,
+}
+
+/// A compiled module plus what it is allowed to do. Every call runs in a fresh
+/// instance, so no state leaks between calls.
+pub struct WasmTool {
+    engine: Engine,
+    module: Module,
+    manifest: WasmManifest,
+    storage: Arc<dyn Storage>,
+    schema: ToolSchema,
+}
+
+impl WasmTool {
+    pub fn load(wasm_path: &Path, manifest: WasmManifest, storage: Arc<dyn Storage>) -> AppResult<Self> {
+        let bytes = fs::read(wasm_path)
+            .map_err(|e| AppError::Config(format!("Could not read {}: {}", wasm_path.display(), e)))?;
+        Self::from_bytes(&bytes, manifest, storage)
+    }
+
+    pub fn from_bytes(bytes: &[u8], manifest: WasmManifest, storage: Arc<dyn Storage>) -> AppResult<Self> {
+        let mut config = EngineConfig::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, bytes).map_err(wasm_error)?;
+
+        let mut tool = Self {
+            engine,
+            module,
+            manifest,
+            storage,
+            schema: ToolSchema {
+                name: String::new(),
+                description: String::new(),
+                category: ToolCategory::External,
+                actions: Vec::new(),
+                examples: Vec::new(),
+            },
+        };
+
+        let (mut store, instance) = tool.instantiate()?;
+        let schema_fn = instance.get_typed_func::<(), i64>(&store, "schema").map_err(wasm_error)?;
+        let packed = schema_fn.call(&mut store, ()).map_err(|e| call_error(&store, e))?;
+        let schema_json = read_packed(&instance, &store, packed)?;
+
+        tool.schema = serde_json::from_slice(&schema_json)
+            .map_err(|e| wasm_error(format!("Invalid schema from WASM tool: {}", e)))?;
+
+        if tool.schema.name.trim().is_empty() {
+            return Err(wasm_error("WASM tool has an empty name"));
+        }
+
+        Ok(tool)
+    }
+
+    fn instantiate(&self) -> AppResult<(Store<HostState>, Instance)> {
+        let max_memory = self.manifest.max_memory_mb.unwrap_or(DEFAULT_MAX_MEMORY_MB) * 1024 * 1024;
+        let mut store = Store::new(&self.engine, HostState {
+            storage: self.storage.clone(),
+            limits: StoreLimitsBuilder::new().memory_size(max_memory).build(),
+            log: Vec::new(),
+        });
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(self.manifest.fuel.unwrap_or(DEFAULT_FUEL)).map_err(wasm_error)?;
+
+        let linker = self.linker()?;
+        let instance = linker
+            .instantiate(&mut store, &self.module)
+            .map_err(|e| AppError::PermissionDenied(format!(
+                "WASM tool needs a capability its manifest does not grant, or imports something unknown: {}", e
+            )))?
+            .start(&mut store)
+            .map_err(wasm_error)?;
+
+        Ok((store, instance))
+    }
+
+    /// Host functions, limited to the granted capabilities
+    fn linker(&self) -> AppResult<Linker<HostState>> {
+        let mut linker = Linker::new(&self.engine);
+
+        linker
+            .func_wrap(HOST_MODULE, "log", |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+                let message = read_caller_memory(&caller, ptr, len)?;
+                caller.data_mut().log.push(String::from_utf8_lossy(&message).into_owned());
+                Ok(())
+            })
+            .map_err(wasm_error)?;
+
+        if self.manifest.grants(Capability::NotesRead) {
+            linker
+                .func_wrap(HOST_MODULE, "notes_read", |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+                    let input = read_caller_memory(&caller, ptr, len)?;
+                    let reply = host_notes_read(caller.data().storage.as_ref(), &input);
+                    write_caller_reply(&mut caller, &reply)
+                })
+                .map_err(wasm_error)?;
+        }
+
+        if self.manifest.grants(Capability::NotesWrite) {
+            linker
+                .func_wrap(HOST_MODULE, "notes_write", |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+                    let input = read_caller_memory(&caller, ptr, len)?;
+                    let reply = host_notes_write(caller.data().storage.as_ref(), &input);
+                    write_caller_reply(&mut caller, &reply)
+                })
+                .map_err(wasm_error)?;
+        }
+
+        Ok(linker)
+    }
+
+    fn run(&self, action: &str, parameters: &Value) -> AppResult<String> {
+        let (mut store, instance) = self.instantiate()?;
+        let request = serde_json::to_vec(&json!({ "action": action, "parameters": parameters }))?;
+
+        let (ptr, len) = write_guest(&instance, &mut store, &request)?;
+        let execute = instance.get_typed_func::<(i32, i32), i64>(&store, "execute").map_err(wasm_error)?;
+        let packed = execute.call(&mut store, (ptr, len)).map_err(|e| call_error(&store, e))?;
+
+        let reply: Value = serde_json::from_slice(&read_packed(&instance, &store, packed)?)
+            .map_err(|e| wasm_error(format!("Invalid reply from WASM tool {}: {}", self.schema.name, e)))?;
+
+        if let Some(error) = reply.get("error").filter(|e| !e.is_null()) {
+            return Err(AppError::Api {
+                status: NO_STATUS,
+                body: error.as_str().map(str::to_string).unwrap_or_else(|| error.to_string()),
+            });
+        }
+
+        match reply.get("result") {
+            Some(Value::String(text)) => Ok(text.clone()),
+            Some(other) => Ok(other.to_string()),
+            None => Err(wasm_error(format!("WASM tool {} replied without a result", self.schema.name))),
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for WasmTool {
+    fn name(&self) -> &str {
+        &self.schema.name
+    }
+
+    fn description(&self) -> &str {
+        &self.schema.description
+    }
+
+    fn category(&self) -> ToolCategory {
+        self.schema.category.clone()
+    }
+
+    fn get_schema(&self) -> ToolSchema {
+        self.schema.clone()
+    }
+
+    fn actions(&self) -> Vec<ToolAction> {
+        self.schema
+            .actions
+            .iter()
+            .map(|action| ToolAction::new(&action.name, &action.description))
+            .collect()
+    }
+
+    async fn execute(&self, action: &str, parameters: &Value) -> AppResult<String> {
+        if !self.schema.actions.iter().any(|a| a.name == action) {
+            return Err(AppError::InvalidParameters(format!("Unknown action: {}", action)));
+        }
+
+        // Fuel bounds the run time, so running inline is safe
+        self.run(action, parameters)
+    }
+}
+
+/// Loads every `*.wasm` in `dir` with its manifest; failures are reported and skipped
+pub fn register_wasm_tools(registry: &mut ToolRegistry, dir: &Path, storage: Arc<dyn Storage>) {
+    for (wasm_path, manifest_path) in discover_wasm_tools(dir) {
+        let loaded = WasmManifest::load(&manifest_path)
+            .and_then(|manifest| WasmTool::load(&wasm_path, manifest, storage.clone()));
+
+        match loaded {
+            Ok(tool) if registry.get_tool(tool.name()).is_some() => {
+                eprintln!("⚠️  Skipping WASM tool {}: tool '{}' already exists", wasm_path.display(), tool.name());
+            }
+            Ok(tool) => registry.register_tool(Arc::new(tool)),
+            Err(e) => eprintln!("⚠️  Skipping WASM tool {}: {}", wasm_path.display(), e),
+        }
+    }
+}
+
+/// `(module, manifest)` path pairs, sorted by module name
+pub fn discover_wasm_tools(dir: &Path) -> Vec<(PathBuf, PathBuf)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut tools: Vec<(PathBuf, PathBuf)> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("wasm"))
+        .map(|p| {
+            let stem = p.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+            let manifest = p.with_file_name(format!("{}{}", stem, MANIFEST_SUFFIX));
+            (p, manifest)
+        })
+        .collect();
+
+    tools.sort();
+    tools
+}
+
+fn host_notes_read(storage: &dyn Storage, input: &[u8]) -> Value {
+    let date = String::from_utf8_lossy(input);
+    match NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d") {
+        Ok(date) => match storage.load_day(date) {
+            Ok(day) => json!({ "result": day }),
+            Err(e) => json!({ "error": e.to_string() }),
+        },
+        Err(e) => json!({ "error": format!("Invalid date: {}", e) }),
+    }
+}
+
+fn host_notes_write(storage: &dyn Storage, input: &[u8]) -> Value {
+    match serde_json::from_slice::<DayLog>(input) {
+        Ok(day) => match storage.save_day(&day) {
+            Ok(()) => json!({ "result": "saved" }),
+            Err(e) => json!({ "error": e.to_string() }),
+        },
+        Err(e) => json!({ "error": format!("Invalid DayLog: {}", e) }),
+    }
+}
+
+fn read_caller_memory(caller: &Caller<'_, HostState>, ptr: i32, len: i32) -> Result<Vec<u8>, wasmi::Error> {
+    let memory = match caller.get_export("memory") {
+        Some(Extern::Memory(memory)) => memory,
+        _ => return Err(wasmi::Error::new("module does not export memory")),
+    };
+
+    let mut buffer = vec![0u8; len.max(0) as usize];
+    memory
+        .read(caller, ptr as u32 as usize, &mut buffer)
+        .map_err(|e| wasmi::Error::new(e.to_string()))?;
+    Ok(buffer)
+}
+
+/// Copies a host reply into guest memory through its `alloc` export
+fn write_caller_reply(caller: &mut Caller<'_, HostState>, reply: &Value) -> Result<i64, wasmi::Error> {
+    let bytes = reply.to_string().into_bytes();
+
+    let alloc = match caller.get_export("alloc") {
+        Some(Extern::Func(func)) => func.typed::<i32, i32>(&*caller)?,
+        _ => return Err(wasmi::Error::new("module does not export alloc")),
+    };
+    let ptr = alloc.call(&mut *caller, bytes.len() as i32)?;
+
+    let memory = match caller.get_export("memory") {
+        Some(Extern::Memory(memory)) => memory,
+        _ => return Err(wasmi::Error::new("module does not export memory")),
+    };
+    memory
+        .write(&mut *caller, ptr as u32 as usize, &bytes)
+        .map_err(|e| wasmi::Error::new(e.to_string()))?;
+
+    Ok(pack(ptr, bytes.len() as i32))
+}
+
+fn write_guest(instance: &Instance, store: &mut Store<HostState>, bytes: &[u8]) -> AppResult<(i32, i32)> {
+    let alloc = instance.get_typed_func::<i32, i32>(&*store, "alloc").map_err(wasm_error)?;
+    let ptr = alloc.call(&mut *store, bytes.len() as i32).map_err(|e| call_error(store, e))?;
+
+    let memory = instance.get_memory(&*store, "memory")
+        .ok_or_else(|| AppError::Config("WASM tool does not export memory".to_string()))?;
+    memory.write(&mut *store, ptr as u32 as usize, bytes).map_err(wasm_error)?;
+
+    Ok((ptr, bytes.len() as i32))
+}
+
+fn read_packed(instance: &Instance, store: &Store<HostState>, packed: i64) -> AppResult<Vec<u8>> {
+    let ptr = (packed as u64 >> 32) as usize;
+    let len = (packed as u64 & 0xffff_ffff) as usize;
+
+    let memory = instance.get_memory(store, "memory")
+        .ok_or_else(|| AppError::Config("WASM tool does not export memory".to_string()))?;
+    let mut buffer = vec![0u8; len];
+    memory.read(store, ptr, &mut buffer).map_err(wasm_error)?;
+    Ok(buffer)
+}
+
+fn pack(ptr: i32, len: i32) -> i64 {
+    (((ptr as u32 as u64) << 32) | len as u32 as u64) as i64
+}
+
+fn wasm_error(error: impl std::fmt::Display) -> AppError {
+    AppError::Api { status: NO_STATUS, body: format!("WASM: {}", error) }
+}
+
+/// Trap or fuel exhaustion, with whatever the module logged before failing
+fn call_error(store: &Store<HostState>, error: wasmi::Error) -> AppError {
+    let log = &store.data().log;
+    if log.is_empty() {
+        wasm_error(error)
+    } else {
+        AppError::Api { status: NO_STATUS, body: format!("WASM: {} (log: {})", error, log.join(" | ")) }
+    }
+}
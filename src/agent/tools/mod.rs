@@ -21,6 +21,11 @@ pub trait Tool: Send + Sync {
     fn actions(&self) -> Vec<ToolAction>;
 
     async fn execute(&self, action: &str, parameters: &Value) -> AppResult<String>;
+
+    /// Releases external resources (child processes, connections); called once when the agent is done
+    async fn shutdown(&self) -> AppResult<()> {
+        Ok(())
+    }
 }
 
 // Enhanced trait for tools with detailed schemas
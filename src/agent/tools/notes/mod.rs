@@ -1,28 +1,30 @@
 use async_trait::async_trait;
 use serde_json::Value;
-use crate::agent::tools::{Tool, ToolAction, ToolSchema, ToolCategory, ActionSchema, ParameterSchema, ParameterType, ReturnSchema, ToolExample};
+use crate::agent::tools::{Tool, ToolAction, ToolSchema, ToolCategory, ActionSchema, ParameterSchema, ParameterType, ReturnSchema, ToolExample, ValidationRule};
+use crate::dates::{self, parse_date, DateRange};
+use crate::embeddings::SemanticIndex;
 use crate::error::AppResult;
-use crate::storage::Storage;
-use crate::models::{Note, DayLog};
-use chrono::{NaiveDate, Utc};
+use crate::storage::{fs::FsStorage, Storage};
+use crate::models::{Note, DayLog, Editor, NoteRef, Priority, TaskStatus};
+use crate::{notes, tasks};
 use std::sync::Arc;
 
 pub struct NotesTool {
     storage: Arc<dyn Storage>,
+    semantic_index: SemanticIndex,
 }
 
 impl NotesTool {
-    pub fn new() -> AppResult<Self> {
-        let storage = Arc::new(crate::storage::fs::FsStorage::new()?);
-        Ok(Self { storage })
+    pub fn new(storage: Arc<dyn Storage>) -> AppResult<Self> {
+        let semantic_index = SemanticIndex::open(&FsStorage::default_data_dir()?)?;
+        Ok(Self { storage, semantic_index })
     }
 
     async fn create_note(&self, text: &str, date: Option<&str>) -> AppResult<String> {
         let target_date = if let Some(date_str) = date {
-            NaiveDate::parse_from_str(date_str, "%Y-%m-%d")
-                .map_err(|e| crate::error::AppError::ChronoParse(e))?
+            parse_date(date_str)?
         } else {
-            Utc::now().date_naive()
+            dates::today()
         };
 
         let note = Note::new(text.to_string());
@@ -38,8 +40,7 @@ impl NotesTool {
 
     async fn read_notes(&self, date: Option<&str>, limit: Option<u32>) -> AppResult<String> {
         if let Some(date_str) = date {
-            let target_date = NaiveDate::parse_from_str(date_str, "%Y-%m-%d")
-                .map_err(|e| crate::error::AppError::ChronoParse(e))?;
+            let target_date = parse_date(date_str)?;
 
             let day_log = self.storage.load_day(target_date)?;
             let notes = day_log.notes();
@@ -64,17 +65,16 @@ impl NotesTool {
                 Ok(result)
             }
         } else {
-            // List recent notes from multiple days
-            let mut days = self.storage.iter_days()?;
-            days.reverse(); // Most recent first
+            // List recent notes, loading days newest first only until the limit is reached
             let mut result = String::from("Recent notes:\n");
             let mut count = 0;
             let max_count = limit.unwrap_or(10);
 
-            for day_log in days {
+            for day_log in self.storage.days()?.rev() {
                 if count >= max_count {
                     break;
                 }
+                let day_log = day_log?;
 
                 for note in day_log.notes().iter().rev() {
                     result.push_str(&format!("[{}] {}\n",
@@ -96,38 +96,115 @@ impl NotesTool {
         }
     }
 
-    async fn update_note(&self, date: &str, index: u32, new_text: &str) -> AppResult<String> {
-        let target_date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
-            .map_err(|e| crate::error::AppError::ChronoParse(e))?;
+    /// Notes of every day between `from` and `to` (today when omitted), oldest first
+    async fn read_range(&self, from: &str, to: Option<&str>, limit: Option<u32>) -> AppResult<String> {
+        let end = match to {
+            Some(to) => parse_date(to)?,
+            None => dates::today(),
+        };
+        let range = DateRange::new(parse_date(from)?, end)?;
+        let max_count = limit.unwrap_or(100) as usize;
 
-        let mut day_log = self.storage.load_day(target_date)?;
+        let mut result = format!("Notes from {} to {}:\n", range.start, range.end);
+        let mut count = 0;
+        for day_log in self.storage.days_between(range)? {
+            let day_log = day_log?;
+            for (i, note) in day_log.notes().iter().enumerate() {
+                if count >= max_count {
+                    return Ok(result);
+                }
+                result.push_str(&format!("[{}#{}] {} {}\n",
+                    day_log.date,
+                    i + 1,
+                    note.when().format("%H:%M"),
+                    note.text()
+                ));
+                count += 1;
+            }
+        }
 
-        if let Some(note) = day_log.notes_mut().get_mut(index as usize) {
-            *note = Note::new(new_text.to_string());
-            self.storage.save_day(&day_log)?;
-            Ok(format!("Note {} updated successfully for {}", index + 1, target_date))
+        if count == 0 {
+            Ok(format!("No notes found from {} to {}", range.start, range.end))
         } else {
-            Err(crate::error::AppError::Storage(
-                format!("Note {} not found for {}", index + 1, target_date)
-            ))
+            Ok(result)
         }
     }
 
+    async fn update_note(&self, date: &str, index: u32, new_text: &str) -> AppResult<String> {
+        let note_ref = NoteRef::new(parse_date(date)?, index as usize);
+        notes::amend_note(self.storage.as_ref(), note_ref, new_text, Editor::Agent)?;
+        Ok(format!("Note {} updated successfully for {}", note_ref.index, note_ref.date))
+    }
+
     async fn delete_note(&self, date: &str, index: u32) -> AppResult<String> {
-        let target_date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
-            .map_err(|e| crate::error::AppError::ChronoParse(e))?;
+        let note_ref = NoteRef::new(parse_date(date)?, index as usize);
+        notes::remove_note(self.storage.as_ref(), note_ref)?;
+        Ok(format!("Note {} deleted successfully from {}", note_ref.index, note_ref.date))
+    }
 
-        let mut day_log = self.storage.load_day(target_date)?;
+    async fn move_note(&self, date: &str, index: u32, to: Option<&str>, position: Option<u32>) -> AppResult<String> {
+        let from = NoteRef::new(parse_date(date)?, index as usize);
+        let to_date = to.map(parse_date).transpose()?.unwrap_or(from.date);
+        let moved = notes::move_note(self.storage.as_ref(), from, to_date, position.map(|p| p as usize))?;
+        Ok(format!("Note {} moved to {}", from, moved))
+    }
 
-        if (index as usize) < day_log.notes().len() {
-            day_log.notes_mut().remove(index as usize);
-            self.storage.save_day(&day_log)?;
-            Ok(format!("Note {} deleted successfully from {}", index + 1, target_date))
-        } else {
-            Err(crate::error::AppError::Storage(
-                format!("Note {} not found for {}", index + 1, target_date)
-            ))
+    async fn add_task(&self, text: &str, due: Option<&str>, priority: Option<&str>) -> AppResult<String> {
+        let due = due.map(parse_date).transpose()?;
+        let priority = priority
+            .map(|p| match p.to_lowercase().as_str() {
+                "low" => Ok(Priority::Low),
+                "medium" => Ok(Priority::Medium),
+                "high" => Ok(Priority::High),
+                other => Err(crate::error::AppError::InvalidParameters(format!("Unknown priority '{}': use low, medium or high", other))),
+            })
+            .transpose()?;
+
+        let task = tasks::add_task(self.storage.as_ref(), dates::today(), text, due, priority)?;
+        Ok(format!("Task added: {}", task.line()))
+    }
+
+    async fn complete_task(&self, query: &str, status: Option<&str>) -> AppResult<String> {
+        let status = match status.unwrap_or("done") {
+            "done" => TaskStatus::Done,
+            "cancelled" | "canceled" => TaskStatus::Cancelled,
+            "open" => TaskStatus::Open,
+            other => return Err(crate::error::AppError::InvalidParameters(format!("Unknown status '{}': use done, cancelled or open", other))),
+        };
+
+        let note_ref = tasks::resolve_task(self.storage.as_ref(), query)?;
+        let task = tasks::set_status(self.storage.as_ref(), note_ref, status)?;
+        Ok(format!("Task updated: {}", task.line()))
+    }
+
+    async fn list_tasks(&self, include_closed: bool) -> AppResult<String> {
+        let mut list = tasks::list_tasks(self.storage.as_ref(), include_closed)?;
+        tasks::sort_by_urgency(&mut list);
+
+        if list.is_empty() {
+            return Ok("No open tasks".to_string());
         }
+
+        let mut result = "Tasks (most urgent first):\n".to_string();
+        for task in list {
+            result.push_str(&format!("{}\n", task.line()));
+        }
+        Ok(result)
+    }
+
+    async fn semantic_search(&self, query: &str, limit: Option<u32>) -> AppResult<String> {
+        let limit = limit.unwrap_or(5) as usize;
+        let hits = self.semantic_index.search_text(self.storage.as_ref(), query, limit).await?;
+
+        if hits.is_empty() {
+            return Ok("No similar notes found".to_string());
+        }
+
+        let mut result = format!("Notes most similar to \"{}\":\n", query);
+        for hit in hits {
+            result.push_str(&format!("[{}] (score {:.2}) {}\n", hit.note, hit.score, hit.text));
+        }
+        Ok(result)
     }
 }
 
@@ -165,7 +242,7 @@ impl Tool for NotesTool {
                         },
                         ParameterSchema {
                             name: "date".to_string(),
-                            description: "Date for the note in YYYY-MM-DD format".to_string(),
+                            description: "Date for the note: YYYY-MM-DD or an expression like 'yesterday' or 'last monday'".to_string(),
                             param_type: ParameterType::Date,
                             required: false,
                             default_value: Some(serde_json::Value::String("today".to_string())),
@@ -184,7 +261,23 @@ impl Tool for NotesTool {
                     parameters: vec![
                         ParameterSchema {
                             name: "date".to_string(),
-                            description: "Date in YYYY-MM-DD format to read notes from".to_string(),
+                            description: "Day to read notes from: YYYY-MM-DD or an expression like 'yesterday' or '2 weeks ago'".to_string(),
+                            param_type: ParameterType::Date,
+                            required: false,
+                            default_value: None,
+                            validation: None,
+                        },
+                        ParameterSchema {
+                            name: "from".to_string(),
+                            description: "First day of a range to read, e.g. '2025-01-01', 'last monday' or '2 weeks ago'".to_string(),
+                            param_type: ParameterType::Date,
+                            required: false,
+                            default_value: None,
+                            validation: None,
+                        },
+                        ParameterSchema {
+                            name: "to".to_string(),
+                            description: "Last day of the range started with 'from' (defaults to today)".to_string(),
                             param_type: ParameterType::Date,
                             required: false,
                             default_value: None,
@@ -207,11 +300,11 @@ impl Tool for NotesTool {
                 },
                 ActionSchema {
                     name: "update".to_string(),
-                    description: "Update an existing note by its position/index".to_string(),
+                    description: "Replace the text of an existing note by its position/index; its original time is kept".to_string(),
                     parameters: vec![
                         ParameterSchema {
                             name: "date".to_string(),
-                            description: "Date of the note in YYYY-MM-DD format".to_string(),
+                            description: "Date of the note: YYYY-MM-DD or an expression like 'yesterday'".to_string(),
                             param_type: ParameterType::Date,
                             required: true,
                             default_value: None,
@@ -242,11 +335,11 @@ impl Tool for NotesTool {
                 },
                 ActionSchema {
                     name: "delete".to_string(),
-                    description: "Delete a specific note by its position/index".to_string(),
+                    description: "Delete a specific note by its position/index; it goes to the trash and can be restored with `aigenda trash restore`".to_string(),
                     parameters: vec![
                         ParameterSchema {
                             name: "date".to_string(),
-                            description: "Date of the note in YYYY-MM-DD format".to_string(),
+                            description: "Date of the note: YYYY-MM-DD or an expression like 'yesterday'".to_string(),
                             param_type: ParameterType::Date,
                             required: true,
                             default_value: None,
@@ -267,6 +360,165 @@ impl Tool for NotesTool {
                         possible_errors: vec!["Note not found".to_string(), "Invalid date format".to_string(), "Invalid index".to_string()],
                     },
                 },
+                ActionSchema {
+                    name: "move".to_string(),
+                    description: "Move a note to another date and/or position, keeping its time".to_string(),
+                    parameters: vec![
+                        ParameterSchema {
+                            name: "date".to_string(),
+                            description: "Date of the note: YYYY-MM-DD or an expression like 'yesterday'".to_string(),
+                            param_type: ParameterType::Date,
+                            required: true,
+                            default_value: None,
+                            validation: None,
+                        },
+                        ParameterSchema {
+                            name: "index".to_string(),
+                            description: "Position of the note to move (1-based index)".to_string(),
+                            param_type: ParameterType::Integer { min: Some(1), max: None },
+                            required: true,
+                            default_value: None,
+                            validation: None,
+                        },
+                        ParameterSchema {
+                            name: "to".to_string(),
+                            description: "Date to move the note to (defaults to the same day)".to_string(),
+                            param_type: ParameterType::Date,
+                            required: false,
+                            default_value: None,
+                            validation: None,
+                        },
+                        ParameterSchema {
+                            name: "position".to_string(),
+                            description: "1-based position on the target day (defaults to the end)".to_string(),
+                            param_type: ParameterType::Integer { min: Some(1), max: None },
+                            required: false,
+                            default_value: None,
+                            validation: None,
+                        },
+                    ],
+                    returns: ReturnSchema {
+                        description: "The note's new YYYY-MM-DD#N id".to_string(),
+                        return_type: ParameterType::String { max_length: None },
+                        possible_errors: vec!["Note not found".to_string(), "Position out of range".to_string()],
+                    },
+                },
+                ActionSchema {
+                    name: "add_task".to_string(),
+                    description: "Add an open task (to-do) to today's log, optionally with a due date and priority".to_string(),
+                    parameters: vec![
+                        ParameterSchema {
+                            name: "text".to_string(),
+                            description: "What needs doing".to_string(),
+                            param_type: ParameterType::String { max_length: Some(5000) },
+                            required: true,
+                            default_value: None,
+                            validation: None,
+                        },
+                        ParameterSchema {
+                            name: "due".to_string(),
+                            description: "Due date: YYYY-MM-DD or an expression like 'friday' or 'in 2 weeks'".to_string(),
+                            param_type: ParameterType::Date,
+                            required: false,
+                            default_value: None,
+                            validation: None,
+                        },
+                        ParameterSchema {
+                            name: "priority".to_string(),
+                            description: "Priority of the task".to_string(),
+                            param_type: ParameterType::String { max_length: Some(10) },
+                            required: false,
+                            default_value: None,
+                            validation: Some(ValidationRule {
+                                pattern: None,
+                                enum_values: Some(vec!["low".into(), "medium".into(), "high".into()]),
+                                custom: None,
+                            }),
+                        },
+                    ],
+                    returns: ReturnSchema {
+                        description: "The new task with its YYYY-MM-DD#N id".to_string(),
+                        return_type: ParameterType::String { max_length: None },
+                        possible_errors: vec!["Invalid date format".to_string(), "Unknown priority".to_string()],
+                    },
+                },
+                ActionSchema {
+                    name: "complete_task".to_string(),
+                    description: "Mark a task done (or cancelled, or open again), found by id or by some of its text".to_string(),
+                    parameters: vec![
+                        ParameterSchema {
+                            name: "task".to_string(),
+                            description: "Task id (YYYY-MM-DD#N) or text matching exactly one open task, e.g. 'deploy'".to_string(),
+                            param_type: ParameterType::String { max_length: Some(1000) },
+                            required: true,
+                            default_value: None,
+                            validation: None,
+                        },
+                        ParameterSchema {
+                            name: "status".to_string(),
+                            description: "New status".to_string(),
+                            param_type: ParameterType::String { max_length: Some(10) },
+                            required: false,
+                            default_value: Some(serde_json::Value::String("done".to_string())),
+                            validation: Some(ValidationRule {
+                                pattern: None,
+                                enum_values: Some(vec!["done".into(), "cancelled".into(), "open".into()]),
+                                custom: None,
+                            }),
+                        },
+                    ],
+                    returns: ReturnSchema {
+                        description: "The updated task".to_string(),
+                        return_type: ParameterType::String { max_length: None },
+                        possible_errors: vec!["No open task matches".to_string(), "Several open tasks match".to_string()],
+                    },
+                },
+                ActionSchema {
+                    name: "list_tasks".to_string(),
+                    description: "List open tasks across all days, most urgent first".to_string(),
+                    parameters: vec![
+                        ParameterSchema {
+                            name: "all".to_string(),
+                            description: "Include done and cancelled tasks".to_string(),
+                            param_type: ParameterType::Boolean,
+                            required: false,
+                            default_value: Some(serde_json::Value::Bool(false)),
+                            validation: None,
+                        },
+                    ],
+                    returns: ReturnSchema {
+                        description: "Tasks with ids, status, priority and due dates".to_string(),
+                        return_type: ParameterType::String { max_length: None },
+                        possible_errors: vec![],
+                    },
+                },
+                ActionSchema {
+                    name: "semantic_search".to_string(),
+                    description: "Find notes related to a query across all days, ranked by similarity rather than exact matches".to_string(),
+                    parameters: vec![
+                        ParameterSchema {
+                            name: "query".to_string(),
+                            description: "What the notes should be about".to_string(),
+                            param_type: ParameterType::String { max_length: Some(1000) },
+                            required: true,
+                            default_value: None,
+                            validation: None,
+                        },
+                        ParameterSchema {
+                            name: "limit".to_string(),
+                            description: "Maximum number of notes to return".to_string(),
+                            param_type: ParameterType::Integer { min: Some(1), max: Some(50) },
+                            required: false,
+                            default_value: Some(serde_json::Value::Number(serde_json::Number::from(5))),
+                            validation: None,
+                        },
+                    ],
+                    returns: ReturnSchema {
+                        description: "Matching notes as YYYY-MM-DD#N ids with similarity scores".to_string(),
+                        return_type: ParameterType::String { max_length: None },
+                        possible_errors: vec!["Embeddings provider unavailable".to_string()],
+                    },
+                },
             ],
             examples: vec![
                 ToolExample {
@@ -291,6 +543,16 @@ impl Tool for NotesTool {
                     }),
                     expected_result: "Notes for 2025-09-28:\n1. [20:45] Finished implementing AI tools".to_string(),
                 },
+                ToolExample {
+                    description: "Complete a task by its text".to_string(),
+                    user_request: "mark the deploy task done".to_string(),
+                    tool_call: serde_json::json!({
+                        "tool": "notes",
+                        "action": "complete_task",
+                        "parameters": { "task": "deploy" }
+                    }),
+                    expected_result: "Task updated: 2025-09-27#2 [x] Deploy the new release".to_string(),
+                },
                 ToolExample {
                     description: "Update a specific note".to_string(),
                     user_request: "update my first note from today".to_string(),
@@ -313,20 +575,44 @@ impl Tool for NotesTool {
         vec![
             ToolAction::new("create", "Add a new note")
                 .with_parameter("text", "The note content", true, "string")
-                .with_parameter("date", "Date in YYYY-MM-DD format (defaults to today)", false, "string"),
+                .with_parameter("date", "Date as YYYY-MM-DD or an expression like 'yesterday' (defaults to today)", false, "string"),
 
             ToolAction::new("read", "Read notes")
-                .with_parameter("date", "Date in YYYY-MM-DD format (optional, shows recent notes if omitted)", false, "string")
+                .with_parameter("date", "Date as YYYY-MM-DD or an expression like 'last monday' (optional, shows recent notes if omitted)", false, "string")
+                .with_parameter("from", "First day of a range, e.g. 'last monday' (optional)", false, "string")
+                .with_parameter("to", "Last day of the range (defaults to today)", false, "string")
                 .with_parameter("limit", "Maximum number of notes to show", false, "number"),
 
             ToolAction::new("update", "Update an existing note")
-                .with_parameter("date", "Date in YYYY-MM-DD format", true, "string")
+                .with_parameter("date", "Date as YYYY-MM-DD or an expression like 'yesterday'", true, "string")
                 .with_parameter("index", "Note index (1-based)", true, "number")
                 .with_parameter("text", "New note content", true, "string"),
 
             ToolAction::new("delete", "Delete a note")
-                .with_parameter("date", "Date in YYYY-MM-DD format", true, "string")
+                .with_parameter("date", "Date as YYYY-MM-DD or an expression like 'yesterday'", true, "string")
                 .with_parameter("index", "Note index (1-based)", true, "number"),
+
+            ToolAction::new("move", "Move a note to another date or position")
+                .with_parameter("date", "Date as YYYY-MM-DD or an expression like 'yesterday'", true, "string")
+                .with_parameter("index", "Note index (1-based)", true, "number")
+                .with_parameter("to", "Target date (defaults to the same day)", false, "string")
+                .with_parameter("position", "1-based position on the target day (defaults to the end)", false, "number"),
+
+            ToolAction::new("add_task", "Add an open task to today's log")
+                .with_parameter("text", "What needs doing", true, "string")
+                .with_parameter("due", "Due date, e.g. 'friday' (optional)", false, "string")
+                .with_parameter("priority", "low, medium or high (optional)", false, "string"),
+
+            ToolAction::new("complete_task", "Mark a task done, cancelled or open")
+                .with_parameter("task", "Task id (YYYY-MM-DD#N) or text matching one open task", true, "string")
+                .with_parameter("status", "done (default), cancelled or open", false, "string"),
+
+            ToolAction::new("list_tasks", "List tasks, most urgent first")
+                .with_parameter("all", "Include done and cancelled tasks", false, "boolean"),
+
+            ToolAction::new("semantic_search", "Find notes similar to a query")
+                .with_parameter("query", "What the notes should be about", true, "string")
+                .with_parameter("limit", "Maximum number of notes to show", false, "number"),
         ]
     }
 
@@ -334,32 +620,60 @@ impl Tool for NotesTool {
         match action {
             "create" => {
                 let text = parameters["text"].as_str()
-                    .ok_or_else(|| crate::error::AppError::Storage("Missing text parameter".to_string()))?;
+                    .ok_or_else(|| crate::error::AppError::InvalidParameters("Missing text parameter".to_string()))?;
                 let date = parameters["date"].as_str();
                 self.create_note(text, date).await
             }
             "read" => {
                 let date = parameters["date"].as_str();
                 let limit = parameters["limit"].as_u64().map(|l| l as u32);
-                self.read_notes(date, limit).await
+                match parameters["from"].as_str() {
+                    Some(from) => self.read_range(from, parameters["to"].as_str(), limit).await,
+                    None => self.read_notes(date, limit).await,
+                }
             }
             "update" => {
                 let date = parameters["date"].as_str()
-                    .ok_or_else(|| crate::error::AppError::Storage("Missing date parameter".to_string()))?;
+                    .ok_or_else(|| crate::error::AppError::InvalidParameters("Missing date parameter".to_string()))?;
                 let index = parameters["index"].as_u64()
-                    .ok_or_else(|| crate::error::AppError::Storage("Missing index parameter".to_string()))? as u32;
+                    .ok_or_else(|| crate::error::AppError::InvalidParameters("Missing index parameter".to_string()))? as u32;
                 let text = parameters["text"].as_str()
-                    .ok_or_else(|| crate::error::AppError::Storage("Missing text parameter".to_string()))?;
-                self.update_note(date, index.saturating_sub(1), text).await
+                    .ok_or_else(|| crate::error::AppError::InvalidParameters("Missing text parameter".to_string()))?;
+                self.update_note(date, index, text).await
             }
             "delete" => {
                 let date = parameters["date"].as_str()
-                    .ok_or_else(|| crate::error::AppError::Storage("Missing date parameter".to_string()))?;
+                    .ok_or_else(|| crate::error::AppError::InvalidParameters("Missing date parameter".to_string()))?;
+                let index = parameters["index"].as_u64()
+                    .ok_or_else(|| crate::error::AppError::InvalidParameters("Missing index parameter".to_string()))? as u32;
+                self.delete_note(date, index).await
+            }
+            "move" => {
+                let date = parameters["date"].as_str()
+                    .ok_or_else(|| crate::error::AppError::InvalidParameters("Missing date parameter".to_string()))?;
                 let index = parameters["index"].as_u64()
-                    .ok_or_else(|| crate::error::AppError::Storage("Missing index parameter".to_string()))? as u32;
-                self.delete_note(date, index.saturating_sub(1)).await
+                    .ok_or_else(|| crate::error::AppError::InvalidParameters("Missing index parameter".to_string()))? as u32;
+                let position = parameters["position"].as_u64().map(|p| p as u32);
+                self.move_note(date, index, parameters["to"].as_str(), position).await
+            }
+            "add_task" => {
+                let text = parameters["text"].as_str()
+                    .ok_or_else(|| crate::error::AppError::InvalidParameters("Missing text parameter".to_string()))?;
+                self.add_task(text, parameters["due"].as_str(), parameters["priority"].as_str()).await
+            }
+            "complete_task" => {
+                let task = parameters["task"].as_str()
+                    .ok_or_else(|| crate::error::AppError::InvalidParameters("Missing task parameter".to_string()))?;
+                self.complete_task(task, parameters["status"].as_str()).await
+            }
+            "list_tasks" => self.list_tasks(parameters["all"].as_bool().unwrap_or(false)).await,
+            "semantic_search" => {
+                let query = parameters["query"].as_str()
+                    .ok_or_else(|| crate::error::AppError::InvalidParameters("Missing query parameter".to_string()))?;
+                let limit = parameters["limit"].as_u64().map(|l| l as u32);
+                self.semantic_search(query, limit).await
             }
-            _ => Err(crate::error::AppError::Storage(format!("Unknown action: {}", action)))
+            _ => Err(crate::error::AppError::InvalidParameters(format!("Unknown action: {}", action)))
         }
     }
 }
\ No newline at end of file
@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Map, Value};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolSchema {
@@ -35,6 +35,10 @@ pub struct ParameterSchema {
     pub validation: Option<ValidationRule>,
 }
 
+/// What a `Date` parameter accepts; see `crate::dates`
+const DATE_EXPRESSIONS: &str =
+    "YYYY-MM-DD, today, yesterday, tomorrow, monday, last friday, next tuesday, last week, 3 days ago, in 2 days";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ParameterType {
     String { max_length: Option<usize> },
@@ -165,8 +169,168 @@ impl ToolSchema {
                 format!("array<{}>", self.format_parameter_type(item_type))
             }
             ParameterType::Object { properties: _ } => "object".to_string(),
-            ParameterType::Date => "date (YYYY-MM-DD)".to_string(),
+            ParameterType::Date => format!("date ({})", DATE_EXPRESSIONS),
             ParameterType::DateTime => "datetime (ISO 8601)".to_string(),
         }
     }
-}
\ No newline at end of file
+}
+
+impl ActionSchema {
+    /// JSON Schema object describing this action's parameters
+    pub fn input_schema(&self) -> Value {
+        parameters_to_json_schema(&self.parameters)
+    }
+}
+
+impl ParameterSchema {
+    pub fn to_json_schema(&self) -> Value {
+        let mut schema = self.param_type.to_json_schema();
+
+        if let Some(obj) = schema.as_object_mut() {
+            let description = match self.param_type {
+                ParameterType::Date => format!("{} (accepts {})", self.description, DATE_EXPRESSIONS),
+                _ => self.description.clone(),
+            };
+            obj.insert("description".to_string(), Value::String(description));
+            if let Some(default) = &self.default_value {
+                obj.insert("default".to_string(), default.clone());
+            }
+            if let Some(validation) = &self.validation {
+                if let Some(pattern) = &validation.pattern {
+                    obj.insert("pattern".to_string(), Value::String(pattern.clone()));
+                }
+                if let Some(enum_vals) = &validation.enum_values {
+                    obj.insert("enum".to_string(), Value::Array(enum_vals.clone()));
+                }
+            }
+        }
+
+        schema
+    }
+}
+
+impl ParameterType {
+    /// Best-effort mapping back from JSON Schema; unknown shapes become strings
+    pub fn from_json_schema(schema: &Value) -> Self {
+        let type_name = match &schema["type"] {
+            Value::String(t) => t.as_str(),
+            // e.g. ["string", "null"]: take the first non-null type
+            Value::Array(types) => types.iter()
+                .filter_map(|t| t.as_str())
+                .find(|t| *t != "null")
+                .unwrap_or("string"),
+            _ => "string",
+        };
+
+        match type_name {
+            "number" => ParameterType::Number {
+                min: schema["minimum"].as_f64(),
+                max: schema["maximum"].as_f64(),
+            },
+            "integer" => ParameterType::Integer {
+                min: schema["minimum"].as_i64(),
+                max: schema["maximum"].as_i64(),
+            },
+            "boolean" => ParameterType::Boolean,
+            "array" => ParameterType::Array {
+                item_type: Box::new(ParameterType::from_json_schema(&schema["items"])),
+            },
+            "object" => ParameterType::Object {
+                properties: parameters_from_json_schema(schema),
+            },
+            _ => match schema["format"].as_str() {
+                Some("date") => ParameterType::Date,
+                Some("date-time") => ParameterType::DateTime,
+                _ => ParameterType::String {
+                    max_length: schema["maxLength"].as_u64().map(|m| m as usize),
+                },
+            },
+        }
+    }
+
+    pub fn to_json_schema(&self) -> Value {
+        match self {
+            ParameterType::String { max_length } => match max_length {
+                Some(max) => json!({ "type": "string", "maxLength": max }),
+                None => json!({ "type": "string" }),
+            },
+            ParameterType::Number { min, max } => {
+                let mut schema = json!({ "type": "number" });
+                if let Some(min) = min {
+                    schema["minimum"] = json!(min);
+                }
+                if let Some(max) = max {
+                    schema["maximum"] = json!(max);
+                }
+                schema
+            }
+            ParameterType::Integer { min, max } => {
+                let mut schema = json!({ "type": "integer" });
+                if let Some(min) = min {
+                    schema["minimum"] = json!(min);
+                }
+                if let Some(max) = max {
+                    schema["maximum"] = json!(max);
+                }
+                schema
+            }
+            ParameterType::Boolean => json!({ "type": "boolean" }),
+            ParameterType::Array { item_type } => {
+                json!({ "type": "array", "items": item_type.to_json_schema() })
+            }
+            ParameterType::Object { properties } => parameters_to_json_schema(properties),
+            // Not `"format": "date"`: clients validating it would refuse expressions like "yesterday"
+            ParameterType::Date => json!({ "type": "string", "description": format!("A date: {}", DATE_EXPRESSIONS) }),
+            ParameterType::DateTime => json!({ "type": "string", "format": "date-time" }),
+        }
+    }
+}
+
+fn parameters_to_json_schema(parameters: &[ParameterSchema]) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+
+    for param in parameters {
+        properties.insert(param.name.clone(), param.to_json_schema());
+        if param.required {
+            required.push(Value::String(param.name.clone()));
+        }
+    }
+
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+/// Parameters of a JSON Schema `object` (as used by MCP `inputSchema`)
+pub fn parameters_from_json_schema(schema: &Value) -> Vec<ParameterSchema> {
+    let required: Vec<&str> = schema["required"]
+        .as_array()
+        .map(|r| r.iter().filter_map(|v| v.as_str()).collect())
+        .unwrap_or_default();
+
+    let Some(properties) = schema["properties"].as_object() else {
+        return Vec::new();
+    };
+
+    properties
+        .iter()
+        .map(|(name, property)| ParameterSchema {
+            name: name.clone(),
+            description: property["description"].as_str().unwrap_or_default().to_string(),
+            param_type: ParameterType::from_json_schema(property),
+            required: required.contains(&name.as_str()),
+            default_value: property.get("default").cloned(),
+            validation: match (property["pattern"].as_str(), property["enum"].as_array()) {
+                (None, None) => None,
+                (pattern, enum_values) => Some(ValidationRule {
+                    pattern: pattern.map(str::to_string),
+                    enum_values: enum_values.cloned(),
+                    custom: None,
+                }),
+            },
+        })
+        .collect()
+}
@@ -12,7 +12,7 @@ pub struct ClaudeClient {
 impl ClaudeClient {
     pub fn new() -> AppResult<Self> {
         let api_key = env::var("ANTHROPIC_API_KEY")
-            .map_err(|_| crate::error::AppError::Storage(
+            .map_err(|_| crate::error::AppError::Config(
                 "ANTHROPIC_API_KEY environment variable not set".to_string()
             ))?;
 
@@ -44,20 +44,21 @@ impl ClaudeClient {
             .json(&request_body)
             .send()
             .await
-            .map_err(|e| crate::error::AppError::Storage(format!("HTTP request failed: {}", e)))?;
+            .map_err(|e| crate::error::AppError::Network(format!("HTTP request failed: {}", e)))?;
 
         if !response.status().is_success() {
             let status = response.status();
             let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(crate::error::AppError::Storage(
-                format!("API request failed with status {}: {}", status, error_text)
-            ));
+            return Err(crate::error::AppError::Api {
+                status: status.as_u16(),
+                body: error_text,
+            });
         }
 
         let response_json: Value = response
             .json()
             .await
-            .map_err(|e| crate::error::AppError::Storage(format!("Failed to parse response: {}", e)))?;
+            .map_err(|e| crate::error::AppError::Network(format!("Failed to read response: {}", e)))?;
 
         // Extract the content from Claude's response
         if let Some(content) = response_json
@@ -69,9 +70,10 @@ impl ClaudeClient {
         {
             Ok(content.to_string())
         } else {
-            Err(crate::error::AppError::Storage(
-                "Unexpected response format from Claude API".to_string()
-            ))
+            Err(crate::error::AppError::Api {
+                status: 200,
+                body: format!("Unexpected response format from Claude API: {}", response_json),
+            })
         }
     }
 }
\ No newline at end of file
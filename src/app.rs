@@ -1,30 +1,147 @@
+use std::path::Path;
+use std::sync::Arc;
+
 use crate::{
-    cli::{Cli, Commands},
-    commands::{add, list, agent},
-    error::AppResult,
-    storage::{fs::FsStorage, Storage},
+    cli::{Cli, Commands, ListArgs, McpCommand},
+    clock::clock,
+    commands::{add, backup, doctor, edit, key, list, manage, migrate, notebooks, agent, mcp, similar, sync, template, todo, trash as trash_cmd},
+    config::Config,
+    error::{AppError, AppResult},
+    models::Editor,
+    storage::{self, fs::FsStorage, git::GitStorage, recurring::RecurringStorage, Storage, KEY_FILE},
+    trash,
 };
 
 pub struct App<S: Storage> {
-    store: S,
+    /// Shared with the agent's tools, so the store is opened (and unlocked) once
+    store: Arc<S>,
     cli: Cli,
 }
 
-impl<S: Storage> App<S> {
+impl<S: Storage + 'static> App<S> {
     pub fn new(store: S, cli: Cli) -> Self {
-        Self { store, cli }
+        Self { store: Arc::new(store), cli }
     }
 
     pub async fn run(&self) -> AppResult<()> {
+        let result = self.dispatch().await;
+
+        // One history entry per command or agent run, even when it failed halfway
+        let editor = match self.cli.command {
+            Commands::Ai { .. } | Commands::Mcp { .. } => Editor::Agent,
+            _ => Editor::Cli,
+        };
+        let checkpoint = self.store.checkpoint(editor);
+        result.and(checkpoint)
+    }
+
+    async fn dispatch(&self) -> AppResult<()> {
         match &self.cli.command {
-            Commands::Add { text } => add::run_add(&self.store, text.clone()),
-            Commands::List { all, date } => list::run_list(&self.store, *all, date.clone()),
-            Commands::Ai { prompt } => agent::handle_agent_command(prompt.clone()).await,
+            Commands::Add { text } => add::run_add(&self.store, text.clone(), self.cli.output),
+            Commands::List(args) => list::run_list(&self.store, args, self.cli.output),
+            Commands::Rm { notes } => manage::run_rm(&self.store, notes, self.cli.output),
+            Commands::Amend { note, text } => manage::run_amend(&self.store, note, text, self.cli.output),
+            Commands::Log { note } => manage::run_log(&self.store, note, self.cli.output),
+            Commands::Revert { note, to } => manage::run_revert(&self.store, note, *to, self.cli.output),
+            Commands::Mv { note, to, position } => {
+                manage::run_mv(&self.store, note, to.as_deref(), *position, self.cli.output)
+            }
+            Commands::Trash { command } => trash_cmd::run_trash(&self.store, command, self.cli.output),
+            Commands::Edit { date, note } => edit::run_edit(&self.store, date.as_deref(), *note, self.cli.output),
+            Commands::Today => list::run_list(&self.store, &ListArgs::default(), self.cli.output),
+            Commands::Template { command } => template::run_template(&self.store, command, self.cli.output),
+            Commands::Similar { query, limit, reindex } => {
+                similar::run_similar(&self.store, query.clone(), *limit, *reindex, self.cli.output).await
+            }
+            Commands::Sync => sync::run_sync(&self.store, self.cli.output),
+            Commands::Todo { command } => todo::run_todo(&self.store, command, self.cli.output),
+            Commands::Ai { prompt } => agent::handle_agent_command(self.store.clone(), prompt.clone(), self.cli.output).await,
+            Commands::Mcp { command } => match command {
+                McpCommand::Serve => mcp::run_serve(self.store.clone()).await,
+            },
+            // `run` handles these without opening a store
+            Commands::Key { .. }
+            | Commands::Doctor { .. }
+            | Commands::Notebooks { .. }
+            | Commands::Migrate { .. }
+            | Commands::Backup { .. }
+            | Commands::Restore { .. } => Err(AppError::InvalidParameters(
+                "This command doesn't run on an open store".to_string(),
+            )),
         }
     }
 }
 
-pub fn build_default(cli: Cli) -> AppResult<App<FsStorage>> {
-    let store = FsStorage::new()?;
+pub fn build_default(cli: Cli) -> AppResult<App<impl Storage>> {
+    let config = Config::load()?;
+    let store = open_store(&config)?;
+    let retention = config.trash_retention_days.unwrap_or(trash::DEFAULT_RETENTION_DAYS);
+    trash::purge_expired(&store, retention, clock().now())?;
+
     Ok(App::new(store, cli))
 }
+
+/// Runs a command in the chosen notebook. Key management, migrations, checks and backups work
+/// on the stores themselves, so they don't open the configured one, recurring items and all.
+pub async fn run(cli: Cli) -> AppResult<()> {
+    // Notebooks can be managed even when the default one is gone
+    if let Commands::Notebooks { command } = &cli.command {
+        return notebooks::run_notebooks(command, cli.data_dir.clone(), cli.output);
+    }
+    crate::notebooks::select(cli.data_dir.clone(), cli.notebook.as_deref())?;
+
+    match &cli.command {
+        Commands::Key { command } => key::run_key(command, cli.output),
+        Commands::Doctor { migrate } => doctor::run_doctor(*migrate, cli.output),
+        Commands::Backup { out } => backup::run_backup(out.as_deref(), cli.output),
+        Commands::Restore { file, replace, dry_run } => backup::run_restore(file, *replace, *dry_run, cli.output),
+        Commands::Migrate { from, to, force, switch } => {
+            migrate::run_migrate(from.as_deref(), to, *force, *switch, cli.output)
+        }
+        _ => build_default(cli)?.run().await,
+    }
+}
+
+/// The configured store: day files in the data dir (encrypted once `aigenda key init` has
+/// run), versioned with git when configured, with recurring items added to today
+pub fn open_store(config: &Config) -> AppResult<impl Storage> {
+    let data_dir = FsStorage::default_data_dir()?;
+    let store = open_days(config, &data_dir)?;
+    // Queue saved days for the embeddings index
+    #[cfg(feature = "ai")]
+    let store: Box<dyn Storage> = if data_dir.join(KEY_FILE).exists() {
+        store
+    } else {
+        Box::new(crate::embeddings::IndexedStorage::new(store, &data_dir))
+    };
+    let store = GitStorage::from_config(store, config.git.as_ref(), &data_dir)?;
+    RecurringStorage::from_config(store, config)
+}
+
+/// The days as stored, without recurring items, git or indexing: for commands that check or
+/// rewrite stored days, where reading today mustn't add its recurring notes
+pub fn open_days(config: &Config, data_dir: &Path) -> AppResult<Box<dyn Storage>> {
+    if data_dir.join(KEY_FILE).exists() {
+        return open_encrypted(config, data_dir);
+    }
+    storage::open_format(config.storage, &storage::default_location(config.storage, data_dir))
+}
+
+/// Encrypted days aren't indexed for semantic search: the index would keep their text in the clear
+#[cfg(feature = "encryption")]
+fn open_encrypted(config: &Config, data_dir: &Path) -> AppResult<Box<dyn Storage>> {
+    use crate::{keys::{self, KeyFile}, storage::encrypted::EncryptedStorage};
+
+    let key_file = KeyFile::load(data_dir)?.ok_or_else(|| AppError::Config("Missing key file".to_string()))?;
+    let key = keys::unlock(data_dir, &key_file, config)?;
+    let previous = key_file.previous_key(&key)?;
+    Ok(Box::new(EncryptedStorage::new(data_dir, key, key_file.encrypt_filenames)?.with_previous(previous)))
+}
+
+#[cfg(not(feature = "encryption"))]
+fn open_encrypted(_config: &Config, data_dir: &Path) -> AppResult<Box<dyn Storage>> {
+    Err(AppError::Config(format!(
+        "The notes in {} are encrypted. Rebuild with: cargo build --features encryption",
+        data_dir.display()
+    )))
+}
@@ -0,0 +1,380 @@
+//! Backups: one `.tar.gz` of the data dir, config and agent memory, with a manifest of
+//! SHA-256 checksums that restoring checks first. Restoring either replaces the current files
+//! with the archived ones or merges the archived notes into the current store.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDateTime};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    config::Config,
+    error::{AppError, AppResult},
+    migrate::Location,
+    notebooks::NOTEBOOKS_DIR,
+    schema::{self, SCHEMA_VERSION},
+    storage::{Storage, KEY_FILE},
+};
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Left out of backups: git history has its own remote, the embeddings index is rebuilt, and
+/// other notebooks are backed up on their own
+const SKIPPED: &[&str] = &[".git", ".embeddings", NOTEBOOKS_DIR];
+
+/// The archive root the data dir is stored under
+pub const DATA: &str = "data";
+
+const NAME_FORMAT: &str = "aigenda-%Y-%m-%d-%H%M%S.tar.gz";
+
+/// Something backed up: a folder, or a single file, stored under `name` in the archive
+#[derive(Debug, Clone)]
+pub struct Root {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl Root {
+    pub fn new(name: &str, path: PathBuf) -> Self {
+        Self { name: name.to_string(), path }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Manifest {
+    pub created_at: DateTime<FixedOffset>,
+    pub aigenda_version: String,
+    pub schema_version: u32,
+    pub files: Vec<FileEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FileEntry {
+    /// Path in the archive, like `data/2025-01-15.json`
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// Writes a backup of `roots` to `out`, replacing it only once the archive is complete
+pub fn create(roots: &[Root], out: &Path, now: DateTime<FixedOffset>) -> AppResult<Manifest> {
+    let mut files = Vec::new();
+    for root in roots {
+        collect(&root.path, &root.name, &mut files)?;
+    }
+    // An archive written into a backed-up folder isn't part of itself
+    files.retain(|(_, path)| path.as_path() != out);
+
+    let mut contents = Vec::new();
+    let mut manifest = Manifest {
+        created_at: now,
+        aigenda_version: env!("CARGO_PKG_VERSION").to_string(),
+        schema_version: SCHEMA_VERSION,
+        files: Vec::new(),
+    };
+    for (name, path) in files {
+        let bytes = fs::read(&path)
+            .map_err(|e| AppError::Storage(format!("Could not read file {}: {}", path.display(), e)))?;
+        manifest.files.push(FileEntry { path: name.clone(), size: bytes.len() as u64, sha256: checksum(&bytes) });
+        contents.push((name, bytes));
+    }
+
+    if let Some(dir) = out.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    let partial = out.with_extension("partial");
+    let written = write_archive(&partial, &manifest, &contents, now.timestamp() as u64)
+        .and_then(|()| fs::rename(&partial, out).map_err(AppError::from));
+    if written.is_err() {
+        let _ = fs::remove_file(&partial);
+    }
+    written.map_err(|e| AppError::Storage(format!("Could not write backup {}: {}", out.display(), e)))?;
+    Ok(manifest)
+}
+
+fn write_archive(path: &Path, manifest: &Manifest, contents: &[(String, Vec<u8>)], mtime: u64) -> AppResult<()> {
+    let mut archive = tar::Builder::new(GzEncoder::new(File::create(path)?, Compression::default()));
+    let manifest = serde_json::to_vec_pretty(manifest)?;
+    for (name, bytes) in std::iter::once((MANIFEST_FILE, &manifest)).chain(contents.iter().map(|(n, b)| (n.as_str(), b))) {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(bytes.len() as u64);
+        header.set_mode(0o600);
+        header.set_mtime(mtime);
+        archive.append_data(&mut header, name, bytes.as_slice())?;
+    }
+    archive.into_inner()?.finish()?;
+    Ok(())
+}
+
+/// The regular files under `path`, named by their place in the archive
+fn collect(path: &Path, name: &str, files: &mut Vec<(String, PathBuf)>) -> AppResult<()> {
+    if path.is_file() {
+        files.push((name.to_string(), path.to_path_buf()));
+        return Ok(());
+    }
+    if !path.is_dir() {
+        return Ok(());
+    }
+
+    let mut entries = fs::read_dir(path)
+        .map_err(|e| AppError::Storage(format!("Could not read directory {}: {}", path.display(), e)))?
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        if SKIPPED.contains(&file_name.as_str()) {
+            continue;
+        }
+        // Sockets and the like aren't data
+        let kind = entry.file_type()?;
+        if kind.is_file() || kind.is_dir() {
+            collect(&entry.path(), &format!("{}/{}", name, file_name), files)?;
+        }
+    }
+    Ok(())
+}
+
+fn checksum(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// A backup read into memory, with every file checked against its manifest
+pub struct Archive {
+    pub manifest: Manifest,
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+impl Archive {
+    pub fn open(path: &Path) -> AppResult<Self> {
+        let corrupt = |message: String| AppError::Corrupt(format!("{}: {}", path.display(), message));
+        let file = File::open(path)
+            .map_err(|e| AppError::NotFound(format!("Could not open backup {}: {}", path.display(), e)))?;
+
+        let mut manifest = None;
+        let mut files = BTreeMap::new();
+        let mut archive = tar::Archive::new(GzDecoder::new(file));
+        for entry in archive.entries().map_err(|e| corrupt(e.to_string()))? {
+            let mut entry = entry.map_err(|e| corrupt(e.to_string()))?;
+            let name = entry.path().map_err(|e| corrupt(e.to_string()))?.to_string_lossy().into_owned();
+            let mut bytes = Vec::new();
+            entry.read_to_end(&mut bytes).map_err(|e| corrupt(e.to_string()))?;
+            if name == MANIFEST_FILE {
+                manifest = Some(
+                    serde_json::from_slice::<Manifest>(&bytes).map_err(|e| corrupt(format!("Invalid manifest: {}", e)))?,
+                );
+            } else {
+                files.insert(name, bytes);
+            }
+        }
+        let manifest = manifest.ok_or_else(|| corrupt(format!("No {}; not an aigenda backup", MANIFEST_FILE)))?;
+
+        let listed: BTreeSet<&str> = manifest.files.iter().map(|f| f.path.as_str()).collect();
+        if let Some(extra) = files.keys().find(|name| !listed.contains(name.as_str())) {
+            return Err(corrupt(format!("{} isn't in the manifest", extra)));
+        }
+        for entry in &manifest.files {
+            if !is_relative(&entry.path) {
+                return Err(corrupt(format!("Unsafe path {}", entry.path)));
+            }
+            let bytes = files.get(&entry.path).ok_or_else(|| corrupt(format!("{} is missing", entry.path)))?;
+            if bytes.len() as u64 != entry.size || checksum(bytes) != entry.sha256 {
+                return Err(corrupt(format!("{} doesn't match its checksum", entry.path)));
+            }
+        }
+
+        schema::check_version(manifest.schema_version).map_err(|e| schema::located(e, path.display()))?;
+        Ok(Self { manifest, files })
+    }
+
+    /// Archived files under `root`, by their path inside it
+    fn under<'a>(&'a self, root: &'a str) -> impl Iterator<Item = (&'a str, &'a [u8])> {
+        self.files.iter().filter_map(move |(name, bytes)| {
+            let rest = if name == root { "" } else { name.strip_prefix(root)?.strip_prefix('/')? };
+            Some((rest, bytes.as_slice()))
+        })
+    }
+
+    /// The settings backed up along with the notes
+    fn config(&self) -> AppResult<Config> {
+        // With `--data-dir`, settings are backed up with the notes
+        match self.files.get("config/config.json").or_else(|| self.files.get("data/config.json")) {
+            Some(bytes) => serde_json::from_slice(bytes)
+                .map_err(|e| AppError::Config(format!("Could not parse the backed up config.json: {}", e))),
+            None => Ok(Config::default()),
+        }
+    }
+}
+
+/// Only plain relative paths are unpacked, never `..` or absolute ones
+fn is_relative(path: &str) -> bool {
+    !path.is_empty() && Path::new(path).components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+#[derive(Serialize, Debug, Default, PartialEq, Eq)]
+pub struct RestoreReport {
+    /// Days the archive added notes to (merge)
+    pub days: usize,
+    pub notes: usize,
+    pub trashed: usize,
+    /// Files written (replace), or restored because they were missing (merge)
+    pub files: usize,
+    /// Current files the archive doesn't have, deleted (replace)
+    pub removed: usize,
+}
+
+/// Puts the archived files in place of the current ones under every root. With `dry_run`,
+/// only reports what would change.
+pub fn replace(archive: &Archive, roots: &[Root], dry_run: bool) -> AppResult<RestoreReport> {
+    let mut report = RestoreReport::default();
+    for root in roots {
+        let archived: BTreeMap<&str, &[u8]> = archive.under(&root.name).collect();
+        let mut current = Vec::new();
+        collect(&root.path, "", &mut current)?;
+        for (name, path) in current {
+            let name = name.trim_start_matches('/');
+            if !archived.contains_key(name) {
+                report.removed += 1;
+                if !dry_run {
+                    fs::remove_file(&path)
+                        .map_err(|e| AppError::Storage(format!("Could not delete {}: {}", path.display(), e)))?;
+                }
+            }
+        }
+        for (name, bytes) in archived {
+            let path = if name.is_empty() { root.path.clone() } else { root.path.join(name) };
+            if fs::read(&path).is_ok_and(|current| current == bytes) {
+                continue;
+            }
+            report.files += 1;
+            if !dry_run {
+                write(&path, bytes)?;
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Adds the archived notes and trash that `into` doesn't have, and restores other backed up
+/// files (config, templates, memory) only where they're missing. Encrypted backups can only
+/// be replaced.
+pub fn merge<S: Storage + ?Sized>(archive: &Archive, into: &S, roots: &[Root], dry_run: bool) -> AppResult<RestoreReport> {
+    if archive.files.contains_key(&format!("{}/{}", DATA, KEY_FILE)) {
+        return Err(AppError::Config("The backup is encrypted; restore it with --replace".to_string()));
+    }
+
+    // Unpacked days are plaintext notes, so they go in a fresh folder only the user can open
+    let scratch = tempfile::Builder::new()
+        .prefix("aigenda-restore-")
+        .tempdir()
+        .map_err(|e| AppError::Storage(format!("Could not create a folder to restore into: {}", e)))?;
+    for (name, bytes) in archive.under(DATA).filter(|(name, _)| !name.is_empty()) {
+        write(&scratch.path().join(name), bytes)?;
+    }
+    let from = Location::default_for(archive.config()?.storage, scratch.path()).open()?;
+    let mut report = merge_notes(from.as_ref(), into, dry_run)?;
+
+    for root in roots.iter().filter(|r| r.name != DATA) {
+        for (name, bytes) in archive.under(&root.name) {
+            let path = if name.is_empty() { root.path.clone() } else { root.path.join(name) };
+            if !path.exists() {
+                report.files += 1;
+                if !dry_run {
+                    write(&path, bytes)?;
+                }
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Copies notes `into` doesn't have yet (matched by their timestamp) from `from`, each placed
+/// among the current ones by time so notes moved since are left where they are
+pub fn merge_notes<F, T>(from: &F, into: &T, dry_run: bool) -> AppResult<RestoreReport>
+where
+    F: Storage + ?Sized,
+    T: Storage + ?Sized,
+{
+    let mut report = RestoreReport::default();
+    for date in from.list_dates()? {
+        let theirs = from.load_day(date)?;
+        let mut day = into.load_day(date)?;
+        let mut added = 0;
+        for note in theirs.notes {
+            if day.notes.iter().any(|n| n.when == note.when) {
+                continue;
+            }
+            let at = day.notes.iter().position(|n| n.when > note.when).unwrap_or(day.notes.len());
+            day.notes.insert(at, note);
+            added += 1;
+        }
+        let rules = theirs.recurring.into_iter().filter(|r| !day.recurring.contains(r)).collect::<Vec<_>>();
+        if added == 0 && rules.is_empty() && into.exists(date)? {
+            continue;
+        }
+
+        day.recurring.extend(rules);
+        report.days += 1;
+        report.notes += added;
+        if !dry_run {
+            into.save_day(&day)?;
+        }
+    }
+
+    let mut trash = into.load_trash()?;
+    for gone in from.load_trash()? {
+        if !trash.iter().any(|t| t.deleted_at == gone.deleted_at && t.note.when == gone.note.when) {
+            trash.push(gone);
+            report.trashed += 1;
+        }
+    }
+    if report.trashed > 0 && !dry_run {
+        into.save_trash(&trash)?;
+    }
+    Ok(report)
+}
+
+fn write(path: &Path, bytes: &[u8]) -> AppResult<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, bytes).map_err(|e| AppError::Storage(format!("Could not write to {}: {}", path.display(), e)))
+}
+
+/// The file name of a backup taken at `when`, in a folder of backups
+pub fn file_name(when: DateTime<FixedOffset>) -> String {
+    when.format(NAME_FORMAT).to_string()
+}
+
+/// Deletes backups in `dir` except the newest of each of the last `daily` days that have one
+/// and the newest of each of the last `weekly` such weeks. The newest backup is always kept; files not named
+/// like a backup are left alone. Returns the deleted files.
+pub fn rotate(dir: &Path, daily: usize, weekly: usize) -> AppResult<Vec<PathBuf>> {
+    let mut backups: Vec<(NaiveDateTime, PathBuf)> = Vec::new();
+    for entry in fs::read_dir(dir).map_err(|e| AppError::Storage(format!("Could not read directory {}: {}", dir.display(), e)))? {
+        let path = entry?.path();
+        let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        if let Ok(taken) = NaiveDateTime::parse_from_str(&name, NAME_FORMAT) {
+            backups.push((taken, path));
+        }
+    }
+    backups.sort_by_key(|(taken, _)| std::cmp::Reverse(*taken));
+
+    let (mut days, mut weeks) = (BTreeSet::new(), BTreeSet::new());
+    let mut deleted = Vec::new();
+    for (i, (taken, path)) in backups.into_iter().enumerate() {
+        let week = taken.date().iso_week();
+        let newest_of_day = days.len() < daily && days.insert(taken.date());
+        let newest_of_week = weeks.len() < weekly && weeks.insert((week.year(), week.week()));
+        if i == 0 || newest_of_day || newest_of_week {
+            continue;
+        }
+        fs::remove_file(&path).map_err(|e| AppError::Storage(format!("Could not delete {}: {}", path.display(), e)))?;
+        deleted.push(path);
+    }
+    Ok(deleted)
+}
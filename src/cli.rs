@@ -1,25 +1,195 @@
-use clap::{Parser, Subcommand};
+use clap::{ArgGroup, Args, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+
+use crate::models::Priority;
 
 #[derive(Parser)]
 #[command(name = "aigenda", version, about = "AI-ready daily notes CLI")]
 pub struct Cli {
+    /// How errors are reported on stderr
+    #[arg(long, global = true, value_enum, default_value_t = ErrorFormat::Text, env = "AIGENDA_ERROR_FORMAT")]
+    pub error_format: ErrorFormat,
+
+    /// Output format; `json`/`ndjson` also report errors as JSON
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text, env = "AIGENDA_OUTPUT")]
+    pub output: OutputFormat,
+
+    /// Folder for notes, agent memory and settings, instead of the platform's data and config dirs
+    #[arg(long, global = true, value_name = "DIR", env = "AIGENDA_HOME")]
+    pub data_dir: Option<PathBuf>,
+
+    /// Notebook to use instead of the default one
+    #[arg(long, global = true, value_name = "NAME", env = "AIGENDA_NOTEBOOK")]
+    pub notebook: Option<String>,
+
     #[command(subcommand)]
     pub command: Commands,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ErrorFormat {
+    /// Human-readable message
+    Text,
+    /// One JSON object with kind, exit code and message
+    Json,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable text
+    Text,
+    /// One pretty-printed JSON document
+    Json,
+    /// One compact JSON value per line
+    Ndjson,
+}
+
 #[derive(Subcommand)]
 pub enum Commands {
     /// Add a note to today's log
     Add { text: Vec<String> },
 
     /// List notes (today by default)
-    List {
-        /// List all days
+    List(ListArgs),
+
+    /// Remove notes by id (YYYY-MM-DD#N)
+    Rm {
+        #[arg(required = true)]
+        notes: Vec<String>,
+    },
+
+    /// Replace a note's text, keeping its original time
+    Amend {
+        /// Note id (YYYY-MM-DD#N)
+        note: String,
+        text: Vec<String>,
+    },
+
+    /// Show every revision of a note's text
+    Log {
+        /// Note id (YYYY-MM-DD#N)
+        note: String,
+    },
+
+    /// Restore an earlier revision of a note (kept as a new revision)
+    Revert {
+        /// Note id (YYYY-MM-DD#N)
+        note: String,
+        /// Revision number, as shown by `log`
+        #[arg(long, value_name = "REV")]
+        to: usize,
+    },
+
+    /// Move a note to another day or position
+    #[command(group(ArgGroup::new("target").args(["to", "position"]).multiple(true).required(true)))]
+    Mv {
+        /// Note id (YYYY-MM-DD#N)
+        note: String,
+        /// Day to move it to: YYYY-MM-DD or an expression like `tomorrow`
         #[arg(long)]
-        all: bool,
-        /// Specific date (YYYY-MM-DD)
+        to: Option<String>,
+        /// 1-based position on that day (the end by default)
         #[arg(long)]
+        position: Option<usize>,
+    },
+
+    /// Deleted notes: list, restore or purge them
+    Trash {
+        #[command(subcommand)]
+        command: TrashCommand,
+    },
+
+    /// Edit a day's notes in $EDITOR
+    Edit {
+        /// Day to edit (today by default): YYYY-MM-DD or an expression like `yesterday`
         date: Option<String>,
+        /// Only edit this note (1-based)
+        #[arg(long)]
+        note: Option<usize>,
+    },
+
+    /// Today's notes, recurring items and carried-over tasks
+    Today,
+
+    /// Apply note templates from the `templates/` config folder
+    Template {
+        #[command(subcommand)]
+        command: TemplateCommand,
+    },
+
+    /// Find notes similar to a note (YYYY-MM-DD#N) or to free text
+    Similar {
+        /// Note id or search text; empty just refreshes the index
+        query: Vec<String>,
+        /// Maximum number of results
+        #[arg(long, default_value_t = 5)]
+        limit: usize,
+        /// Rebuild the embeddings index from scratch
+        #[arg(long)]
+        reindex: bool,
+    },
+
+    /// Pull and push the notes' git repository (needs `git.remote` in config.json)
+    Sync,
+
+    /// Copy all notes to another storage format, e.g. `--to sqlite`; run it again to resume
+    Migrate {
+        /// Store to copy from: fs, markdown or sqlite, optionally with :PATH (the configured one by default)
+        #[arg(long, value_name = "STORE")]
+        from: Option<String>,
+        /// Store to copy to, like `sqlite` or `markdown:~/vault/journal`
+        #[arg(long, value_name = "STORE")]
+        to: String,
+        /// Copy into a store that already has notes, replacing the days that differ
+        #[arg(long)]
+        force: bool,
+        /// Use the new store from now on (it must be in the data dir)
+        #[arg(long)]
+        switch: bool,
+    },
+
+    /// Save notes, settings and agent memory to one compressed archive
+    Backup {
+        /// Archive to write; without it, a dated one in the backups folder, rotating old ones
+        #[arg(long, value_name = "FILE")]
+        out: Option<PathBuf>,
+    },
+
+    /// Restore a backup, adding its notes to the current ones
+    Restore {
+        /// Archive written by `aigenda backup`
+        file: PathBuf,
+        /// Replace the current notes and settings with the backup's instead of merging
+        #[arg(long)]
+        replace: bool,
+        /// Check the archive and show what would change, without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Check that every stored day can be read and report ones written with an older schema
+    Doctor {
+        /// Rewrite outdated documents with the current schema
+        #[arg(long)]
+        migrate: bool,
+    },
+
+    /// Separate journals with their own notes, memory and settings: list, create, pick the default
+    Notebooks {
+        #[command(subcommand)]
+        command: NotebooksCommand,
+    },
+
+    /// Encrypt the notes at rest and manage their key
+    Key {
+        #[command(subcommand)]
+        command: KeyCommand,
+    },
+
+    /// Tasks: notes with a status, due date and priority
+    Todo {
+        #[command(subcommand)]
+        command: TodoCommand,
     },
 
     /// AI agent with natural language processing
@@ -27,4 +197,138 @@ pub enum Commands {
         /// Natural language command for the AI agent
         prompt: Vec<String>,
     },
+
+    /// Model Context Protocol integration
+    Mcp {
+        #[command(subcommand)]
+        command: McpCommand,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum McpCommand {
+    /// Expose tools and notes to MCP clients over stdio
+    Serve,
+}
+
+#[derive(Subcommand)]
+pub enum TrashCommand {
+    /// List deleted notes with their trash ids
+    List,
+    /// Put deleted notes back where they were
+    Restore {
+        #[arg(required = true)]
+        ids: Vec<usize>,
+    },
+    /// Permanently delete notes from the trash
+    #[command(group(ArgGroup::new("which").args(["ids", "all"]).required(true)))]
+    Purge {
+        ids: Vec<usize>,
+        /// Empty the whole trash
+        #[arg(long)]
+        all: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum NotebooksCommand {
+    /// List notebooks, marking the default one
+    List,
+    /// Start a new, empty notebook
+    Create { name: String },
+    /// Use a notebook when `--notebook` isn't given (`main` for the original one)
+    Default { name: String },
+}
+
+#[derive(Subcommand)]
+pub enum TemplateCommand {
+    /// Add a template's notes and tasks to a day's log
+    Apply {
+        name: String,
+        /// Day to apply it to (today by default)
+        #[arg(long)]
+        date: Option<String>,
+    },
+    /// List available templates
+    List,
+}
+
+#[derive(Subcommand)]
+pub enum KeyCommand {
+    /// Encrypt the data dir; asks for a passphrase unless a keyfile is configured
+    Init {
+        /// Unlock with this file's contents instead of a passphrase
+        #[arg(long, value_name = "PATH")]
+        keyfile: Option<PathBuf>,
+        /// Also hide the dates, by storing days under random file names
+        #[arg(long)]
+        encrypt_filenames: bool,
+    },
+    /// Print a shell command that keeps the key unlocked in `AIGENDA_KEY`
+    Unlock,
+    /// Keep the key unlocked in a background process for a while
+    Agent {
+        /// Minutes to keep the key
+        #[arg(long, default_value_t = 60)]
+        timeout: u64,
+    },
+    /// Re-encrypt everything under a new key, and change the passphrase or keyfile
+    Rotate {
+        /// Unlock with this file from now on
+        #[arg(long, value_name = "PATH")]
+        keyfile: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TodoCommand {
+    /// Add an open task to today's log
+    Add {
+        text: Vec<String>,
+        /// Due date: YYYY-MM-DD or an expression like `friday` or `in 2 weeks`
+        #[arg(long)]
+        due: Option<String>,
+        #[arg(long, value_enum)]
+        priority: Option<Priority>,
+    },
+    /// Mark a task done, by id (YYYY-MM-DD#N) or by some of its text
+    Done { task: Vec<String> },
+    /// Cancel a task, by id (YYYY-MM-DD#N) or by some of its text
+    Cancel { task: Vec<String> },
+    /// Reopen a done or cancelled task by id
+    Reopen { task: String },
+    /// List open tasks, most urgent first
+    List {
+        /// Include done and cancelled tasks
+        #[arg(long)]
+        all: bool,
+    },
+}
+
+/// Which days `list` shows. Dates accept `YYYY-MM-DD` or expressions like
+/// `yesterday`, `last monday` or `2 weeks ago`.
+#[derive(Args, Default)]
+#[command(group(ArgGroup::new("range").args(["all", "date", "from", "week", "month", "last"])))]
+pub struct ListArgs {
+    /// List all days
+    #[arg(long)]
+    pub all: bool,
+    /// A single day
+    #[arg(long)]
+    pub date: Option<String>,
+    /// First day of a range (up to today unless --to is given)
+    #[arg(long)]
+    pub from: Option<String>,
+    /// Last day of the range started with --from
+    #[arg(long, requires = "from")]
+    pub to: Option<String>,
+    /// The week (Monday to Sunday) containing a day; this week without a value
+    #[arg(long, value_name = "DATE", num_args = 0..=1, default_missing_value = "today")]
+    pub week: Option<String>,
+    /// A month as YYYY-MM or the month containing a day; this month without a value
+    #[arg(long, value_name = "MONTH", num_args = 0..=1, default_missing_value = "today")]
+    pub month: Option<String>,
+    /// The last days, weeks, months or years up to today: 7d, 2w, 3m, 1y
+    #[arg(long, value_name = "SPAN")]
+    pub last: Option<String>,
 }
@@ -0,0 +1,78 @@
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate, Utc};
+use chrono_tz::Tz;
+use std::sync::OnceLock;
+
+use crate::{
+    config::Config,
+    error::{AppError, AppResult},
+};
+
+/// The user's wall clock: which timezone notes are stamped in, and at which hour
+/// one day ends and the next begins. Every "what day is it" question goes through it,
+/// so the CLI and the agent always file a note under the same day.
+#[derive(Clone, Copy, Debug)]
+pub struct Clock {
+    /// `None` follows the system timezone
+    timezone: Option<Tz>,
+    day_starts_at: u32,
+}
+
+impl Clock {
+    pub fn new(timezone: Option<&str>, day_starts_at: u32) -> AppResult<Self> {
+        let timezone = timezone
+            .map(|name| name.parse::<Tz>().map_err(|_| AppError::Config(format!("Unknown timezone '{}'", name))))
+            .transpose()?;
+
+        if day_starts_at > 23 {
+            return Err(AppError::Config(format!(
+                "day_starts_at must be an hour between 0 and 23, got {}", day_starts_at
+            )));
+        }
+
+        Ok(Self { timezone, day_starts_at })
+    }
+
+    /// System timezone, days starting at midnight
+    pub fn system() -> Self {
+        Self { timezone: None, day_starts_at: 0 }
+    }
+
+    pub fn from_config(config: &Config) -> AppResult<Self> {
+        Self::new(config.timezone.as_deref(), config.day_starts_at.unwrap_or(0))
+    }
+
+    pub fn now(&self) -> DateTime<FixedOffset> {
+        self.local(Utc::now().fixed_offset())
+    }
+
+    /// `when` as seen on the user's clock
+    pub fn local(&self, when: DateTime<FixedOffset>) -> DateTime<FixedOffset> {
+        match self.timezone {
+            Some(tz) => when.with_timezone(&tz).fixed_offset(),
+            None => when.with_timezone(&Local).fixed_offset(),
+        }
+    }
+
+    /// The day `when` belongs to, honouring the rollover hour
+    pub fn day_of(&self, when: DateTime<FixedOffset>) -> NaiveDate {
+        (self.local(when) - Duration::hours(self.day_starts_at as i64)).date_naive()
+    }
+
+    pub fn today(&self) -> NaiveDate {
+        self.day_of(self.now())
+    }
+}
+
+/// The clock from the user config, loaded once. A broken config is reported and
+/// the system clock used instead, so reading notes keeps working.
+pub fn clock() -> &'static Clock {
+    static CLOCK: OnceLock<Clock> = OnceLock::new();
+    CLOCK.get_or_init(|| {
+        Config::load()
+            .and_then(|config| Clock::from_config(&config))
+            .unwrap_or_else(|e| {
+                eprintln!("⚠️  {}; using the system clock", e);
+                Clock::system()
+            })
+    })
+}
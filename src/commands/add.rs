@@ -1,20 +1,29 @@
 use crate::{
+    cli::OutputFormat,
+    clock::clock,
     error::AppResult,
-    models::Note,
+    models::{Note, NoteRef},
+    output::print_json,
     storage::Storage,
 };
-use chrono::Local;
+use serde_json::json;
 
-pub fn run_add<S: Storage>(store: &S, words: Vec<String>) -> AppResult<()> {
-    let now = Local::now();
-    let text = words.join(" ");
-    let mut day = store.load_day(now.date_naive())?;
-    day.notes.push(Note {
-        when: now.to_rfc3339(),
-        text,
-        tags: vec![],
-    });
+pub fn run_add<S: Storage>(store: &S, words: Vec<String>, output: OutputFormat) -> AppResult<()> {
+    let note = Note::new(words.join(" "));
+    let mut day = store.load_day(clock().day_of(note.when))?;
+    day.notes.push(note);
     store.save_day(&day)?;
-    println!("Added note to {}.", day.date.format("%Y-%m-%d"));
-    Ok(())
+
+    if output == OutputFormat::Text {
+        println!("Added note to {}.", day.date.format("%Y-%m-%d"));
+        return Ok(());
+    }
+
+    let id = NoteRef::new(day.date, day.notes.len());
+    print_json(output, &json!({
+        "id": id.to_string(),
+        "date": day.date.format("%Y-%m-%d").to_string(),
+        "index": id.index,
+        "note": day.notes.last(),
+    }))
 }
@@ -1,13 +1,20 @@
 #[cfg(feature = "ai")]
-use crate::agent::{Agent, ConsoleStreamingHandler};
+use crate::agent::{Agent, AgentEvent, ConsoleStreamingHandler, JsonStreamingHandler, NdjsonStreamingHandler};
 #[cfg(feature = "ai")]
 use crate::ai::claude::ClaudeClient;
-use crate::error::AppResult;
+use crate::cli::OutputFormat;
+use crate::error::{AppError, AppResult};
+use crate::storage::Storage;
+use std::sync::Arc;
 
 #[cfg(feature = "ai")]
-pub async fn handle_agent_command(prompt: Vec<String>) -> AppResult<()> {
+pub async fn handle_agent_command(store: Arc<dyn Storage>, prompt: Vec<String>, output: OutputFormat) -> AppResult<()> {
     let input = prompt.join(" ");
 
+    if output != OutputFormat::Text {
+        return run_structured(store, &input, output).await;
+    }
+
     if input.trim().is_empty() {
         println!("Usage: aigenda ai <your natural language command>");
         println!("Example: aigenda ai \"add a note about today's meeting\"");
@@ -16,7 +23,7 @@ pub async fn handle_agent_command(prompt: Vec<String>) -> AppResult<()> {
     }
 
     // Initialize the agent
-    let mut agent = Agent::new()?;
+    let mut agent = Agent::new(store).await?;
 
     // Try to initialize Claude client if API key is available
     if let Ok(claude_client) = ClaudeClient::new() {
@@ -30,13 +37,14 @@ pub async fn handle_agent_command(prompt: Vec<String>) -> AppResult<()> {
                 println!("\n✅ Command completed successfully!");
             }
             Err(e) => {
-                eprintln!("❌ Error executing command: {}", e);
-
-                // Fallback: show available tools
+                // Fallback: show available tools, then fail with the error's exit code
                 println!("\n📋 Available tools:");
                 for tool in agent.list_available_tools() {
                     println!("  • {}", tool);
                 }
+
+                agent.shutdown().await;
+                return Err(e);
             }
         }
     } else {
@@ -54,6 +62,39 @@ pub async fn handle_agent_command(prompt: Vec<String>) -> AppResult<()> {
         println!("   aigenda ai \"update my note from today\"");
     }
 
+    agent.shutdown().await;
     Ok(())
 }
 
+/// Runs the agent with JSON events on stdout instead of console output. With `ndjson`
+/// events stream as they happen and permission is asked over stdin (see `NdjsonStreamingHandler`).
+#[cfg(feature = "ai")]
+async fn run_structured(store: Arc<dyn Storage>, input: &str, output: OutputFormat) -> AppResult<()> {
+    if input.trim().is_empty() {
+        return Err(AppError::InvalidParameters("Missing prompt".to_string()));
+    }
+
+    let mut agent = Agent::new(store).await?.with_claude_client(ClaudeClient::new()?);
+
+    if output == OutputFormat::Json {
+        let mut handler = JsonStreamingHandler::new();
+        let result = agent.execute_command_streaming(input, &mut handler).await;
+        agent.shutdown().await;
+        return handler.finish(&result?);
+    }
+
+    let mut handler = NdjsonStreamingHandler::new(
+        std::io::stdout(),
+        std::io::BufReader::new(std::io::stdin()),
+    );
+    let result = agent.execute_command_streaming(input, &mut handler).await;
+    agent.shutdown().await;
+
+    match result {
+        Ok(response) => handler.emit(&AgentEvent::Done { response }),
+        Err(e) => {
+            handler.emit(&AgentEvent::from(&e))?;
+            Err(e)
+        }
+    }
+}
@@ -1,7 +1,10 @@
 // Stub for AI agent when AI features are disabled
+use crate::cli::OutputFormat;
 use crate::error::AppResult;
+use crate::storage::Storage;
+use std::sync::Arc;
 
-pub async fn handle_agent_command(_prompt: Vec<String>) -> AppResult<()> {
+pub async fn handle_agent_command(_store: Arc<dyn Storage>, _prompt: Vec<String>, _output: OutputFormat) -> AppResult<()> {
     eprintln!("❌ AI command requires AI features. Rebuild with: cargo build --features ai");
     std::process::exit(1);
 }
\ No newline at end of file
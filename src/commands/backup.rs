@@ -0,0 +1,105 @@
+use std::path::Path;
+
+use serde_json::json;
+
+use crate::{
+    app,
+    backup::{self, Archive, Root, RestoreReport},
+    cli::OutputFormat,
+    clock::clock,
+    config::Config,
+    error::AppResult,
+    models::Editor,
+    notebooks,
+    output::print_json,
+    storage::{fs::FsStorage, git::GitRepo},
+};
+
+const KEEP_DAILY: usize = 7;
+const KEEP_WEEKLY: usize = 4;
+
+pub fn run_backup(out: Option<&Path>, output: OutputFormat) -> AppResult<()> {
+    let config = Config::load()?;
+    let now = clock().now();
+    let (path, rotated) = match out {
+        Some(path) => (path.to_path_buf(), None),
+        None => {
+            let dir = match &config.backup.dir {
+                Some(dir) => dir.clone(),
+                None => notebooks::current()?.backups_dir.clone(),
+            };
+            (dir.join(backup::file_name(now)), Some(dir))
+        }
+    };
+
+    let manifest = backup::create(&roots()?, &path, now)?;
+    let removed = match rotated {
+        Some(dir) => backup::rotate(
+            &dir,
+            config.backup.keep_daily.unwrap_or(KEEP_DAILY),
+            config.backup.keep_weekly.unwrap_or(KEEP_WEEKLY),
+        )?,
+        None => Vec::new(),
+    };
+
+    if output != OutputFormat::Text {
+        return print_json(output, &json!({ "path": path, "manifest": manifest, "removed": removed }));
+    }
+    println!("Backed up {} files to {}.", manifest.files.len(), path.display());
+    if !removed.is_empty() {
+        println!("Removed {} old backups.", removed.len());
+    }
+    Ok(())
+}
+
+pub fn run_restore(file: &Path, replace: bool, dry_run: bool, output: OutputFormat) -> AppResult<()> {
+    let archive = Archive::open(file)?;
+    let config = Config::load()?;
+    let data_dir = FsStorage::default_data_dir()?;
+    let report = if replace {
+        backup::replace(&archive, &roots()?, dry_run)?
+    } else {
+        // The days as stored: merging into today mustn't add its recurring notes
+        let store = app::open_days(&config, &data_dir)?;
+        backup::merge(&archive, &store, &roots()?, dry_run)?
+    };
+    if let (Some(git), false) = (&config.git, dry_run) {
+        let repo = GitRepo::open_or_init(&data_dir, git.branch())?;
+        repo.record(&format!("restore backup from {}", archive.manifest.created_at.format("%Y-%m-%d %H:%M")))?;
+        repo.commit(Editor::Cli)?;
+    }
+
+    if output != OutputFormat::Text {
+        return print_json(output, &json!({ "replace": replace, "dry_run": dry_run, "report": report }));
+    }
+    println!("{} is intact: {} files from {}.", file.display(), archive.manifest.files.len(), archive.manifest.created_at.format("%Y-%m-%d %H:%M"));
+    print_report(replace, dry_run, &report);
+    Ok(())
+}
+
+fn print_report(replace: bool, dry_run: bool, report: &RestoreReport) {
+    if replace {
+        match dry_run {
+            true => println!("Would write {} files and delete {}.", report.files, report.removed),
+            false => println!("Wrote {} files and deleted {}.", report.files, report.removed),
+        }
+        return;
+    }
+
+    let (add, restore) = if dry_run { ("Would add", "Would restore") } else { ("Added", "Restored") };
+    println!("{} {} notes to {} days and {} notes to the trash.", add, report.notes, report.days, report.trashed);
+    if report.files > 0 {
+        println!("{} {} missing settings files.", restore, report.files);
+    }
+}
+
+/// What a backup holds: the notebook's data dir (agent memory included) and its config
+/// folder, unless that's the data dir too
+fn roots() -> AppResult<Vec<Root>> {
+    let notebook = notebooks::current()?;
+    let mut roots = vec![Root::new(backup::DATA, notebook.data_dir.clone())];
+    if notebook.config_dir != notebook.data_dir {
+        roots.push(Root::new("config", notebook.config_dir.clone()));
+    }
+    Ok(roots)
+}
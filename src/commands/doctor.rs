@@ -0,0 +1,89 @@
+use serde_json::json;
+
+use crate::{
+    app,
+    cli::OutputFormat,
+    config::Config,
+    error::{AppError, AppResult},
+    output::print_json,
+    schema::{Document, MIGRATIONS, SCHEMA_VERSION},
+    storage::{fs::FsStorage, Storage},
+};
+
+/// Checks that every stored document can be read, and lists (or, with `migrate`, rewrites)
+/// the ones written with an older schema. Works on the days as stored, so an upgrade
+/// doesn't add today's recurring notes.
+pub fn run_doctor(migrate: bool, output: OutputFormat) -> AppResult<()> {
+    let store = app::open_days(&Config::load()?, &FsStorage::default_data_dir()?)?;
+    check(&store, migrate, output)
+}
+
+fn check<S: Storage>(store: &S, migrate: bool, output: OutputFormat) -> AppResult<()> {
+    let dates = store.list_dates()?;
+    let mut unreadable = Vec::new();
+    for &date in &dates {
+        if let Err(e) = store.load_day(date) {
+            unreadable.push(e.to_string());
+        }
+    }
+    if let Err(e) = store.load_trash() {
+        unreadable.push(e.to_string());
+    }
+
+    if !unreadable.is_empty() {
+        if output != OutputFormat::Text {
+            print_json(output, &json!({ "schema_version": SCHEMA_VERSION, "days": dates.len(), "unreadable": unreadable }))?;
+        } else {
+            for problem in &unreadable {
+                println!("❌ {}", problem);
+            }
+        }
+        return Err(AppError::Corrupt(format!("{} stored documents could not be read", unreadable.len())));
+    }
+
+    let outdated = store.outdated()?;
+    if migrate {
+        for (date, _) in &outdated {
+            match date {
+                Some(date) => store.save_day(&store.load_day(*date)?)?,
+                None => store.save_trash(&store.load_trash()?)?,
+            }
+        }
+    }
+
+    if output != OutputFormat::Text {
+        let documents: Vec<_> = outdated
+            .iter()
+            .map(|(date, version)| json!({ "date": date, "schema_version": version }))
+            .collect();
+        return print_json(output, &json!({
+            "schema_version": SCHEMA_VERSION,
+            "days": dates.len(),
+            "outdated": documents,
+            "migrated": migrate,
+        }));
+    }
+
+    println!("Schema version {}, {} days, all readable.", SCHEMA_VERSION, dates.len());
+    if outdated.is_empty() {
+        return Ok(());
+    }
+    if migrate {
+        println!("Upgraded {} documents to schema version {}.", outdated.len(), SCHEMA_VERSION);
+        return Ok(());
+    }
+
+    println!("{} documents use an older schema:", outdated.len());
+    for (date, version) in &outdated {
+        let (name, document) = match date {
+            Some(date) => (date.format("%Y-%m-%d").to_string(), Document::Day),
+            None => ("trash".to_string(), Document::Trash),
+        };
+        println!("  {} (version {})", name, version);
+        for step in MIGRATIONS.iter().filter(|m| m.document == document && m.from >= *version) {
+            println!("    - {}", step.description);
+        }
+    }
+    println!("They're upgraded when read; run `aigenda doctor --migrate` to rewrite them now.");
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use std::{env, fs, process::Command};
+
+use serde_json::json;
+
+use crate::{
+    cli::OutputFormat,
+    dates::{self, parse_date_from},
+    day_edit,
+    error::{AppError, AppResult},
+    output::print_json,
+    storage::Storage,
+    trash,
+};
+
+pub fn run_edit<S: Storage>(store: &S, date: Option<&str>, note: Option<usize>, output: OutputFormat) -> AppResult<()> {
+    let date = match date {
+        Some(date) => parse_date_from(date, dates::today())?,
+        None => dates::today(),
+    };
+    let day = store.load_day(date)?;
+    let buffer = day_edit::render(&day, note)?;
+
+    let path = env::temp_dir().join(format!("aigenda-{}-{}.md", date.format("%Y-%m-%d"), std::process::id()));
+    fs::write(&path, &buffer)?;
+    let edited = open_editor(&path).and_then(|_| Ok(fs::read_to_string(&path)?));
+    let _ = fs::remove_file(&path);
+    let edited = edited?;
+
+    let (updated, summary) = day_edit::apply(&day, &edited, note)?;
+    if !summary.is_empty() {
+        if updated.notes.is_empty() && updated.recurring.is_empty() {
+            store.delete_day(date)?;
+        } else {
+            store.save_day(&updated)?;
+        }
+        for &position in &summary.removed {
+            trash::discard(store, date, position, day.notes[position - 1].clone())?;
+        }
+    }
+
+    if output != OutputFormat::Text {
+        return print_json(output, &json!({
+            "date": date.format("%Y-%m-%d").to_string(),
+            "summary": summary,
+            "day": updated,
+        }));
+    }
+
+    if summary.is_empty() {
+        println!("No changes to {}.", date.format("%Y-%m-%d"));
+    } else {
+        println!(
+            "Saved {}: {} added, {} removed, {} changed{}.",
+            date.format("%Y-%m-%d"),
+            summary.added,
+            summary.removed.len(),
+            summary.changed,
+            if summary.reordered { ", reordered" } else { "" }
+        );
+    }
+    Ok(())
+}
+
+/// Runs `$VISUAL` or `$EDITOR` (`vi` when neither is set) on the file and waits for it
+fn open_editor(path: &std::path::Path) -> AppResult<()> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .ok()
+        .filter(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+
+    // Allow editors with arguments, like `code --wait`
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+    let status = Command::new(program)
+        .args(words)
+        .arg(path)
+        .status()
+        .map_err(|e| AppError::Config(format!("Could not start editor '{}': {}", editor, e)))?;
+
+    if !status.success() {
+        return Err(AppError::Io(std::io::Error::other(format!(
+            "Editor '{}' exited with {}; nothing was saved", editor, status
+        ))));
+    }
+    Ok(())
+}
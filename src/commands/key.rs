@@ -0,0 +1,160 @@
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use serde_json::json;
+
+use crate::{
+    cli::{KeyCommand, OutputFormat},
+    config::{Config, StorageFormat},
+    error::{AppError, AppResult},
+    keys::{self, DataKey, KeyFile, SecretKind, DEFAULT_LOG_N},
+    output::print_json,
+    storage::{self, encrypted::EncryptedStorage, fs::FsStorage, Storage},
+};
+
+pub fn run_key(command: &KeyCommand, output: OutputFormat) -> AppResult<()> {
+    let config = Config::load()?;
+    let data_dir = FsStorage::default_data_dir()?;
+
+    match command {
+        KeyCommand::Init { keyfile, encrypt_filenames } => {
+            init(&data_dir, &config, keyfile.as_deref(), *encrypt_filenames, output)
+        }
+        KeyCommand::Unlock => {
+            let key = keys::unlock(&data_dir, &load(&data_dir)?, &config)?;
+            if output != OutputFormat::Text {
+                return print_json(output, &json!({ "key": key.to_hex() }));
+            }
+            // For `eval "$(aigenda key unlock)"`
+            println!("export AIGENDA_KEY={}", key.to_hex());
+            Ok(())
+        }
+        KeyCommand::Agent { timeout } => agent(&data_dir, &config, *timeout),
+        KeyCommand::Rotate { keyfile } => rotate(&data_dir, &config, keyfile.as_deref(), output),
+    }
+}
+
+/// Encrypts the plaintext days and trash, in whichever format they're stored, and deletes
+/// them once the encrypted copies and the key file are written
+fn init(data_dir: &Path, config: &Config, keyfile: Option<&Path>, encrypt_filenames: bool, output: OutputFormat) -> AppResult<()> {
+    if KeyFile::load(data_dir)?.is_some() {
+        return Err(AppError::Conflict(format!(
+            "The notes in {} are already encrypted; use `aigenda key rotate` to change the key",
+            data_dir.display()
+        )));
+    }
+    let (secret, kind) = new_secret(config, keyfile)?;
+
+    let location = storage::default_location(config.storage, data_dir);
+    let plain = storage::open_format(config.storage, &location)?;
+    let days = plain.iter_days()?;
+    let trash = plain.load_trash()?;
+
+    let key = DataKey::generate()?;
+    let key_file = KeyFile::new(&key, &secret, kind, encrypt_filenames, DEFAULT_LOG_N)?;
+    let encrypted = EncryptedStorage::new(data_dir, key, encrypt_filenames)?;
+    for day in &days {
+        encrypted.save_day(day)?;
+    }
+    if !trash.is_empty() {
+        encrypted.save_trash(&trash)?;
+    }
+    key_file.save(data_dir)?;
+
+    for day in &days {
+        plain.delete_day(day.date)?;
+    }
+    plain.save_trash(&[])?;
+    if config.storage == StorageFormat::Sqlite {
+        // Deleted rows can linger in the database's free pages
+        drop(plain);
+        fs::remove_file(&location)
+            .map_err(|e| AppError::Storage(format!("Could not delete {}: {}", location.display(), e)))?;
+    }
+    // The semantic search index holds note text too
+    let index = data_dir.join(".embeddings");
+    if index.exists() {
+        fs::remove_dir_all(&index)?;
+    }
+
+    if output != OutputFormat::Text {
+        return print_json(output, &json!({ "encrypted_days": days.len(), "data_dir": data_dir }));
+    }
+    println!("Encrypted {} days in {}.", days.len(), data_dir.display());
+    remind_keyfile(config, keyfile);
+    if data_dir.join(".git").exists() {
+        println!("Earlier git commits still hold the notes in the clear.");
+    }
+    Ok(())
+}
+
+/// Seals everything under a fresh data key, wrapped under a new passphrase or keyfile. The
+/// new key file is saved first with the old key sealed inside it, and that is dropped once
+/// every file is re-encrypted, so an interrupted rotation loses nothing and can be rerun.
+fn rotate(data_dir: &Path, config: &Config, keyfile: Option<&Path>, output: OutputFormat) -> AppResult<()> {
+    let current = load(data_dir)?;
+    let key = keys::unlock(data_dir, &current, config)?;
+    let previous = current.previous_key(&key)?;
+    let (secret, kind) = new_secret(config, keyfile)?;
+
+    let new_key = DataKey::generate()?;
+    let mut key_file = KeyFile::new(&new_key, &secret, kind, current.encrypt_filenames, DEFAULT_LOG_N)?;
+    key_file.keep_previous(&new_key, &key)?;
+
+    let mut store = EncryptedStorage::new(data_dir, key, current.encrypt_filenames)?;
+    if previous.is_some() {
+        // Finish an interrupted rotation, so everything opens with the current key
+        store = store.with_previous(previous).reseal()?;
+    }
+    key_file.save(data_dir)?;
+    let store = store.rekey(new_key)?;
+    key_file.forget_previous();
+    key_file.save(data_dir)?;
+
+    let days = store.list_dates()?.len();
+    if output != OutputFormat::Text {
+        return print_json(output, &json!({ "encrypted_days": days, "data_dir": data_dir }));
+    }
+    println!("Re-encrypted {} days under a new key.", days);
+    println!("Keys cached in AIGENDA_KEY or a key agent no longer work; unlock again.");
+    remind_keyfile(config, keyfile);
+    Ok(())
+}
+
+#[cfg(unix)]
+fn agent(data_dir: &Path, config: &Config, timeout: u64) -> AppResult<()> {
+    let key = keys::unlock(data_dir, &load(data_dir)?, config)?;
+    let socket = keys::socket_path()?;
+    eprintln!("Holding the key on {} for {} minutes.", socket.display(), timeout);
+    keys::agent::serve(&socket, &key, Duration::from_secs(timeout * 60))
+}
+
+#[cfg(not(unix))]
+fn agent(_data_dir: &Path, _config: &Config, _timeout: u64) -> AppResult<()> {
+    Err(AppError::Config("The key agent needs Unix sockets; use `aigenda key unlock` instead".to_string()))
+}
+
+fn load(data_dir: &Path) -> AppResult<KeyFile> {
+    KeyFile::load(data_dir)?.ok_or_else(|| {
+        AppError::NotFound(format!("The notes in {} aren't encrypted; run `aigenda key init`", data_dir.display()))
+    })
+}
+
+/// The given keyfile, the configured one, or a new passphrase
+fn new_secret(config: &Config, keyfile: Option<&Path>) -> AppResult<(Vec<u8>, SecretKind)> {
+    let configured = config.encryption.as_ref().and_then(|e| e.keyfile.as_deref());
+    match keyfile.or(configured) {
+        Some(path) => Ok((keys::read_keyfile(path)?, SecretKind::Keyfile)),
+        None => Ok((keys::new_passphrase()?, SecretKind::Passphrase)),
+    }
+}
+
+/// Unlocking reads the keyfile named in config.json
+fn remind_keyfile(config: &Config, keyfile: Option<&Path>) {
+    let configured = config.encryption.as_ref().and_then(|e| e.keyfile.as_deref());
+    if let Some(path) = keyfile.filter(|path| Some(*path) != configured) {
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        println!("Set \"encryption\": {{\"keyfile\": {:?}}} in config.json to unlock with it.", path.display().to_string());
+    }
+}
@@ -0,0 +1,8 @@
+// Stub for key management when encryption is disabled
+use crate::cli::{KeyCommand, OutputFormat};
+use crate::error::AppResult;
+
+pub fn run_key(_command: &KeyCommand, _output: OutputFormat) -> AppResult<()> {
+    eprintln!("❌ Key command requires encryption. Rebuild with: cargo build --features encryption");
+    std::process::exit(1);
+}
@@ -1,35 +1,98 @@
-use crate::{error::AppResult, storage::Storage};
+use crate::{
+    cli::{ListArgs, OutputFormat},
+    dates::{self, parse_date_from, DateRange},
+    error::AppResult,
+    models::DayLog,
+    output::print_json_list,
+    storage::Storage,
+    tasks,
+};
 use chrono::NaiveDate;
 
-pub fn run_list<S: Storage>(store: &S, all: bool, date: Option<String>) -> AppResult<()> {
-    if all {
-        for day in store.iter_days()? {
-            print_day(&day);
+pub fn run_list<S: Storage>(store: &S, args: &ListArgs, output: OutputFormat) -> AppResult<()> {
+    if args.all {
+        return print_days(&store.iter_days()?, output);
+    }
+
+    let today = dates::today();
+    let range = selected_range(args, today)?;
+
+    // A single day is shown even when empty; longer ranges skip empty days
+    let days = if range.is_single_day() {
+        vec![store.load_day(range.start)?]
+    } else {
+        let mut days = Vec::new();
+        for day in store.days_between(range)? {
+            let day = day?;
+            if !day.notes.is_empty() {
+                days.push(day);
+            }
+        }
+        days
+    };
+    print_days(&days, output)?;
+
+    // Today's view also shows the open tasks left on earlier days
+    if output == OutputFormat::Text && range == DateRange::day(today) {
+        let carried = tasks::carried_over(store, today)?;
+        if !carried.is_empty() {
+            println!("## Carried over");
+            carried.iter().for_each(|t| println!("- {}", t.line()));
+            println!();
         }
-        return Ok(());
+    }
+    Ok(())
+}
+
+/// The days picked by the flags, today when there are none
+pub fn selected_range(args: &ListArgs, today: NaiveDate) -> AppResult<DateRange> {
+    if let Some(date) = &args.date {
+        return Ok(DateRange::day(parse_date_from(date, today)?));
+    }
+
+    if let Some(from) = &args.from {
+        let end = match &args.to {
+            Some(to) => parse_date_from(to, today)?,
+            None => today,
+        };
+        return DateRange::new(parse_date_from(from, today)?, end);
+    }
+
+    if let Some(week) = &args.week {
+        return Ok(DateRange::week_of(parse_date_from(week, today)?));
+    }
+
+    if let Some(month) = &args.month {
+        return DateRange::month(month, today);
     }
 
-    if let Some(d) = date {
-        let parsed = NaiveDate::parse_from_str(&d, "%Y-%m-%d")?;
-        let day = store.load_day(parsed)?;
-        print_day(&day);
-        return Ok(());
+    if let Some(last) = &args.last {
+        return DateRange::last(last, today);
     }
 
-    let today = chrono::Local::now().date_naive();
-    let day = store.load_day(today)?;
-    print_day(&day);
+    Ok(DateRange::day(today))
+}
+
+fn print_days(days: &[DayLog], output: OutputFormat) -> AppResult<()> {
+    if output != OutputFormat::Text {
+        return print_json_list(output, days);
+    }
+
+    days.iter().for_each(print_day);
     Ok(())
 }
 
-fn print_day(day: &crate::models::DayLog) {
+fn print_day(day: &DayLog) {
     if day.notes.is_empty() {
         println!("(no notes) {}", day.date);
         return;
     }
     println!("# {}", day.date);
     for (i, n) in day.notes.iter().enumerate() {
-        println!("- [{:02}] {}", i + 1, n.text);
+        match &n.task {
+            Some(task) => println!("- [{:02}] {} {}", i + 1, task.label(), n.text),
+            None => println!("- [{:02}] {}", i + 1, n.text),
+        }
     }
     println!();
 }
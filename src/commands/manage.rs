@@ -0,0 +1,100 @@
+use serde_json::json;
+
+use crate::{
+    cli::OutputFormat,
+    dates::{self, parse_date_from},
+    error::AppResult,
+    clock::clock,
+    models::{Editor, NoteRef},
+    notes,
+    output::{print_json, print_json_list},
+    storage::Storage,
+};
+
+pub fn run_rm<S: Storage>(store: &S, ids: &[String], output: OutputFormat) -> AppResult<()> {
+    let mut refs = ids.iter().map(|id| id.parse::<NoteRef>()).collect::<AppResult<Vec<_>>>()?;
+    // Remove from the end of each day first so the remaining ids stay valid
+    refs.sort_by(|a, b| b.date.cmp(&a.date).then(b.index.cmp(&a.index)));
+    refs.dedup();
+
+    let mut removed = Vec::new();
+    for note_ref in refs {
+        let note = notes::remove_note(store, note_ref)?;
+        if output == OutputFormat::Text {
+            println!("Removed {}: {}", note_ref, note.text);
+        }
+        removed.push(json!({ "id": note_ref.to_string(), "note": note }));
+    }
+
+    if output != OutputFormat::Text {
+        return print_json_list(output, &removed);
+    }
+    Ok(())
+}
+
+pub fn run_amend<S: Storage>(store: &S, id: &str, words: &[String], output: OutputFormat) -> AppResult<()> {
+    let note_ref = id.parse::<NoteRef>()?;
+    let note = notes::amend_note(store, note_ref, &words.join(" "), Editor::Cli)?;
+
+    if output == OutputFormat::Text {
+        println!("Amended {}.", note_ref);
+        return Ok(());
+    }
+    print_json(output, &json!({ "id": note_ref.to_string(), "note": note }))
+}
+
+pub fn run_mv<S: Storage>(
+    store: &S,
+    id: &str,
+    to: Option<&str>,
+    position: Option<usize>,
+    output: OutputFormat,
+) -> AppResult<()> {
+    let from = id.parse::<NoteRef>()?;
+    let to_date = match to {
+        Some(to) => parse_date_from(to, dates::today())?,
+        None => from.date,
+    };
+    let moved = notes::move_note(store, from, to_date, position)?;
+
+    if output == OutputFormat::Text {
+        println!("Moved {} to {}.", from, moved);
+        return Ok(());
+    }
+    print_json(output, &json!({ "from": from.to_string(), "id": moved.to_string() }))
+}
+
+pub fn run_log<S: Storage>(store: &S, id: &str, output: OutputFormat) -> AppResult<()> {
+    let note_ref = id.parse::<NoteRef>()?;
+    let history = notes::get_note(store, note_ref)?.history();
+
+    if output != OutputFormat::Text {
+        return print_json_list(output, &history);
+    }
+
+    println!("# {}", note_ref);
+    for (i, revision) in history.iter().enumerate() {
+        let editor = revision.editor.map(|e| e.to_string()).unwrap_or_default();
+        let current = if i + 1 == history.len() { "  (current)" } else { "" };
+        println!(
+            "r{}  {}  {:<6} {}{}",
+            i + 1,
+            clock().local(revision.edited_at).format("%Y-%m-%d %H:%M"),
+            editor,
+            revision.text,
+            current
+        );
+    }
+    Ok(())
+}
+
+pub fn run_revert<S: Storage>(store: &S, id: &str, rev: usize, output: OutputFormat) -> AppResult<()> {
+    let note_ref = id.parse::<NoteRef>()?;
+    let note = notes::revert_note(store, note_ref, rev, Editor::Cli)?;
+
+    if output == OutputFormat::Text {
+        println!("Reverted {} to r{}: {}", note_ref, rev, note.text);
+        return Ok(());
+    }
+    print_json(output, &json!({ "id": note_ref.to_string(), "note": note }))
+}
@@ -0,0 +1,19 @@
+#[cfg(feature = "ai")]
+use crate::{agent::ToolRegistry, mcp::McpServer};
+use crate::{error::AppResult, storage::Storage};
+use std::sync::Arc;
+
+/// `aigenda mcp serve`: speaks MCP on stdin/stdout until the client disconnects
+#[cfg(feature = "ai")]
+pub async fn run_serve(store: Arc<dyn Storage>) -> AppResult<()> {
+    let mut registry = ToolRegistry::new();
+    registry.auto_discover_tools(store.clone()).await?;
+
+    McpServer::new(registry, &store).serve_stdio().await
+}
+
+#[cfg(not(feature = "ai"))]
+pub async fn run_serve(_store: Arc<dyn Storage>) -> AppResult<()> {
+    eprintln!("❌ MCP server requires AI features. Rebuild with: cargo build --features ai");
+    std::process::exit(1);
+}
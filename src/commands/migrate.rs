@@ -0,0 +1,98 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    cli::OutputFormat,
+    config::Config,
+    error::{AppError, AppResult},
+    migrate::{self, Location},
+    output::print_json,
+    storage::{fs::FsStorage, Storage},
+};
+
+/// A migration that was started, so running it again resumes it
+const STATE_FILE: &str = "migration.json";
+
+#[derive(Serialize, Deserialize, PartialEq)]
+struct State {
+    from: String,
+    to: String,
+}
+
+pub fn run_migrate(from: Option<&str>, to: &str, force: bool, switch: bool, output: OutputFormat) -> AppResult<()> {
+    let config = Config::load()?;
+    let data_dir = FsStorage::default_data_dir()?;
+    let source = match from {
+        Some(spec) => Location::parse(spec, &data_dir)?,
+        None => Location::default_for(config.storage, &data_dir),
+    };
+    let target = Location::parse(to, &data_dir)?;
+    if source == target {
+        return Err(AppError::InvalidParameters(format!("{} is both the source and the destination", source)));
+    }
+    if switch && target != Location::default_for(target.format, &data_dir) {
+        return Err(AppError::InvalidParameters(format!(
+            "--switch needs the destination in the data dir: use --to {}",
+            target.format
+        )));
+    }
+
+    let state = State { from: source.to_string(), to: target.to_string() };
+    let state_path = state_path()?;
+    let resume = force || read_state(&state_path)?.is_some_and(|s| s == state);
+    let (from_store, to_store) = (source.open()?, target.open()?);
+    if !resume && !to_store.list_dates()?.is_empty() {
+        return Err(AppError::Conflict(format!("{} already has notes; pass --force to copy into it", target)));
+    }
+
+    write_state(&state_path, &state)?;
+    let report = migrate::migrate(from_store.as_ref(), to_store.as_ref(), true)?;
+    fs::remove_file(&state_path)?;
+
+    if switch {
+        Config::set("storage", json!(target.format))?;
+    }
+
+    if output != OutputFormat::Text {
+        return print_json(
+            output,
+            &json!({ "from": state.from, "to": state.to, "report": report, "switched": switch }),
+        );
+    }
+    println!("Copied {} days from {} to {}.", report.copied, source, target);
+    if report.skipped > 0 {
+        println!("{} days were already there.", report.skipped);
+    }
+    if report.trashed > 0 {
+        println!("Copied the trash ({} notes).", report.trashed);
+    }
+    println!("Verified all {} days.", report.days);
+    if switch {
+        println!("Now using {} storage; the old copy is still in {}.", target.format, source.path.display());
+    }
+    Ok(())
+}
+
+fn state_path() -> AppResult<PathBuf> {
+    Ok(Config::config_dir()?.join(STATE_FILE))
+}
+
+fn read_state(path: &Path) -> AppResult<Option<State>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content).ok())
+}
+
+fn write_state(path: &Path, state: &State) -> AppResult<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let content = serde_json::to_string(state).map_err(|e| AppError::Storage(format!("Could not serialize: {}", e)))?;
+    fs::write(path, content)?;
+    Ok(())
+}
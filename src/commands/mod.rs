@@ -1,5 +1,26 @@
 pub mod add;
+pub mod backup;
+pub mod doctor;
+pub mod edit;
 pub mod list;
+pub mod manage;
+pub mod mcp;
+pub mod migrate;
+pub mod notebooks;
+pub mod similar;
+pub mod sync;
+pub mod template;
+pub mod todo;
+pub mod trash;
+
+#[cfg(feature = "encryption")]
+pub mod key;
+
+#[cfg(not(feature = "encryption"))]
+pub mod key_stub;
+
+#[cfg(not(feature = "encryption"))]
+pub use key_stub as key;
 
 #[cfg(feature = "ai")]
 pub mod agent;
@@ -0,0 +1,49 @@
+use std::path::PathBuf;
+
+use serde_json::json;
+
+use crate::{
+    cli::{NotebooksCommand, OutputFormat},
+    error::AppResult,
+    notebooks::Home,
+    output::{print_json, print_json_list},
+};
+
+pub fn run_notebooks(command: &NotebooksCommand, data_dir: Option<PathBuf>, output: OutputFormat) -> AppResult<()> {
+    let home = Home::new(data_dir)?;
+    match command {
+        NotebooksCommand::List => {
+            let default = home.default_name()?;
+            let names = home.list()?;
+            if output != OutputFormat::Text {
+                let items: Vec<_> = names
+                    .iter()
+                    .map(|name| json!({ "name": name, "default": *name == default }))
+                    .collect();
+                return print_json_list(output, &items);
+            }
+            for name in &names {
+                let marker = if *name == default { "*" } else { " " };
+                println!("{} {}", marker, name);
+            }
+            Ok(())
+        }
+        NotebooksCommand::Create { name } => {
+            let notebook = home.create(name)?;
+            if output != OutputFormat::Text {
+                return print_json(output, &json!({ "name": notebook.name, "data_dir": notebook.data_dir }));
+            }
+            println!("Created notebook '{}' in {}.", notebook.name, notebook.data_dir.display());
+            println!("Use it with --notebook {}, or by default after `aigenda notebooks default {}`.", name, name);
+            Ok(())
+        }
+        NotebooksCommand::Default { name } => {
+            let notebook = home.set_default(name)?;
+            if output != OutputFormat::Text {
+                return print_json(output, &json!({ "default": notebook.name }));
+            }
+            println!("Notebook '{}' is now the default.", notebook.name);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,72 @@
+#[cfg(feature = "ai")]
+use crate::{
+    embeddings::SemanticIndex,
+    models::NoteRef,
+    output::{print_json, print_json_list},
+    storage::fs::FsStorage,
+};
+#[cfg(feature = "ai")]
+use serde_json::json;
+use crate::{cli::OutputFormat, error::AppResult, storage::Storage};
+
+/// `aigenda similar <note-id|text>`: a `YYYY-MM-DD#N` argument finds notes like
+/// that note, anything else is treated as free text.
+#[cfg(feature = "ai")]
+pub async fn run_similar<S: Storage>(
+    store: &S,
+    query: Vec<String>,
+    limit: usize,
+    reindex: bool,
+    output: OutputFormat,
+) -> AppResult<()> {
+    let query = query.join(" ");
+    let index = SemanticIndex::open(&FsStorage::default_data_dir()?)?;
+
+    if query.trim().is_empty() {
+        let refreshed = index.refresh(store, reindex).await?;
+        if output != OutputFormat::Text {
+            return print_json(output, &json!({ "indexed": refreshed.entries.len() }));
+        }
+        println!("Indexed {} notes.", refreshed.entries.len());
+        return Ok(());
+    }
+
+    if reindex {
+        index.refresh(store, true).await?;
+    }
+
+    let hits = match query.trim().parse::<NoteRef>() {
+        Ok(note) => index.search_similar(store, note, limit).await?,
+        Err(_) => index.search_text(store, &query, limit).await?,
+    };
+
+    if output != OutputFormat::Text {
+        let hits: Vec<_> = hits
+            .iter()
+            .map(|hit| json!({ "id": hit.note.to_string(), "text": hit.text, "score": hit.score }))
+            .collect();
+        return print_json_list(output, &hits);
+    }
+
+    if hits.is_empty() {
+        println!("(no similar notes)");
+        return Ok(());
+    }
+
+    for hit in hits {
+        println!("- [{}] ({:.2}) {}", hit.note, hit.score, hit.text);
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "ai"))]
+pub async fn run_similar<S: Storage>(
+    _store: &S,
+    _query: Vec<String>,
+    _limit: usize,
+    _reindex: bool,
+    _output: OutputFormat,
+) -> AppResult<()> {
+    eprintln!("❌ Similarity search requires AI features. Rebuild with: cargo build --features ai");
+    std::process::exit(1);
+}
@@ -0,0 +1,33 @@
+use serde_json::json;
+
+use crate::{
+    cli::OutputFormat,
+    config::Config,
+    error::{AppError, AppResult},
+    models::Editor,
+    output::print_json,
+    storage::{fs::FsStorage, git::GitRepo, Storage},
+};
+
+pub fn run_sync<S: Storage>(store: &S, output: OutputFormat) -> AppResult<()> {
+    let config = Config::load()?;
+    let git = config
+        .git
+        .as_ref()
+        .ok_or_else(|| AppError::Config("Git sync is off: add a \"git\" section to config.json".to_string()))?;
+    let remote = git
+        .remote
+        .as_deref()
+        .ok_or_else(|| AppError::Config("No git remote: set \"git\": {\"remote\": ...} in config.json".to_string()))?;
+
+    // Commit anything pending first, so it's pushed along
+    store.checkpoint(Editor::Cli)?;
+    let repo = GitRepo::open_or_init(&FsStorage::default_data_dir()?, git.branch())?;
+    repo.sync(remote, git.branch())?;
+
+    if output != OutputFormat::Text {
+        return print_json(output, &json!({ "remote": remote, "branch": git.branch() }));
+    }
+    println!("Synced with {} ({}).", remote, git.branch());
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use serde_json::json;
+
+use crate::{
+    cli::{OutputFormat, TemplateCommand},
+    dates::{self, parse_date_from},
+    error::AppResult,
+    output::{print_json, print_json_list},
+    storage::Storage,
+    templates::{self, Templates},
+};
+
+pub fn run_template<S: Storage>(store: &S, command: &TemplateCommand, output: OutputFormat) -> AppResult<()> {
+    let templates = Templates::open_default()?;
+
+    match command {
+        TemplateCommand::Apply { name, date } => {
+            let date = match date {
+                Some(date) => parse_date_from(date, dates::today())?,
+                None => dates::today(),
+            };
+            let template = templates.load(name)?;
+            let ids = templates::apply(store, &template, date)?;
+
+            if output == OutputFormat::Text {
+                println!("Applied template '{}' to {}: {} notes added.", template.name, date.format("%Y-%m-%d"), ids.len());
+                return Ok(());
+            }
+            let ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+            print_json(output, &json!({
+                "template": template.name,
+                "date": date.format("%Y-%m-%d").to_string(),
+                "ids": ids,
+            }))
+        }
+        TemplateCommand::List => {
+            let names = templates.names()?;
+            if output != OutputFormat::Text {
+                return print_json_list(output, &names);
+            }
+            if names.is_empty() {
+                println!("(no templates)");
+            }
+            names.iter().for_each(|name| println!("{}", name));
+            Ok(())
+        }
+    }
+}
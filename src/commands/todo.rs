@@ -0,0 +1,54 @@
+use crate::{
+    cli::{OutputFormat, TodoCommand},
+    dates::{self, parse_date_from},
+    error::AppResult,
+    models::TaskStatus,
+    output::{print_json, print_json_list},
+    storage::Storage,
+    tasks::{self, TaskEntry},
+};
+
+pub fn run_todo<S: Storage>(store: &S, command: &TodoCommand, output: OutputFormat) -> AppResult<()> {
+    let today = dates::today();
+
+    match command {
+        TodoCommand::Add { text, due, priority } => {
+            let due = due.as_deref().map(|d| parse_date_from(d, today)).transpose()?;
+            let task = tasks::add_task(store, today, &text.join(" "), due, *priority)?;
+            print_task(&task, "Added", output)
+        }
+        TodoCommand::Done { task } => close(store, &task.join(" "), TaskStatus::Done, "Done:", output),
+        TodoCommand::Cancel { task } => close(store, &task.join(" "), TaskStatus::Cancelled, "Cancelled:", output),
+        TodoCommand::Reopen { task } => {
+            let task = tasks::set_status(store, task.parse()?, TaskStatus::Open)?;
+            print_task(&task, "Reopened:", output)
+        }
+        TodoCommand::List { all } => {
+            let mut list = tasks::list_tasks(store, *all)?;
+            tasks::sort_by_urgency(&mut list);
+
+            if output != OutputFormat::Text {
+                return print_json_list(output, &list);
+            }
+            if list.is_empty() {
+                println!("(no open tasks)");
+            }
+            list.iter().for_each(|t| println!("{}", t.line()));
+            Ok(())
+        }
+    }
+}
+
+fn close<S: Storage>(store: &S, query: &str, status: TaskStatus, verb: &str, output: OutputFormat) -> AppResult<()> {
+    let note_ref = tasks::resolve_task(store, query)?;
+    let task = tasks::set_status(store, note_ref, status)?;
+    print_task(&task, verb, output)
+}
+
+fn print_task(task: &TaskEntry, verb: &str, output: OutputFormat) -> AppResult<()> {
+    if output == OutputFormat::Text {
+        println!("{} {}", verb, task.line());
+        return Ok(());
+    }
+    print_json(output, task)
+}
@@ -0,0 +1,58 @@
+use serde_json::json;
+
+use crate::{
+    cli::{OutputFormat, TrashCommand},
+    clock::clock,
+    error::AppResult,
+    output::{print_json, print_json_list},
+    storage::Storage,
+    trash,
+};
+
+pub fn run_trash<S: Storage>(store: &S, command: &TrashCommand, output: OutputFormat) -> AppResult<()> {
+    match command {
+        TrashCommand::List => {
+            let items = store.load_trash()?;
+            if output != OutputFormat::Text {
+                let items: Vec<_> = items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| json!({ "id": i + 1, "item": item }))
+                    .collect();
+                return print_json_list(output, &items);
+            }
+
+            if items.is_empty() {
+                println!("(trash is empty)");
+            }
+            for (i, item) in items.iter().enumerate() {
+                println!(
+                    "[{:02}] {}#{} (deleted {})  {}",
+                    i + 1,
+                    item.date.format("%Y-%m-%d"),
+                    item.position,
+                    clock().local(item.deleted_at).format("%Y-%m-%d %H:%M"),
+                    item.note.text
+                );
+            }
+            Ok(())
+        }
+        TrashCommand::Restore { ids } => {
+            let restored = trash::restore(store, ids)?;
+            if output != OutputFormat::Text {
+                let ids: Vec<String> = restored.iter().map(|r| r.to_string()).collect();
+                return print_json(output, &json!({ "restored": ids }));
+            }
+            restored.iter().for_each(|r| println!("Restored {}.", r));
+            Ok(())
+        }
+        TrashCommand::Purge { ids, all } => {
+            let purged = trash::purge(store, if *all { None } else { Some(ids) })?;
+            if output != OutputFormat::Text {
+                return print_json(output, &json!({ "purged": purged }));
+            }
+            println!("Purged {} notes.", purged);
+            Ok(())
+        }
+    }
+}
@@ -1 +1,180 @@
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "ai")]
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
 
+use crate::error::{AppError, AppResult};
+use crate::notebooks;
+use crate::recurrence::Recurrence;
+
+const CONFIG_FILE: &str = "config.json";
+
+/// User settings, read from `config.json` in the notebook's config directory
+/// (`~/.config/aigenda/config.json` on Linux for the main notebook). Every field is optional.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Config {
+    /// IANA timezone notes are stamped and filed in, e.g. "Europe/Madrid"; the system's when unset
+    pub timezone: Option<String>,
+    /// Hour (0-23) at which a new day starts, so a note at 1am with `4` still files under
+    /// the previous day. Midnight when unset.
+    pub day_starts_at: Option<u32>,
+    /// How days are written in the data dir: "json" (the default), "markdown" or "sqlite"
+    pub storage: StorageFormat,
+    /// Notes and tasks added automatically on matching days
+    pub recurring: Vec<Recurrence>,
+    /// Days deleted notes stay in the trash (30 when unset; 0 keeps them forever)
+    pub trash_retention_days: Option<u32>,
+    /// Version the data dir with git; `{}` commits locally, a `remote` enables `aigenda sync`
+    pub git: Option<GitConfig>,
+    /// Settings for an encrypted data dir (see `aigenda key init`)
+    pub encryption: Option<EncryptionConfig>,
+    /// Where `aigenda backup` keeps its archives and how many it keeps
+    pub backup: BackupConfig,
+    /// External MCP servers whose tools are offered to the agent, keyed by name
+    #[cfg(feature = "ai")]
+    #[serde(alias = "mcpServers")]
+    pub mcp_servers: BTreeMap<String, McpServerConfig>,
+    /// Per-plugin settings for executables in the `tools/` config folder, keyed by file name
+    #[cfg(feature = "ai")]
+    pub plugins: BTreeMap<String, PluginConfig>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageFormat {
+    /// `YYYY-MM-DD.json`
+    #[default]
+    Json,
+    /// `YYYY-MM-DD.md`, editable in Obsidian or any text editor
+    Markdown,
+    /// `aigenda.db` in the data dir (needs `--features sqlite`)
+    Sqlite,
+}
+
+impl fmt::Display for StorageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            StorageFormat::Json => "json",
+            StorageFormat::Markdown => "markdown",
+            StorageFormat::Sqlite => "sqlite",
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct GitConfig {
+    /// URL or remote name that `aigenda sync` pulls from and pushes to
+    pub remote: Option<String>,
+    /// Branch to commit on and sync ("main" when unset)
+    pub branch: Option<String>,
+}
+
+impl GitConfig {
+    pub fn branch(&self) -> &str {
+        self.branch.as_deref().unwrap_or("main")
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct EncryptionConfig {
+    /// File whose contents unlock the data instead of a passphrase
+    pub keyfile: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct BackupConfig {
+    /// Folder for backups taken without `--out` (`aigenda-backups` next to the data dir when unset)
+    pub dir: Option<PathBuf>,
+    /// The newest backup of each of the last N days is kept (7 when unset)
+    pub keep_daily: Option<usize>,
+    /// The newest backup of each of the last N weeks is kept too (4 when unset)
+    pub keep_weekly: Option<usize>,
+}
+
+#[cfg(feature = "ai")]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct McpServerConfig {
+    /// Executable to spawn; it must speak MCP on stdin/stdout
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Per-request timeout in seconds (default 30)
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+#[cfg(feature = "ai")]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct PluginConfig {
+    /// Seconds a single invocation may take (default 10)
+    pub timeout_secs: Option<u64>,
+    /// Extra environment variables; plugins don't inherit ours
+    pub env: BTreeMap<String, String>,
+    pub disabled: bool,
+}
+
+impl Config {
+    /// The config dir of the notebook in use
+    pub fn config_dir() -> AppResult<PathBuf> {
+        Ok(notebooks::current()?.config_dir.clone())
+    }
+
+    /// Folder scanned for executable tool plugins
+    #[cfg(feature = "ai")]
+    pub fn plugins_dir() -> AppResult<PathBuf> {
+        Ok(Self::config_dir()?.join("tools"))
+    }
+
+    /// Folder scanned for sandboxed WASM tools
+    #[cfg(feature = "wasm")]
+    pub fn wasm_plugins_dir() -> AppResult<PathBuf> {
+        Ok(Self::config_dir()?.join("wasm"))
+    }
+
+    /// Loads the user config, falling back to defaults when there is none
+    pub fn load() -> AppResult<Self> {
+        Self::load_from(&Self::config_dir()?.join(CONFIG_FILE))
+    }
+
+    /// Sets one top-level setting in `config.json`, leaving the rest of the file as it is
+    pub fn set(key: &str, value: serde_json::Value) -> AppResult<()> {
+        let path = Self::config_dir()?.join(CONFIG_FILE);
+        let mut settings = serde_json::Map::new();
+        if path.exists() {
+            let content = fs::read_to_string(&path)
+                .map_err(|e| AppError::Storage(format!("Could not read config {}: {}", path.display(), e)))?;
+            settings = serde_json::from_str(&content)
+                .map_err(|e| AppError::Config(format!("Could not parse config {}: {}", path.display(), e)))?;
+        }
+        settings.insert(key.to_string(), value);
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content = serde_json::to_string_pretty(&settings)
+            .map_err(|e| AppError::Storage(format!("Could not serialize config: {}", e)))?;
+        fs::write(&path, content + "\n")
+            .map_err(|e| AppError::Storage(format!("Could not write config {}: {}", path.display(), e)))
+    }
+
+    pub fn load_from(path: &Path) -> AppResult<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)
+            .map_err(|e| AppError::Storage(format!("Could not read config {}: {}", path.display(), e)))?;
+
+        serde_json::from_str(&content)
+            .map_err(|e| AppError::Config(format!("Could not parse config {}: {}", path.display(), e)))
+    }
+}
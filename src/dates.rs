@@ -0,0 +1,162 @@
+//! Date expressions accepted wherever a date is: `2025-01-15`, `today`, `yesterday`,
+//! `tomorrow`, `monday`, `last friday`, `next tuesday`, `last week`, `3 days ago`,
+//! `2 weeks ago`, `a month ago`, `in 2 days`. Ranges build on top of them.
+
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+
+use crate::clock::clock;
+use crate::error::{AppError, AppResult};
+
+/// The user's current day, per their timezone and rollover hour
+pub fn today() -> NaiveDate {
+    clock().today()
+}
+
+/// Parses a date expression relative to [`today`]
+#[cfg(feature = "ai")]
+pub fn parse_date(expr: &str) -> AppResult<NaiveDate> {
+    parse_date_from(expr, today())
+}
+
+/// Parses a date expression relative to `today`
+pub fn parse_date_from(expr: &str, today: NaiveDate) -> AppResult<NaiveDate> {
+    if let Ok(date) = NaiveDate::parse_from_str(expr.trim(), "%Y-%m-%d") {
+        return Ok(date);
+    }
+
+    let lower = expr.trim().to_lowercase();
+    let words: Vec<&str> = lower.split_whitespace().collect();
+
+    let date = match words.as_slice() {
+        ["today"] | ["now"] => Some(today),
+        ["yesterday"] => today.checked_sub_days(Days::new(1)),
+        ["tomorrow"] => today.checked_add_days(Days::new(1)),
+        [day] | ["this", day] => parse_weekday(day).map(|wd| week_start(today) + Days::new(wd.num_days_from_monday() as u64)),
+        ["last", "week"] => today.checked_sub_days(Days::new(7)),
+        ["last", "month"] => today.checked_sub_months(Months::new(1)),
+        ["last", "year"] => today.checked_sub_months(Months::new(12)),
+        ["last", day] => parse_weekday(day).map(|wd| {
+            let back = (today.weekday().num_days_from_monday() + 7 - wd.num_days_from_monday()) % 7;
+            today - Days::new(if back == 0 { 7 } else { back as u64 })
+        }),
+        ["next", day] => parse_weekday(day).map(|wd| {
+            let ahead = (wd.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+            today + Days::new(if ahead == 0 { 7 } else { ahead as u64 })
+        }),
+        [count, unit, "ago"] => parse_count(count).and_then(|n| shift(today, n, unit, false)),
+        ["in", count, unit] => parse_count(count).and_then(|n| shift(today, n, unit, true)),
+        _ => None,
+    };
+
+    date.ok_or_else(|| AppError::InvalidParameters(format!(
+        "Unrecognized date '{}': use YYYY-MM-DD, today, yesterday, last monday, 2 weeks ago, ...",
+        expr.trim()
+    )))
+}
+
+/// An inclusive span of days
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DateRange {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl DateRange {
+    pub fn new(start: NaiveDate, end: NaiveDate) -> AppResult<Self> {
+        if start > end {
+            return Err(AppError::InvalidParameters(format!(
+                "Range starts after it ends: {} > {}", start, end
+            )));
+        }
+        Ok(Self { start, end })
+    }
+
+    pub fn day(date: NaiveDate) -> Self {
+        Self { start: date, end: date }
+    }
+
+    /// Monday to Sunday around `date`
+    pub fn week_of(date: NaiveDate) -> Self {
+        let start = week_start(date);
+        Self { start, end: start + Days::new(6) }
+    }
+
+    pub fn month_of(date: NaiveDate) -> Self {
+        let start = date.with_day(1).unwrap_or(date);
+        let end = start
+            .checked_add_months(Months::new(1))
+            .and_then(|next| next.pred_opt())
+            .unwrap_or(date);
+        Self { start, end }
+    }
+
+    /// `7d`, `2w`, `3m`, `1y` (or `7 days`, `2 weeks`...): that many days, weeks, months
+    /// or years back, ending with `today`
+    pub fn last(span: &str, today: NaiveDate) -> AppResult<Self> {
+        let span = span.trim().to_lowercase();
+        let split = span.find(|c: char| !c.is_ascii_digit()).unwrap_or(span.len());
+        let (count, unit) = span.split_at(split);
+
+        let start = count
+            .parse::<u32>()
+            .ok()
+            .filter(|n| *n > 0)
+            .and_then(|n| shift(today, n, unit.trim(), false))
+            .and_then(|start| start.succ_opt())
+            .ok_or_else(|| AppError::InvalidParameters(format!(
+                "Invalid span '{}': use a count and a unit, like 7d, 2w, 3m or 1y", span
+            )))?;
+
+        Ok(Self { start, end: today })
+    }
+
+    /// A month given as `YYYY-MM`, or the month containing a date expression
+    pub fn month(expr: &str, today: NaiveDate) -> AppResult<Self> {
+        if let Ok(first) = NaiveDate::parse_from_str(&format!("{}-01", expr.trim()), "%Y-%m-%d") {
+            return Ok(Self::month_of(first));
+        }
+        Ok(Self::month_of(parse_date_from(expr, today)?))
+    }
+
+    pub fn is_single_day(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Days::new(date.weekday().num_days_from_monday() as u64)
+}
+
+fn parse_weekday(word: &str) -> Option<Weekday> {
+    match word {
+        "monday" | "mon" => Some(Weekday::Mon),
+        "tuesday" | "tue" | "tues" => Some(Weekday::Tue),
+        "wednesday" | "wed" => Some(Weekday::Wed),
+        "thursday" | "thu" | "thurs" => Some(Weekday::Thu),
+        "friday" | "fri" => Some(Weekday::Fri),
+        "saturday" | "sat" => Some(Weekday::Sat),
+        "sunday" | "sun" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn parse_count(word: &str) -> Option<u32> {
+    match word {
+        "a" | "an" | "one" => Some(1),
+        _ => word.parse().ok(),
+    }
+}
+
+/// Moves `n` units (`d`/`day(s)`, `w`/`week(s)`, `m`/`month(s)`, `y`/`year(s)`) away from `date`
+fn shift(date: NaiveDate, n: u32, unit: &str, forward: bool) -> Option<NaiveDate> {
+    let days = |d: u64| if forward { date.checked_add_days(Days::new(d)) } else { date.checked_sub_days(Days::new(d)) };
+    let months = |m: u32| if forward { date.checked_add_months(Months::new(m)) } else { date.checked_sub_months(Months::new(m)) };
+
+    match unit {
+        "d" | "day" | "days" => days(n as u64),
+        "w" | "week" | "weeks" => days(n as u64 * 7),
+        "m" | "month" | "months" => months(n),
+        "y" | "year" | "years" => months(n.checked_mul(12)?),
+        _ => None,
+    }
+}
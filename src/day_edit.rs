@@ -0,0 +1,237 @@
+//! The buffer `aigenda edit` opens: one `## [N] HH:MM #tag...` heading per note followed by
+//! its text, with `[ ]`, `[x]` or `[-]` in front of tasks. Headings keep each note's identity,
+//! so editing the buffer can change, reorder, remove (drop the block) or add (`## new`) notes
+//! while timestamps, tags and task details survive.
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::{
+    clock::clock,
+    error::{AppError, AppResult},
+    models::{DayLog, Editor, Note, Task, TaskStatus},
+};
+
+/// What an edit did to a day
+#[derive(Serialize, Debug, Default, PartialEq, Eq)]
+pub struct EditSummary {
+    pub added: usize,
+    /// Positions of the removed notes in the original day
+    pub removed: Vec<usize>,
+    pub changed: usize,
+    pub reordered: bool,
+}
+
+impl EditSummary {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Renders the day, or only its note `only` (1-based)
+pub fn render(day: &DayLog, only: Option<usize>) -> AppResult<String> {
+    let mut out = format!(
+        "# {}\n\
+         # Edit the text under each heading. Delete a block to remove a note, move it to reorder,\n\
+         # or add a block under a `## new` heading. Lines above the first heading are ignored.\n\n",
+        day.date.format("%Y-%m-%d")
+    );
+
+    for index in scope(day, only)? {
+        let note = &day.notes[index - 1];
+        out.push_str(&format!("## [{}] {}", index, clock().local(note.when).format("%H:%M")));
+        for tag in &note.tags {
+            out.push_str(&format!(" #{}", tag));
+        }
+        out.push('\n');
+        if let Some(task) = &note.task {
+            out.push_str(checkbox(task.status));
+            out.push(' ');
+        }
+        out.push_str(&note.text);
+        out.push_str("\n\n");
+    }
+
+    if day.notes.is_empty() {
+        out.push_str("## new\n\n");
+    }
+    Ok(out)
+}
+
+/// Applies an edited buffer to `day`. With `only`, the buffer stands for that single note and
+/// the rest of the day is kept as is.
+pub fn apply(day: &DayLog, buffer: &str, only: Option<usize>) -> AppResult<(DayLog, EditSummary)> {
+    let in_scope = scope(day, only)?;
+    let blocks = parse_blocks(buffer)?;
+
+    let mut summary = EditSummary::default();
+    let mut seen = HashSet::new();
+    let mut edited = Vec::new();
+    let mut last_index = 0;
+
+    for block in blocks {
+        let note = match block.index {
+            Some(index) => {
+                if !in_scope.contains(&index) {
+                    return Err(invalid(format!("Note [{}] is not part of this edit", index)));
+                }
+                if !seen.insert(index) {
+                    return Err(invalid(format!("Note [{}] appears more than once", index)));
+                }
+                if index < last_index {
+                    summary.reordered = true;
+                }
+                last_index = index;
+
+                let original = &day.notes[index - 1];
+                let note = block.update(original);
+                if note.text != original.text || note.tags != original.tags || note.task != original.task {
+                    summary.changed += 1;
+                }
+                note
+            }
+            None => {
+                summary.added += 1;
+                block.into_new_note()
+            }
+        };
+        edited.push(note);
+    }
+    summary.removed = in_scope.into_iter().filter(|i| !seen.contains(i)).collect();
+
+    let mut notes = Vec::with_capacity(day.notes.len() + summary.added);
+    match only {
+        Some(n) => {
+            notes.extend_from_slice(&day.notes[..n - 1]);
+            notes.extend(edited);
+            notes.extend_from_slice(&day.notes[n..]);
+        }
+        None => notes = edited,
+    }
+
+    Ok((DayLog { notes, ..day.clone() }, summary))
+}
+
+fn scope(day: &DayLog, only: Option<usize>) -> AppResult<Vec<usize>> {
+    match only {
+        Some(n) if n == 0 || n > day.notes.len() => {
+            Err(AppError::NotFound(format!("Note {}#{}", day.date.format("%Y-%m-%d"), n)))
+        }
+        Some(n) => Ok(vec![n]),
+        None => Ok((1..=day.notes.len()).collect()),
+    }
+}
+
+struct Block {
+    index: Option<usize>,
+    tags: Vec<String>,
+    status: Option<TaskStatus>,
+    text: String,
+}
+
+impl Block {
+    fn update(self, original: &Note) -> Note {
+        let task = self.status.map(|status| match original.task.clone() {
+            Some(task) if task.status == status => task,
+            existing => Task {
+                status,
+                closed_at: (status != TaskStatus::Open).then(|| clock().now()),
+                ..existing.unwrap_or(Task { status, due: None, priority: None, closed_at: None })
+            },
+        });
+
+        let mut note = Note {
+            tags: self.tags,
+            task,
+            ..original.clone()
+        };
+        note.revise(self.text, Editor::Cli);
+        note
+    }
+
+    fn into_new_note(self) -> Note {
+        let original = Note::new(self.text.clone());
+        self.update(&original)
+    }
+}
+
+fn parse_blocks(buffer: &str) -> AppResult<Vec<Block>> {
+    let mut blocks = Vec::new();
+    let mut current: Option<(Block, Vec<&str>)> = None;
+
+    for line in buffer.lines() {
+        if let Some(heading) = line.strip_prefix("## ") {
+            if let Some((block, lines)) = current.take() {
+                blocks.push(finish(block, &lines)?);
+            }
+            current = Some((parse_heading(heading)?, Vec::new()));
+        } else if let Some((_, lines)) = current.as_mut() {
+            lines.push(line);
+        }
+    }
+    if let Some((block, lines)) = current {
+        blocks.push(finish(block, &lines)?);
+    }
+
+    // An untouched `## new` placeholder isn't a note
+    blocks.retain(|b| b.index.is_some() || !b.text.is_empty());
+    Ok(blocks)
+}
+
+fn parse_heading(heading: &str) -> AppResult<Block> {
+    let mut index = None;
+    let mut tags = Vec::new();
+
+    for (i, word) in heading.split_whitespace().enumerate() {
+        if let Some(tag) = word.strip_prefix('#') {
+            if !tag.is_empty() {
+                tags.push(tag.to_string());
+            }
+        } else if i == 0 && word != "new" {
+            let number = word.strip_prefix('[').and_then(|w| w.strip_suffix(']'));
+            index = Some(
+                number
+                    .and_then(|n| n.parse::<usize>().ok())
+                    .ok_or_else(|| invalid(format!("Unrecognized heading '## {}': use '## [N]' or '## new'", heading)))?,
+            );
+        }
+        // The time is shown for reference; timestamps are kept
+    }
+
+    Ok(Block { index, tags, status: None, text: String::new() })
+}
+
+fn finish(mut block: Block, lines: &[&str]) -> AppResult<Block> {
+    let text = lines.join("\n");
+    let mut text = text.trim();
+
+    for status in [TaskStatus::Open, TaskStatus::Done, TaskStatus::Cancelled] {
+        if let Some(rest) = text.strip_prefix(checkbox(status)) {
+            block.status = Some(status);
+            text = rest.trim_start();
+            break;
+        }
+    }
+
+    if text.is_empty() && block.index.is_some() {
+        return Err(invalid(format!(
+            "Note [{}] is empty: delete its heading too to remove it",
+            block.index.unwrap_or_default()
+        )));
+    }
+    block.text = text.to_string();
+    Ok(block)
+}
+
+fn checkbox(status: TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Open => "[ ]",
+        TaskStatus::Done => "[x]",
+        TaskStatus::Cancelled => "[-]",
+    }
+}
+
+fn invalid(message: String) -> AppError {
+    AppError::InvalidParameters(format!("{}; nothing was saved", message))
+}
@@ -0,0 +1,82 @@
+use async_trait::async_trait;
+use crate::error::AppResult;
+use super::EmbeddingProvider;
+
+const DEFAULT_DIMENSIONS: usize = 384;
+
+/// Lexical embedder: feature hashing of words and character trigrams, not a language model.
+///
+/// It needs no download and runs on any CPU, but only matches notes that share words (or
+/// spellings of them), not ones that mean the same thing in other words. For that, use a
+/// model through `AIGENDA_EMBEDDINGS_URL`.
+pub struct HashingEmbedder {
+    dimensions: usize,
+    name: String,
+}
+
+impl HashingEmbedder {
+    pub fn new(dimensions: usize) -> Self {
+        Self {
+            dimensions,
+            name: format!("hashing-v1-{}", dimensions),
+        }
+    }
+
+    pub fn embed_one(&self, text: &str) -> Vec<f32> {
+        let mut vector = vec![0.0f32; self.dimensions];
+        let lowered = text.to_lowercase();
+
+        for word in lowered.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()) {
+            self.add_feature(&mut vector, word.as_bytes(), 1.0);
+
+            let padded: Vec<char> = format!(" {} ", word).chars().collect();
+            for gram in padded.windows(3) {
+                let gram: String = gram.iter().collect();
+                self.add_feature(&mut vector, gram.as_bytes(), 0.5);
+            }
+        }
+
+        let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            for v in &mut vector {
+                *v /= norm;
+            }
+        }
+        vector
+    }
+
+    fn add_feature(&self, vector: &mut [f32], feature: &[u8], weight: f32) {
+        let hash = fnv1a(feature);
+        let bucket = (hash % self.dimensions as u64) as usize;
+        // The top bit decides the sign so collisions tend to cancel out
+        let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+        vector[bucket] += sign * weight;
+    }
+}
+
+impl Default for HashingEmbedder {
+    fn default() -> Self {
+        Self::new(DEFAULT_DIMENSIONS)
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for HashingEmbedder {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn embed(&self, texts: &[String]) -> AppResult<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|t| self.embed_one(t)).collect())
+    }
+}
+
+/// FNV-1a, stable across platforms and Rust versions (unlike `DefaultHasher`)
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for b in bytes {
+        hash ^= *b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
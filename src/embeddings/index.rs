@@ -0,0 +1,319 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    error::{AppError, AppResult},
+    models::{DayLog, Editor, NoteRef, TrashedNote},
+    dates::DateRange,
+    storage::{DayIter, Storage, KEY_FILE},
+};
+use super::{cosine_similarity, provider_from_env, EmbeddingProvider};
+
+/// Lives inside the data dir, next to the day files, but in its own folder so
+/// it is never mistaken for a day file.
+const INDEX_DIR: &str = ".embeddings";
+const INDEX_FILE: &str = "index.json";
+const PENDING_FILE: &str = "pending";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct IndexEntry {
+    pub date: NaiveDate,
+    /// 1-based position of the note in its day
+    pub index: usize,
+    pub text: String,
+    pub vector: Vec<f32>,
+}
+
+/// Persistent brute-force vector index over every note
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VectorIndex {
+    pub provider: String,
+    pub entries: Vec<IndexEntry>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SearchHit {
+    pub note: NoteRef,
+    pub text: String,
+    pub score: f32,
+}
+
+impl VectorIndex {
+    pub fn new(provider: &str) -> Self {
+        Self {
+            provider: provider.to_string(),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn load(path: &Path) -> AppResult<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(path)
+            .map_err(|e| AppError::Storage(format!("Could not read index {}: {}", path.display(), e)))?;
+
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| AppError::Corrupt(format!("Could not parse index {}: {}", path.display(), e)))
+    }
+
+    pub fn save(&self, path: &Path) -> AppResult<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| AppError::Storage(format!("Could not create index directory: {}", e)))?;
+        }
+
+        let content = serde_json::to_string(self)
+            .map_err(|e| AppError::Storage(format!("Could not serialize index: {}", e)))?;
+
+        fs::write(path, content)
+            .map_err(|e| AppError::Storage(format!("Could not write index {}: {}", path.display(), e)))
+    }
+
+    pub fn find(&self, note: NoteRef) -> Option<&IndexEntry> {
+        self.entries.iter().find(|e| e.date == note.date && e.index == note.index)
+    }
+
+    /// Drops every entry of `date` and inserts the new ones
+    pub fn replace_day(&mut self, date: NaiveDate, entries: Vec<IndexEntry>) {
+        self.entries.retain(|e| e.date != date);
+        self.entries.extend(entries);
+    }
+
+    /// Best matches first; `exclude` keeps a note from matching itself
+    pub fn search(&self, query: &[f32], limit: usize, exclude: Option<NoteRef>) -> Vec<SearchHit> {
+        let mut hits: Vec<SearchHit> = self
+            .entries
+            .iter()
+            .filter(|e| exclude.is_none_or(|n| n.date != e.date || n.index != e.index))
+            .map(|e| SearchHit {
+                note: NoteRef::new(e.date, e.index),
+                text: e.text.clone(),
+                score: cosine_similarity(query, &e.vector),
+            })
+            .collect();
+
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(limit);
+        hits
+    }
+}
+
+/// Keeps the on-disk index in sync with storage and answers similarity queries
+pub struct SemanticIndex {
+    dir: PathBuf,
+    provider: Box<dyn EmbeddingProvider>,
+}
+
+impl SemanticIndex {
+    pub fn new(data_dir: &Path, provider: Box<dyn EmbeddingProvider>) -> Self {
+        Self {
+            dir: index_dir(data_dir),
+            provider,
+        }
+    }
+
+    /// Uses the provider configured through the environment
+    pub fn open(data_dir: &Path) -> AppResult<Self> {
+        Ok(Self::new(data_dir, provider_from_env()?))
+    }
+
+    fn index_path(&self) -> PathBuf {
+        self.dir.join(INDEX_FILE)
+    }
+
+    /// Re-embeds the days saved since the last refresh. The whole history is
+    /// (re)indexed when there is no index yet, the provider changed, or `rebuild` is set.
+    pub async fn refresh<S: Storage + ?Sized>(&self, store: &S, rebuild: bool) -> AppResult<VectorIndex> {
+        // The index keeps note text in the clear
+        if self.dir.parent().is_some_and(|data_dir| data_dir.join(KEY_FILE).exists()) {
+            return Err(AppError::Config(
+                "Semantic search is off for encrypted notes: its index would store them in the clear".to_string(),
+            ));
+        }
+
+        let existing = VectorIndex::load(&self.index_path())?
+            .filter(|index| !rebuild && index.provider == self.provider.name());
+
+        let (mut index, days) = match existing {
+            Some(index) => {
+                let mut days = Vec::new();
+                for date in read_pending(&self.dir)? {
+                    days.push(store.load_day(date)?);
+                }
+                (index, days)
+            }
+            None => (VectorIndex::new(self.provider.name()), store.iter_days()?),
+        };
+
+        for day in &days {
+            let entries = self.embed_day(&index, day).await?;
+            index.replace_day(day.date, entries);
+        }
+
+        index.save(&self.index_path())?;
+        clear_pending(&self.dir)?;
+        Ok(index)
+    }
+
+    /// Embeds a day's notes, reusing vectors of notes whose text did not change
+    async fn embed_day(&self, index: &VectorIndex, day: &DayLog) -> AppResult<Vec<IndexEntry>> {
+        let known: HashMap<&str, &Vec<f32>> = index
+            .entries
+            .iter()
+            .filter(|e| e.date == day.date)
+            .map(|e| (e.text.as_str(), &e.vector))
+            .collect();
+
+        let missing: Vec<String> = day
+            .notes
+            .iter()
+            .filter(|n| !known.contains_key(n.text.as_str()))
+            .map(|n| n.text.clone())
+            .collect();
+
+        let fresh = self.provider.embed(&missing).await?;
+        let fresh: HashMap<&str, Vec<f32>> = missing.iter().map(String::as_str).zip(fresh).collect();
+
+        Ok(day
+            .notes
+            .iter()
+            .enumerate()
+            .map(|(i, note)| IndexEntry {
+                date: day.date,
+                index: i + 1,
+                text: note.text.clone(),
+                vector: known
+                    .get(note.text.as_str())
+                    .map(|v| (*v).clone())
+                    .or_else(|| fresh.get(note.text.as_str()).cloned())
+                    .unwrap_or_default(),
+            })
+            .collect())
+    }
+
+    pub async fn search_text<S: Storage + ?Sized>(&self, store: &S, text: &str, limit: usize) -> AppResult<Vec<SearchHit>> {
+        let index = self.refresh(store, false).await?;
+        let query = self.provider.embed(&[text.to_string()]).await?;
+        let query = query.into_iter().next().unwrap_or_default();
+        Ok(index.search(&query, limit, None))
+    }
+
+    pub async fn search_similar<S: Storage + ?Sized>(&self, store: &S, note: NoteRef, limit: usize) -> AppResult<Vec<SearchHit>> {
+        let index = self.refresh(store, false).await?;
+        let entry = index
+            .find(note)
+            .ok_or_else(|| AppError::NotFound(format!("Note {}", note)))?;
+        Ok(index.search(&entry.vector, limit, Some(note)))
+    }
+}
+
+/// Storage decorator that flags every saved day for re-embedding.
+///
+/// Flagging is a cheap append, the actual embedding happens on the next
+/// `SemanticIndex::refresh` so saves never wait on a model.
+pub struct IndexedStorage<S: Storage> {
+    inner: S,
+    index_dir: PathBuf,
+}
+
+impl<S: Storage> IndexedStorage<S> {
+    pub fn new(inner: S, data_dir: &Path) -> Self {
+        Self {
+            inner,
+            index_dir: index_dir(data_dir),
+        }
+    }
+}
+
+impl<S: Storage> Storage for IndexedStorage<S> {
+    fn load_day(&self, date: NaiveDate) -> AppResult<DayLog> {
+        self.inner.load_day(date)
+    }
+
+    fn save_day(&self, day: &DayLog) -> AppResult<()> {
+        self.inner.save_day(day)?;
+        mark_pending(&self.index_dir, day.date)
+    }
+
+    fn list_dates(&self) -> AppResult<Vec<NaiveDate>> {
+        self.inner.list_dates()
+    }
+
+    fn delete_day(&self, date: NaiveDate) -> AppResult<()> {
+        self.inner.delete_day(date)?;
+        mark_pending(&self.index_dir, date)
+    }
+
+    fn exists(&self, date: NaiveDate) -> AppResult<bool> {
+        self.inner.exists(date)
+    }
+
+    fn load_trash(&self) -> AppResult<Vec<TrashedNote>> {
+        self.inner.load_trash()
+    }
+
+    fn save_trash(&self, trash: &[TrashedNote]) -> AppResult<()> {
+        self.inner.save_trash(trash)
+    }
+
+    fn outdated(&self) -> AppResult<Vec<(Option<NaiveDate>, u32)>> {
+        self.inner.outdated()
+    }
+
+    fn checkpoint(&self, editor: Editor) -> AppResult<()> {
+        self.inner.checkpoint(editor)
+    }
+
+    fn days_between(&self, range: DateRange) -> AppResult<DayIter<'_>> {
+        self.inner.days_between(range)
+    }
+}
+
+fn index_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(INDEX_DIR)
+}
+
+fn mark_pending(dir: &Path, date: NaiveDate) -> AppResult<()> {
+    fs::create_dir_all(dir)
+        .map_err(|e| AppError::Storage(format!("Could not create index directory: {}", e)))?;
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(PENDING_FILE))
+        .map_err(|e| AppError::Storage(format!("Could not open index queue: {}", e)))?;
+
+    writeln!(file, "{}", date.format("%Y-%m-%d"))
+        .map_err(|e| AppError::Storage(format!("Could not update index queue: {}", e)))
+}
+
+fn read_pending(dir: &Path) -> AppResult<BTreeSet<NaiveDate>> {
+    let path = dir.join(PENDING_FILE);
+    if !path.exists() {
+        return Ok(BTreeSet::new());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| AppError::Storage(format!("Could not read index queue: {}", e)))?;
+
+    Ok(content
+        .lines()
+        .filter_map(|line| NaiveDate::parse_from_str(line.trim(), "%Y-%m-%d").ok())
+        .collect())
+}
+
+fn clear_pending(dir: &Path) -> AppResult<()> {
+    let path = dir.join(PENDING_FILE);
+    if path.exists() {
+        fs::remove_file(&path)
+            .map_err(|e| AppError::Storage(format!("Could not clear index queue: {}", e)))?;
+    }
+    Ok(())
+}
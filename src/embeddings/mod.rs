@@ -0,0 +1,53 @@
+use async_trait::async_trait;
+use crate::error::AppResult;
+
+pub mod hashing;
+pub mod index;
+pub mod openai;
+
+pub use hashing::HashingEmbedder;
+pub use index::{IndexedStorage, SemanticIndex};
+pub use openai::OpenAiCompatEmbedder;
+
+/// Turns note text into fixed-size vectors for similarity search
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+    /// Stable identifier stored in the index; changing it forces a rebuild
+    fn name(&self) -> &str;
+
+    async fn embed(&self, texts: &[String]) -> AppResult<Vec<Vec<f32>>>;
+}
+
+/// Picks the provider from the environment.
+///
+/// `AIGENDA_EMBEDDINGS_URL` selects an OpenAI-compatible endpoint (Ollama, llama.cpp,
+/// LocalAI...) serving a real model; otherwise the built-in lexical embedder is used, so
+/// search works offline but only finds notes sharing words with the query.
+pub fn provider_from_env() -> AppResult<Box<dyn EmbeddingProvider>> {
+    match OpenAiCompatEmbedder::from_env()? {
+        Some(provider) => Ok(Box::new(provider)),
+        None => Ok(Box::new(HashingEmbedder::default())),
+    }
+}
+
+/// Cosine similarity; vectors of different length score 0
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+
+    let mut dot = 0.0;
+    let mut norm_a = 0.0;
+    let mut norm_b = 0.0;
+    for (x, y) in a.iter().zip(b) {
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a.sqrt() * norm_b.sqrt())
+    }
+}
@@ -0,0 +1,115 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::env;
+use crate::error::{AppError, AppResult};
+use super::EmbeddingProvider;
+
+/// Embeddings from any server speaking the OpenAI `/v1/embeddings` API
+/// (Ollama, llama.cpp server, LocalAI, vLLM...)
+pub struct OpenAiCompatEmbedder {
+    client: Client,
+    base_url: String,
+    model: String,
+    api_key: Option<String>,
+    name: String,
+}
+
+impl OpenAiCompatEmbedder {
+    pub fn new(base_url: &str, model: &str, api_key: Option<String>) -> Self {
+        let base_url = base_url.trim_end_matches('/').to_string();
+        Self {
+            client: Client::new(),
+            name: format!("openai-compat:{}@{}", model, base_url),
+            base_url,
+            model: model.to_string(),
+            api_key,
+        }
+    }
+
+    /// Reads `AIGENDA_EMBEDDINGS_URL`, `AIGENDA_EMBEDDINGS_MODEL` and `AIGENDA_EMBEDDINGS_API_KEY`.
+    /// Returns `None` when no URL is configured.
+    pub fn from_env() -> AppResult<Option<Self>> {
+        let Ok(base_url) = env::var("AIGENDA_EMBEDDINGS_URL") else {
+            return Ok(None);
+        };
+        let model = env::var("AIGENDA_EMBEDDINGS_MODEL")
+            .map_err(|_| AppError::Config(
+                "AIGENDA_EMBEDDINGS_MODEL must be set when AIGENDA_EMBEDDINGS_URL is used".to_string()
+            ))?;
+        let api_key = env::var("AIGENDA_EMBEDDINGS_API_KEY").ok();
+
+        Ok(Some(Self::new(&base_url, &model, api_key)))
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for OpenAiCompatEmbedder {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn embed(&self, texts: &[String]) -> AppResult<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut request = self
+            .client
+            .post(format!("{}/embeddings", self.base_url))
+            .json(&json!({ "model": self.model, "input": texts }));
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| AppError::Network(format!("Embeddings request failed: {}", e)))?;
+
+        let status = response.status().as_u16();
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(AppError::Api {
+                status,
+                body: error_text,
+            });
+        }
+
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| AppError::Network(format!("Failed to read embeddings response: {}", e)))?;
+
+        let data = body["data"].as_array()
+            .ok_or_else(|| AppError::Api {
+                status,
+                body: format!("Unexpected embeddings response format: {}", body),
+            })?;
+
+        let mut vectors = vec![Vec::new(); texts.len()];
+        for (position, item) in data.iter().enumerate() {
+            let slot = item["index"].as_u64().map(|i| i as usize).unwrap_or(position);
+            let vector = item["embedding"].as_array()
+                .ok_or_else(|| AppError::Api {
+                    status,
+                    body: "Embedding missing from response".to_string(),
+                })?
+                .iter()
+                .map(|v| v.as_f64().unwrap_or(0.0) as f32)
+                .collect();
+            if let Some(target) = vectors.get_mut(slot) {
+                *target = vector;
+            }
+        }
+
+        if vectors.iter().any(|v| v.is_empty()) {
+            return Err(AppError::Api {
+                status,
+                body: "Embeddings response did not cover every input".to_string(),
+            });
+        }
+
+        Ok(vectors)
+    }
+}
@@ -1,6 +1,24 @@
+use serde_json::{json, Value};
 use thiserror::Error;
 
+/// Every failure the app can surface.
+///
+/// Each variant maps to a process exit code (see [`AppError::exit_code`]), so
+/// scripts can tell "bad input" from "network down" without parsing messages:
+///
+/// | code | kind                                        |
+/// |------|---------------------------------------------|
+/// | 1    | `io`, `json`, `storage` (anything else)     |
+/// | 2    | `invalid_parameters`, `date_parse`, `tool_not_found` |
+/// | 3    | `config`                                    |
+/// | 4    | `not_found`                                 |
+/// | 5    | `conflict`                                  |
+/// | 6    | `permission_denied`                         |
+/// | 7    | `network`                                   |
+/// | 8    | `api`                                       |
+/// | 9    | `corrupt`                                   |
 #[derive(Error, Debug)]
+#[allow(dead_code)] // not every variant is produced in every feature build
 pub enum AppError {
     #[error("io: {0}")]
     Io(#[from] std::io::Error),
@@ -10,6 +28,80 @@ pub enum AppError {
     ChronoParse(#[from] chrono::ParseError),
     #[error("storage: {0}")]
     Storage(String),
+    /// Missing or invalid settings: API keys, config files, data directories
+    #[error("config: {0}")]
+    Config(String),
+    /// The remote end could not be reached or the connection broke
+    #[error("network: {0}")]
+    Network(String),
+    /// A remote API answered, but with an error or an unusable reply
+    #[error("api error ({status}): {body}")]
+    Api { status: u16, body: String },
+    #[error("unknown tool: {0}")]
+    ToolNotFound(String),
+    #[error("invalid parameters: {0}")]
+    InvalidParameters(String),
+    #[error("permission denied: {0}")]
+    PermissionDenied(String),
+    #[error("not found: {0}")]
+    NotFound(String),
+    #[error("conflict: {0}")]
+    Conflict(String),
+    /// Stored data exists but can't be decoded
+    #[error("corrupt data: {0}")]
+    Corrupt(String),
+}
+
+impl AppError {
+    /// Stable, machine-friendly name of the variant
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AppError::Io(_) => "io",
+            AppError::Json(_) => "json",
+            AppError::ChronoParse(_) => "date_parse",
+            AppError::Storage(_) => "storage",
+            AppError::Config(_) => "config",
+            AppError::Network(_) => "network",
+            AppError::Api { .. } => "api",
+            AppError::ToolNotFound(_) => "tool_not_found",
+            AppError::InvalidParameters(_) => "invalid_parameters",
+            AppError::PermissionDenied(_) => "permission_denied",
+            AppError::NotFound(_) => "not_found",
+            AppError::Conflict(_) => "conflict",
+            AppError::Corrupt(_) => "corrupt",
+        }
+    }
+
+    /// Process exit code for this error; documented on [`AppError`]
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            AppError::Io(_) | AppError::Json(_) | AppError::Storage(_) => 1,
+            AppError::InvalidParameters(_) | AppError::ChronoParse(_) | AppError::ToolNotFound(_) => 2,
+            AppError::Config(_) => 3,
+            AppError::NotFound(_) => 4,
+            AppError::Conflict(_) => 5,
+            AppError::PermissionDenied(_) => 6,
+            AppError::Network(_) => 7,
+            AppError::Api { .. } => 8,
+            AppError::Corrupt(_) => 9,
+        }
+    }
+
+    /// `{"error": {"kind", "code", "message", ...}}`, as printed on stderr with `--error-format json`
+    pub fn to_json(&self) -> Value {
+        let mut error = json!({
+            "kind": self.kind(),
+            "code": self.exit_code(),
+            "message": self.to_string(),
+        });
+
+        if let AppError::Api { status, body } = self {
+            error["status"] = json!(status);
+            error["body"] = json!(body);
+        }
+
+        json!({ "error": error })
+    }
 }
 
 pub type AppResult<T> = Result<T, AppError>;
@@ -0,0 +1,412 @@
+//! Keys for encrypted storage. Day files are sealed with a random data key; `key.json` in the
+//! data dir keeps that key wrapped under one derived (with scrypt) from a passphrase or a
+//! keyfile. A session can skip the derivation by caching the data key in `AIGENDA_KEY` or in
+//! a `aigenda key agent` process.
+
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use aes_gcm::{
+    aead::{Aead, KeyInit, Payload},
+    Aes256Gcm, Nonce,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::Config,
+    error::{AppError, AppResult},
+    storage::KEY_FILE,
+};
+
+/// scrypt cost (2^15 iterations) for new keys
+pub const DEFAULT_LOG_N: u8 = 15;
+
+const KEY_ENV: &str = "AIGENDA_KEY";
+const PASSPHRASE_ENV: &str = "AIGENDA_PASSPHRASE";
+const SOCKET_ENV: &str = "AIGENDA_KEY_SOCK";
+const NONCE_LEN: usize = 12;
+const CHECK: &[u8] = b"aigenda";
+
+/// The key day files are encrypted with; wiped from memory when dropped
+pub struct DataKey([u8; 32]);
+
+impl DataKey {
+    pub fn generate() -> AppResult<Self> {
+        let mut key = [0u8; 32];
+        fill_random(&mut key)?;
+        Ok(Self(key))
+    }
+
+    pub fn to_hex(&self) -> String {
+        to_hex(&self.0)
+    }
+
+    pub fn from_hex(hex: &str) -> AppResult<Self> {
+        let bytes = from_hex(hex.trim())
+            .filter(|b| b.len() == 32)
+            .ok_or_else(|| AppError::InvalidParameters("A cached key must be 64 hex digits".to_string()))?;
+        let mut key = [0u8; 32];
+        key.copy_from_slice(&bytes);
+        Ok(Self(key))
+    }
+
+    /// `nonce || ciphertext`, authenticated together with `aad`
+    pub fn seal(&self, aad: &[u8], plaintext: &[u8]) -> AppResult<Vec<u8>> {
+        seal(&self.0, aad, plaintext)
+    }
+
+    pub fn open(&self, aad: &[u8], sealed: &[u8]) -> AppResult<Vec<u8>> {
+        open(&self.0, aad, sealed)
+    }
+}
+
+impl Drop for DataKey {
+    fn drop(&mut self) {
+        self.0.iter_mut().for_each(|b| *b = 0);
+    }
+}
+
+/// Where the secret that unwraps the data key comes from
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretKind {
+    Passphrase,
+    Keyfile,
+}
+
+/// `key.json`: the wrapped data key and how to unwrap it
+#[derive(Serialize, Deserialize, Debug)]
+pub struct KeyFile {
+    pub version: u32,
+    pub secret: SecretKind,
+    /// Day files are stored under random names, listed in an encrypted mapping
+    #[serde(default)]
+    pub encrypt_filenames: bool,
+    log_n: u8,
+    salt: String,
+    wrapped: String,
+    /// A known value sealed with the data key, to tell a wrong cached key from corruption
+    check: String,
+    /// During `aigenda key rotate`, the old data key sealed with the new one, so files not
+    /// re-encrypted yet still open
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    previous: Option<String>,
+}
+
+impl KeyFile {
+    /// Wraps `key` under a secret; `log_n` is the scrypt cost
+    pub fn new(key: &DataKey, secret: &[u8], kind: SecretKind, encrypt_filenames: bool, log_n: u8) -> AppResult<Self> {
+        let mut salt = [0u8; 16];
+        fill_random(&mut salt)?;
+        let kek = derive(secret, &salt, log_n)?;
+
+        Ok(Self {
+            version: 1,
+            secret: kind,
+            encrypt_filenames,
+            log_n,
+            salt: to_hex(&salt),
+            wrapped: to_hex(&seal(&kek, b"aigenda key", &key.0)?),
+            check: to_hex(&key.seal(b"check", CHECK)?),
+            previous: None,
+        })
+    }
+
+    /// Keeps `previous` readable by whoever holds `key`, until `forget_previous`
+    pub fn keep_previous(&mut self, key: &DataKey, previous: &DataKey) -> AppResult<()> {
+        self.previous = Some(to_hex(&key.seal(b"previous key", &previous.0)?));
+        Ok(())
+    }
+
+    /// The key files may still be sealed with, when a rotation didn't finish
+    pub fn previous_key(&self, key: &DataKey) -> AppResult<Option<DataKey>> {
+        let Some(previous) = &self.previous else {
+            return Ok(None);
+        };
+        let sealed = from_hex(previous).ok_or_else(|| corrupt("previous key"))?;
+        let bytes = key.open(b"previous key", &sealed).map_err(|_| corrupt("previous key"))?;
+        let mut previous = [0u8; 32];
+        previous.copy_from_slice(&bytes);
+        Ok(Some(DataKey(previous)))
+    }
+
+    pub fn forget_previous(&mut self) {
+        self.previous = None;
+    }
+
+    pub fn path(data_dir: &Path) -> PathBuf {
+        data_dir.join(KEY_FILE)
+    }
+
+    pub fn load(data_dir: &Path) -> AppResult<Option<Self>> {
+        let path = Self::path(data_dir);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path)
+            .map_err(|e| AppError::Storage(format!("Could not read {}: {}", path.display(), e)))?;
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| AppError::Corrupt(format!("Could not parse {}: {}", path.display(), e)))
+    }
+
+    pub fn save(&self, data_dir: &Path) -> AppResult<()> {
+        let path = Self::path(data_dir);
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| AppError::Storage(format!("Could not serialize key file: {}", e)))?;
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, content)
+            .and_then(|_| fs::rename(&tmp, &path))
+            .map_err(|e| AppError::Storage(format!("Could not write {}: {}", path.display(), e)))
+    }
+
+    pub fn unwrap_key(&self, secret: &[u8]) -> AppResult<DataKey> {
+        let salt = from_hex(&self.salt).ok_or_else(|| corrupt("salt"))?;
+        let wrapped = from_hex(&self.wrapped).ok_or_else(|| corrupt("wrapped key"))?;
+        let kek = derive(secret, &salt, self.log_n)?;
+
+        let bytes = open(&kek, b"aigenda key", &wrapped)
+            .map_err(|_| AppError::PermissionDenied("Wrong passphrase or keyfile".to_string()))?;
+        let mut key = [0u8; 32];
+        key.copy_from_slice(&bytes);
+        Ok(DataKey(key))
+    }
+
+    /// Checks a cached key belongs to this data
+    pub fn verify(&self, key: &DataKey) -> AppResult<()> {
+        let check = from_hex(&self.check).ok_or_else(|| corrupt("check value"))?;
+        match key.open(b"check", &check) {
+            Ok(value) if value == CHECK => Ok(()),
+            _ => Err(AppError::PermissionDenied(
+                "The cached key doesn't match this data (was it rotated?); unlock again".to_string(),
+            )),
+        }
+    }
+}
+
+/// Finds the data key: `AIGENDA_KEY`, then a running key agent, then the configured keyfile,
+/// then `AIGENDA_PASSPHRASE` or a passphrase prompt
+pub fn unlock(data_dir: &Path, key_file: &KeyFile, config: &Config) -> AppResult<DataKey> {
+    if let Ok(hex) = std::env::var(KEY_ENV) {
+        let key = DataKey::from_hex(&hex)?;
+        key_file.verify(&key)?;
+        return Ok(key);
+    }
+
+    #[cfg(unix)]
+    if let Some(key) = agent::fetch(&socket_path()?)? {
+        key_file.verify(&key)?;
+        return Ok(key);
+    }
+
+    let secret = read_secret(key_file.secret, config, &format!("Passphrase for {}: ", data_dir.display()))?;
+    key_file.unwrap_key(&secret)
+}
+
+/// The secret for a key file: the configured keyfile's bytes, or a passphrase
+pub fn read_secret(kind: SecretKind, config: &Config, prompt: &str) -> AppResult<Vec<u8>> {
+    match kind {
+        SecretKind::Keyfile => {
+            let path = config
+                .encryption
+                .as_ref()
+                .and_then(|e| e.keyfile.as_ref())
+                .ok_or_else(|| AppError::Config("Set \"encryption\": {\"keyfile\": ...} in config.json".to_string()))?;
+            read_keyfile(path)
+        }
+        SecretKind::Passphrase => {
+            if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+                return Ok(passphrase.into_bytes());
+            }
+            prompt_passphrase(prompt).map(String::into_bytes)
+        }
+    }
+}
+
+/// A passphrase for a new key: `AIGENDA_PASSPHRASE`, or asked for twice
+pub fn new_passphrase() -> AppResult<Vec<u8>> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+        return Ok(passphrase.into_bytes());
+    }
+    let passphrase = prompt_passphrase("New passphrase: ")?;
+    if prompt_passphrase("Repeat passphrase: ")? != passphrase {
+        return Err(AppError::InvalidParameters("The passphrases don't match".to_string()));
+    }
+    Ok(passphrase.into_bytes())
+}
+
+pub fn read_keyfile(path: &Path) -> AppResult<Vec<u8>> {
+    let bytes = fs::read(path)
+        .map_err(|e| AppError::Config(format!("Could not read keyfile {}: {}", path.display(), e)))?;
+    if bytes.len() < 16 {
+        return Err(AppError::Config(format!("Keyfile {} is too short (at least 16 bytes)", path.display())));
+    }
+    Ok(bytes)
+}
+
+/// Reads a line from stdin, hiding it when stdin is a terminal
+pub fn prompt_passphrase(prompt: &str) -> AppResult<String> {
+    eprint!("{}", prompt);
+    io::stderr().flush()?;
+
+    let terminal = io::stdin().is_terminal();
+    if terminal {
+        let _ = Command::new("stty").arg("-echo").status();
+    }
+    let mut line = String::new();
+    let read = io::stdin().lock().read_line(&mut line);
+    if terminal {
+        let _ = Command::new("stty").arg("echo").status();
+        eprintln!();
+    }
+    read?;
+
+    let passphrase = line.trim_end_matches(['\r', '\n']).to_string();
+    if passphrase.is_empty() {
+        return Err(AppError::InvalidParameters("Empty passphrase".to_string()));
+    }
+    Ok(passphrase)
+}
+
+/// `AIGENDA_KEY_SOCK`, or `key.sock` in an `aigenda` folder of the runtime dir (without one,
+/// an `agent` folder of the config dir); named notebooks have their own, as their keys differ
+pub fn socket_path() -> AppResult<PathBuf> {
+    if let Ok(path) = std::env::var(SOCKET_ENV) {
+        return Ok(PathBuf::from(path));
+    }
+    let dir = match std::env::var("XDG_RUNTIME_DIR") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir).join("aigenda"),
+        _ => Config::config_dir()?.join("agent"),
+    };
+    match crate::notebooks::current() {
+        Ok(notebook) if notebook.name != crate::notebooks::MAIN => Ok(dir.join(format!("key-{}.sock", notebook.name))),
+        _ => Ok(dir.join("key.sock")),
+    }
+}
+
+/// A process holding the data key in memory and handing it to local clients for a while
+#[cfg(unix)]
+pub mod agent {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::{
+        fs::{DirBuilderExt, PermissionsExt},
+        net::{UnixListener, UnixStream},
+    };
+    use std::path::Path;
+    use std::time::{Duration, Instant};
+
+    use super::DataKey;
+    use crate::error::{AppError, AppResult};
+
+    /// The key from a running agent, or `None` when there is none
+    pub fn fetch(socket: &Path) -> AppResult<Option<DataKey>> {
+        let Ok(stream) = UnixStream::connect(socket) else {
+            return Ok(None);
+        };
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line)?;
+        if line.trim().is_empty() {
+            return Ok(None);
+        }
+        DataKey::from_hex(&line).map(Some)
+    }
+
+    /// Serves the key on `socket` until `ttl` has passed. The socket must be in a folder only
+    /// the user can open, so nobody else can connect in the moment before it's made private.
+    pub fn serve(socket: &Path, key: &DataKey, ttl: Duration) -> AppResult<()> {
+        private_dir(socket.parent().unwrap_or(Path::new(".")))?;
+        let _ = std::fs::remove_file(socket);
+        let listener = UnixListener::bind(socket)
+            .map_err(|e| AppError::Config(format!("Could not listen on {}: {}", socket.display(), e)))?;
+        std::fs::set_permissions(socket, std::fs::Permissions::from_mode(0o600))?;
+        listener.set_nonblocking(true)?;
+
+        let deadline = Instant::now() + ttl;
+        let result = loop {
+            if Instant::now() >= deadline {
+                break Ok(());
+            }
+            match listener.accept() {
+                Ok((mut stream, _)) => {
+                    let _ = writeln!(stream, "{}", key.to_hex());
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => std::thread::sleep(Duration::from_millis(50)),
+                Err(e) => break Err(e.into()),
+            }
+        };
+        let _ = std::fs::remove_file(socket);
+        result
+    }
+
+    /// Creates `dir` for the user alone, or checks an existing one is closed to others
+    fn private_dir(dir: &Path) -> AppResult<()> {
+        std::fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(dir)
+            .map_err(|e| AppError::Config(format!("Could not create {}: {}", dir.display(), e)))?;
+        if std::fs::metadata(dir)?.permissions().mode() & 0o077 != 0 {
+            return Err(AppError::PermissionDenied(format!(
+                "{} is open to other users; put the key agent's socket in a private folder",
+                dir.display()
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn derive(secret: &[u8], salt: &[u8], log_n: u8) -> AppResult<[u8; 32]> {
+    let params = scrypt::Params::new(log_n, 8, 1, 32)
+        .map_err(|e| AppError::Corrupt(format!("Invalid key derivation parameters: {}", e)))?;
+    let mut kek = [0u8; 32];
+    scrypt::scrypt(secret, salt, &params, &mut kek)
+        .map_err(|e| AppError::Storage(format!("Key derivation failed: {}", e)))?;
+    Ok(kek)
+}
+
+fn seal(key: &[u8; 32], aad: &[u8], plaintext: &[u8]) -> AppResult<Vec<u8>> {
+    let mut nonce = [0u8; NONCE_LEN];
+    fill_random(&mut nonce)?;
+    let cipher = Aes256Gcm::new(key.into());
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), Payload { msg: plaintext, aad })
+        .map_err(|_| AppError::Storage("Encryption failed".to_string()))?;
+
+    let mut sealed = nonce.to_vec();
+    sealed.extend(ciphertext);
+    Ok(sealed)
+}
+
+fn open(key: &[u8; 32], aad: &[u8], sealed: &[u8]) -> AppResult<Vec<u8>> {
+    if sealed.len() < NONCE_LEN {
+        return Err(AppError::Corrupt("Encrypted data is truncated".to_string()));
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    Aes256Gcm::new(key.into())
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad })
+        .map_err(|_| AppError::Corrupt("Could not decrypt: wrong key, or the data was modified".to_string()))
+}
+
+fn fill_random(buf: &mut [u8]) -> AppResult<()> {
+    getrandom::getrandom(buf).map_err(|e| AppError::Storage(format!("No randomness available: {}", e)))
+}
+
+fn corrupt(what: &str) -> AppError {
+    AppError::Corrupt(format!("Invalid {} in {}", what, KEY_FILE))
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| hex.get(i..i + 2).and_then(|b| u8::from_str_radix(b, 16).ok()))
+        .collect()
+}
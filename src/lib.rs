@@ -1,10 +1,25 @@
+pub mod backup;
+pub mod clock;
+pub mod dates;
+pub mod day_edit;
 pub mod error;
+#[cfg(feature = "encryption")]
+pub mod keys;
+pub mod migrate;
 pub mod models;
+pub mod notebooks;
+pub mod notes;
+pub mod recurrence;
+pub mod schema;
 pub mod storage;
+pub mod tasks;
+pub mod templates;
+pub mod trash;
 pub mod config;
 pub mod app;
 pub mod cli;
 pub mod commands;
+pub mod output;
 
 #[cfg(feature = "ai")]
 pub mod ai;
@@ -12,3 +27,9 @@ pub mod ai;
 #[cfg(feature = "ai")]
 pub mod agent;
 
+#[cfg(feature = "ai")]
+pub mod embeddings;
+
+#[cfg(feature = "ai")]
+pub mod mcp;
+
@@ -3,32 +3,74 @@ mod agent;
 #[cfg(feature = "ai")]
 mod ai;
 mod app;
+mod backup;
 mod cli;
+mod clock;
 mod commands;
 mod config;
+mod dates;
+mod day_edit;
+#[cfg(feature = "ai")]
+mod embeddings;
 mod error;
+#[cfg(feature = "encryption")]
+mod keys;
+#[cfg(feature = "ai")]
+mod mcp;
+mod migrate;
 mod models;
+mod notebooks;
+mod notes;
+mod output;
+mod recurrence;
+mod schema;
 mod storage;
+mod tasks;
+mod templates;
+mod trash;
 
 use clap::Parser;
+use cli::{ErrorFormat, OutputFormat};
+use error::AppResult;
 
 #[cfg(feature = "ai")]
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() {
     let cli = cli::Cli::parse();
-    let app = app::build_default(cli)?;
-    app.run().await?;
-    Ok(())
+    let error_format = error_format(&cli);
+
+    let result = app::run(cli).await;
+    exit_on_error(result, error_format);
 }
 
 #[cfg(not(feature = "ai"))]
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+fn main() {
     let cli = cli::Cli::parse();
-    let app = app::build_default(cli)?;
+    let error_format = error_format(&cli);
 
     // Run with basic tokio runtime for non-AI builds
-    let rt = tokio::runtime::Runtime::new()?;
-    rt.block_on(app.run())?;
+    let result = tokio::runtime::Runtime::new()
+        .map_err(Into::into)
+        .and_then(|rt| rt.block_on(app::run(cli)));
+    exit_on_error(result, error_format);
+}
+
+/// Structured output implies structured errors
+fn error_format(cli: &cli::Cli) -> ErrorFormat {
+    if cli.output == OutputFormat::Text {
+        cli.error_format
+    } else {
+        ErrorFormat::Json
+    }
+}
+
+/// Reports the error on stderr and exits with its documented exit code
+fn exit_on_error(result: AppResult<()>, format: ErrorFormat) {
+    let Err(error) = result else { return };
 
-    Ok(())
+    match format {
+        ErrorFormat::Text => eprintln!("Error: {}", error),
+        ErrorFormat::Json => eprintln!("{}", error.to_json()),
+    }
+    std::process::exit(error.exit_code());
 }
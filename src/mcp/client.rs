@@ -0,0 +1,228 @@
+use serde_json::{json, Value};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::Mutex;
+use tokio::time::timeout;
+
+use crate::config::McpServerConfig;
+use crate::error::{AppError, AppResult};
+use super::protocol::{error_response, notification, request, response, RpcError, LATEST_PROTOCOL_VERSION, INVALID_PARAMS, METHOD_NOT_FOUND};
+
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(2);
+/// `AppError::Api` status for errors from a server over stdio, which has no HTTP status
+pub const NO_STATUS: u16 = 0;
+
+/// Connection to an MCP server running as a child process
+pub struct McpClient {
+    name: String,
+    io: Mutex<ClientIo>,
+    next_id: AtomicU64,
+    timeout: Duration,
+}
+
+struct ClientIo {
+    child: Child,
+    /// `None` once shut down; dropping it closes the server's stdin
+    stdin: Option<ChildStdin>,
+    stdout: Lines<BufReader<ChildStdout>>,
+}
+
+impl McpClient {
+    /// Spawns the server and performs the `initialize` handshake
+    pub async fn spawn(name: &str, config: &McpServerConfig) -> AppResult<Self> {
+        let mut child = Command::new(&config.command)
+            .args(&config.args)
+            .envs(&config.env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| AppError::Config(format!("Could not start MCP server '{}': {}", name, e)))?;
+
+        let stdin = child.stdin.take();
+        let stdout = child.stdout.take()
+            .ok_or_else(|| AppError::Config(format!("MCP server '{}' has no stdout", name)))?;
+
+        let client = Self {
+            name: name.to_string(),
+            io: Mutex::new(ClientIo {
+                child,
+                stdin,
+                stdout: BufReader::new(stdout).lines(),
+            }),
+            next_id: AtomicU64::new(1),
+            timeout: Duration::from_secs(config.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS)),
+        };
+
+        match timeout(STARTUP_TIMEOUT, client.initialize()).await {
+            Ok(result) => result?,
+            Err(_) => {
+                client.shutdown().await;
+                return Err(AppError::Network(format!("MCP server '{}' did not initialize in time", name)));
+            }
+        }
+
+        Ok(client)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn initialize(&self) -> AppResult<()> {
+        self.request("initialize", json!({
+            "protocolVersion": LATEST_PROTOCOL_VERSION,
+            "capabilities": {},
+            "clientInfo": { "name": "aigenda", "version": env!("CARGO_PKG_VERSION") }
+        })).await?;
+
+        let mut io = self.io.lock().await;
+        io.send(&notification("notifications/initialized", json!({}))).await
+    }
+
+    /// All tool definitions, following pagination cursors
+    pub async fn list_tools(&self) -> AppResult<Vec<Value>> {
+        let mut tools = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let params = match &cursor {
+                Some(c) => json!({ "cursor": c }),
+                None => json!({}),
+            };
+            let result = self.request("tools/list", params).await?;
+
+            if let Some(page) = result["tools"].as_array() {
+                tools.extend(page.iter().cloned());
+            }
+
+            cursor = result["nextCursor"].as_str().map(str::to_string);
+            if cursor.is_none() {
+                return Ok(tools);
+            }
+        }
+    }
+
+    /// Calls a tool and flattens its text content; `isError` results become errors
+    pub async fn call_tool(&self, tool: &str, arguments: &Value) -> AppResult<String> {
+        let arguments = if arguments.is_null() { json!({}) } else { arguments.clone() };
+        let result = self.request("tools/call", json!({ "name": tool, "arguments": arguments })).await?;
+
+        let text = result["content"]
+            .as_array()
+            .map(|items| {
+                items
+                    .iter()
+                    .map(|item| match item["type"].as_str() {
+                        Some("text") => item["text"].as_str().unwrap_or_default().to_string(),
+                        Some(other) => format!("[{} content]", other),
+                        None => String::new(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+            .unwrap_or_default();
+
+        if result["isError"].as_bool().unwrap_or(false) {
+            Err(AppError::Api { status: NO_STATUS, body: format!("{} ({}): {}", tool, self.name, text) })
+        } else {
+            Ok(text)
+        }
+    }
+
+    /// Sends a request and waits for its response, bounded by the configured timeout
+    pub async fn request(&self, method: &str, params: Value) -> AppResult<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut io = self.io.lock().await;
+
+        match timeout(self.timeout, io.exchange(id, &request(id, method, params))).await {
+            Ok(Ok(Ok(result))) => Ok(result),
+            Ok(Ok(Err(error))) => Err(rejected(&self.name, method, &error)),
+            Ok(Err(e)) => Err(e),
+            Err(_) => Err(AppError::Network(format!(
+                "MCP server '{}' timed out after {}s on {}", self.name, self.timeout.as_secs(), method
+            ))),
+        }
+    }
+
+    /// Closes stdin so the server can exit on its own, then kills it if it lingers
+    pub async fn shutdown(&self) {
+        let mut io = self.io.lock().await;
+        io.stdin.take();
+
+        if timeout(SHUTDOWN_GRACE, io.child.wait()).await.is_err() {
+            let _ = io.child.kill().await;
+        }
+    }
+}
+
+impl ClientIo {
+    async fn send(&mut self, message: &Value) -> AppResult<()> {
+        let stdin = self.stdin.as_mut()
+            .ok_or_else(|| AppError::Network("MCP server connection is closed".to_string()))?;
+
+        let mut line = serde_json::to_string(message)?;
+        line.push('\n');
+        stdin.write_all(line.as_bytes()).await?;
+        stdin.flush().await?;
+        Ok(())
+    }
+
+    /// Writes a request and reads until its response arrives, answering
+    /// server-initiated requests and skipping notifications on the way
+    async fn exchange(&mut self, id: u64, message: &Value) -> AppResult<Result<Value, RpcError>> {
+        self.send(message).await?;
+
+        loop {
+            let line = self.stdout.next_line().await?
+                .ok_or_else(|| AppError::Network("MCP server closed the connection".to_string()))?;
+
+            let Ok(incoming) = serde_json::from_str::<Value>(&line) else {
+                continue;
+            };
+
+            if let Some(method) = incoming["method"].as_str() {
+                if let Some(request_id) = incoming.get("id").cloned() {
+                    let reply = if method == "ping" {
+                        response(request_id, json!({}))
+                    } else {
+                        error_response(request_id, &RpcError::new(METHOD_NOT_FOUND, format!("Unsupported: {}", method)))
+                    };
+                    self.send(&reply).await?;
+                }
+                continue;
+            }
+
+            if incoming["id"].as_u64() != Some(id) {
+                // Late response to a request that already timed out
+                continue;
+            }
+
+            if let Some(error) = incoming.get("error") {
+                return Ok(Err(RpcError::new(
+                    error["code"].as_i64().unwrap_or_default(),
+                    error["message"].as_str().unwrap_or("unknown error"),
+                )));
+            }
+
+            return Ok(Ok(incoming.get("result").cloned().unwrap_or(Value::Null)));
+        }
+    }
+}
+
+/// The error for a request the server answered with a JSON-RPC error. Servers report unknown
+/// tools as invalid params, named in the message.
+fn rejected(server: &str, method: &str, error: &RpcError) -> AppError {
+    let message = format!("MCP server '{}' rejected {}: {} ({})", server, method, error.message, error.code);
+    match error.code {
+        INVALID_PARAMS if error.message.starts_with("Unknown tool") => AppError::ToolNotFound(message),
+        INVALID_PARAMS => AppError::InvalidParameters(message),
+        _ => AppError::Api { status: NO_STATUS, body: message },
+    }
+}
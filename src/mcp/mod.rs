@@ -0,0 +1,8 @@
+//! Model Context Protocol (JSON-RPC 2.0 over stdio) support
+
+pub mod client;
+pub mod protocol;
+pub mod server;
+
+pub use client::McpClient;
+pub use server::McpServer;
@@ -0,0 +1,57 @@
+use serde_json::{json, Value};
+
+pub const JSONRPC_VERSION: &str = "2.0";
+pub const LATEST_PROTOCOL_VERSION: &str = "2025-06-18";
+pub const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2024-11-05", "2025-03-26", "2025-06-18"];
+
+// Standard JSON-RPC error codes
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+
+/// Error object carried by a JSON-RPC error response
+#[derive(Debug, Clone)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(INVALID_PARAMS, message)
+    }
+}
+
+pub fn request(id: u64, method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": JSONRPC_VERSION, "id": id, "method": method, "params": params })
+}
+
+pub fn notification(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": JSONRPC_VERSION, "method": method, "params": params })
+}
+
+pub fn response(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": JSONRPC_VERSION, "id": id, "result": result })
+}
+
+pub fn error_response(id: Value, error: &RpcError) -> Value {
+    json!({
+        "jsonrpc": JSONRPC_VERSION,
+        "id": id,
+        "error": { "code": error.code, "message": error.message }
+    })
+}
+
+/// Name under which a tool action is exposed, e.g. `notes_create`
+pub fn tool_name(tool: &str, action: &str) -> String {
+    format!("{}_{}", tool, action)
+}
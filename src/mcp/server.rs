@@ -0,0 +1,219 @@
+use chrono::NaiveDate;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+use crate::agent::ToolRegistry;
+use crate::error::{AppError, AppResult};
+use crate::storage::Storage;
+use super::protocol::{
+    error_response, response, tool_name, RpcError, INTERNAL_ERROR, INVALID_REQUEST,
+    LATEST_PROTOCOL_VERSION, METHOD_NOT_FOUND, PARSE_ERROR, SUPPORTED_PROTOCOL_VERSIONS,
+};
+
+const RESOURCE_PREFIX: &str = "aigenda://notes/";
+
+/// One registry tool action exposed as an MCP tool
+struct ExposedTool {
+    name: String,
+    tool: String,
+    action: String,
+    description: String,
+    input_schema: Value,
+}
+
+/// Serves the registry's tools and the journal's days to MCP clients
+pub struct McpServer<'a, S: Storage> {
+    registry: ToolRegistry,
+    store: &'a S,
+    tools: Vec<ExposedTool>,
+}
+
+impl<'a, S: Storage> McpServer<'a, S> {
+    pub fn new(registry: ToolRegistry, store: &'a S) -> Self {
+        let mut tools = Vec::new();
+
+        let mut names = registry.list_tools();
+        names.sort();
+        for name in names {
+            let Some(tool) = registry.get_tool(&name) else { continue };
+            let schema = tool.get_schema();
+            for action in &schema.actions {
+                tools.push(ExposedTool {
+                    name: tool_name(&schema.name, &action.name),
+                    tool: schema.name.clone(),
+                    action: action.name.clone(),
+                    description: format!("{} ({})", action.description, schema.description),
+                    input_schema: action.input_schema(),
+                });
+            }
+        }
+
+        Self { registry, store, tools }
+    }
+
+    /// Reads newline-delimited JSON-RPC messages from stdin until EOF
+    pub async fn serve_stdio(&self) -> AppResult<()> {
+        let mut lines = BufReader::new(tokio::io::stdin()).lines();
+        let mut stdout = tokio::io::stdout();
+
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let reply = match serde_json::from_str::<Value>(&line) {
+                Ok(message) => self.handle_message(message).await,
+                Err(e) => Some(error_response(Value::Null, &RpcError::new(PARSE_ERROR, e.to_string()))),
+            };
+
+            if let Some(reply) = reply {
+                let mut out = serde_json::to_string(&reply)?;
+                out.push('\n');
+                stdout.write_all(out.as_bytes()).await?;
+                stdout.flush().await?;
+            }
+        }
+
+        self.registry.shutdown().await;
+        Ok(())
+    }
+
+    /// Handles one message; notifications and stray responses produce no reply
+    pub async fn handle_message(&self, message: Value) -> Option<Value> {
+        let id = message.get("id").cloned();
+        let Some(method) = message.get("method").and_then(|m| m.as_str()) else {
+            // A response to something we never send, or garbage
+            return id
+                .filter(|_| message.get("result").is_none() && message.get("error").is_none())
+                .map(|id| error_response(id, &RpcError::new(INVALID_REQUEST, "Missing method")));
+        };
+
+        let id = id?;
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        Some(match self.dispatch(method, &params).await {
+            Ok(result) => response(id, result),
+            Err(error) => error_response(id, &error),
+        })
+    }
+
+    async fn dispatch(&self, method: &str, params: &Value) -> Result<Value, RpcError> {
+        match method {
+            "initialize" => Ok(self.initialize(params)),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(self.list_tools()),
+            "tools/call" => self.call_tool(params).await,
+            "resources/list" => self.list_resources(),
+            "resources/templates/list" => Ok(json!({
+                "resourceTemplates": [{
+                    "uriTemplate": format!("{}{{date}}", RESOURCE_PREFIX),
+                    "name": "day",
+                    "description": "All notes of a day (date as YYYY-MM-DD)",
+                    "mimeType": "application/json"
+                }]
+            })),
+            "resources/read" => self.read_resource(params),
+            _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+        }
+    }
+
+    fn initialize(&self, params: &Value) -> Value {
+        let requested = params["protocolVersion"].as_str().unwrap_or(LATEST_PROTOCOL_VERSION);
+        let version = if SUPPORTED_PROTOCOL_VERSIONS.contains(&requested) {
+            requested
+        } else {
+            LATEST_PROTOCOL_VERSION
+        };
+
+        json!({
+            "protocolVersion": version,
+            "capabilities": {
+                "tools": { "listChanged": false },
+                "resources": { "subscribe": false, "listChanged": false }
+            },
+            "serverInfo": {
+                "name": "aigenda",
+                "version": env!("CARGO_PKG_VERSION")
+            },
+            "instructions": "Daily notes journal. Each day is a resource; use the notes_* tools to add, read, update or delete notes."
+        })
+    }
+
+    fn list_tools(&self) -> Value {
+        let tools: Vec<Value> = self
+            .tools
+            .iter()
+            .map(|t| json!({
+                "name": t.name,
+                "description": t.description,
+                "inputSchema": t.input_schema,
+            }))
+            .collect();
+
+        json!({ "tools": tools })
+    }
+
+    async fn call_tool(&self, params: &Value) -> Result<Value, RpcError> {
+        let name = params["name"].as_str()
+            .ok_or_else(|| RpcError::invalid_params("Missing tool name"))?;
+        let exposed = self.tools.iter().find(|t| t.name == name)
+            .ok_or_else(|| RpcError::invalid_params(format!("Unknown tool: {}", name)))?;
+        let tool = self.registry.get_tool(&exposed.tool)
+            .ok_or_else(|| RpcError::new(INTERNAL_ERROR, format!("Tool {} is gone", exposed.tool)))?;
+
+        let arguments = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+
+        // Tool failures are results the model should see, not protocol errors
+        let (text, is_error) = match tool.execute(&exposed.action, &arguments).await {
+            Ok(text) => (text, false),
+            Err(e) => (format!("Error: {}", e), true),
+        };
+
+        Ok(json!({
+            "content": [{ "type": "text", "text": text }],
+            "isError": is_error
+        }))
+    }
+
+    /// Newest first, from the stored dates alone; days are only loaded when read
+    fn list_resources(&self) -> Result<Value, RpcError> {
+        let dates = self.store.list_dates().map_err(internal)?;
+
+        let resources: Vec<Value> = dates
+            .iter()
+            .rev()
+            .map(|date| json!({
+                "uri": format!("{}{}", RESOURCE_PREFIX, date.format("%Y-%m-%d")),
+                "name": date.format("%Y-%m-%d").to_string(),
+                "description": format!("Notes for {}", date.format("%A, %B %-d, %Y")),
+                "mimeType": "application/json"
+            }))
+            .collect();
+
+        Ok(json!({ "resources": resources }))
+    }
+
+    fn read_resource(&self, params: &Value) -> Result<Value, RpcError> {
+        let uri = params["uri"].as_str()
+            .ok_or_else(|| RpcError::invalid_params("Missing uri"))?;
+        let date = uri
+            .strip_prefix(RESOURCE_PREFIX)
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+            .ok_or_else(|| RpcError::invalid_params(format!("Unknown resource: {}", uri)))?;
+
+        let day = self.store.load_day(date).map_err(internal)?;
+        let text = serde_json::to_string_pretty(&day).map_err(|e| internal(AppError::Json(e)))?;
+
+        Ok(json!({
+            "contents": [{
+                "uri": uri,
+                "mimeType": "application/json",
+                "text": text
+            }]
+        }))
+    }
+}
+
+fn internal(error: AppError) -> RpcError {
+    RpcError::new(INTERNAL_ERROR, error.to_string())
+}
@@ -0,0 +1,139 @@
+//! Copying every day and the trash from one store to another, e.g. from JSON files to SQLite.
+//! Days the destination already holds intact are skipped, so running an interrupted
+//! migration again picks up where it stopped.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    config::StorageFormat,
+    error::{AppError, AppResult},
+    storage::{self, Storage, KEY_FILE},
+};
+
+/// A store to migrate from or to: its format and where it lives
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub format: StorageFormat,
+    pub path: PathBuf,
+}
+
+impl Location {
+    /// Where the store of `format` lives in `data_dir`
+    pub fn default_for(format: StorageFormat, data_dir: &Path) -> Self {
+        Self { format, path: storage::default_location(format, data_dir) }
+    }
+
+    /// `json`, `markdown` or `sqlite` (`fs` and `md` for short), optionally followed by `:PATH`
+    pub fn parse(spec: &str, data_dir: &Path) -> AppResult<Self> {
+        let (kind, path) = match spec.split_once(':') {
+            Some((kind, path)) => (kind, Some(path)),
+            None => (spec, None),
+        };
+        let format = match kind.to_lowercase().as_str() {
+            "fs" | "json" => StorageFormat::Json,
+            "md" | "markdown" => StorageFormat::Markdown,
+            "sqlite" => StorageFormat::Sqlite,
+            _ => {
+                return Err(AppError::InvalidParameters(format!(
+                    "Unknown store '{}': use fs, markdown or sqlite, optionally with :PATH",
+                    kind
+                )))
+            }
+        };
+
+        match path.filter(|p| !p.is_empty()) {
+            Some(path) => Ok(Self { format, path: PathBuf::from(path) }),
+            None => Ok(Self::default_for(format, data_dir)),
+        }
+    }
+
+    pub fn open(&self) -> AppResult<Box<dyn Storage>> {
+        if self.format != StorageFormat::Sqlite && self.path.join(KEY_FILE).exists() {
+            return Err(AppError::Config(format!("The notes in {} are encrypted and can't be migrated", self.path.display())));
+        }
+        storage::open_format(self.format, &self.path)
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.format, self.path.display())
+    }
+}
+
+#[derive(Serialize, Debug, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// Days in the source, all verified in the destination
+    pub days: usize,
+    pub copied: usize,
+    /// Days the destination already had, from an earlier run
+    pub skipped: usize,
+    pub trashed: usize,
+}
+
+/// Copies `from` into `to` and checks the result. A destination that already has days is
+/// refused unless `resume` is set.
+pub fn migrate<F, T>(from: &F, to: &T, resume: bool) -> AppResult<MigrationReport>
+where
+    F: Storage + ?Sized,
+    T: Storage + ?Sized,
+{
+    let dates = from.list_dates()?;
+    if !resume && !to.list_dates()?.is_empty() {
+        return Err(AppError::Conflict("The destination already has notes".to_string()));
+    }
+
+    let mut report = MigrationReport { days: dates.len(), ..Default::default() };
+    for &date in &dates {
+        let day = from.load_day(date)?;
+        if to.exists(date)? && hash(&to.load_day(date)?)? == hash(&day)? {
+            report.skipped += 1;
+            continue;
+        }
+        to.save_day(&day)?;
+        report.copied += 1;
+    }
+
+    let trash = from.load_trash()?;
+    if !trash.is_empty() {
+        to.save_trash(&trash)?;
+    }
+    report.trashed = trash.len();
+
+    verify(from, to)?;
+    Ok(report)
+}
+
+/// Checks that every day and the trash of `from` read back the same from `to`
+pub fn verify<F, T>(from: &F, to: &T) -> AppResult<()>
+where
+    F: Storage + ?Sized,
+    T: Storage + ?Sized,
+{
+    let dates = from.list_dates()?;
+    let copied = to.list_dates()?;
+    let missing = dates.iter().filter(|d| copied.binary_search(d).is_err()).count();
+    if missing > 0 {
+        return Err(AppError::Corrupt(format!("{} of {} days are missing from the destination", missing, dates.len())));
+    }
+
+    for date in dates {
+        if hash(&from.load_day(date)?)? != hash(&to.load_day(date)?)? {
+            return Err(AppError::Corrupt(format!("{} differs in the destination", date.format("%Y-%m-%d"))));
+        }
+    }
+    if hash(&from.load_trash()?)? != hash(&to.load_trash()?)? {
+        return Err(AppError::Corrupt("The trash differs in the destination".to_string()));
+    }
+    Ok(())
+}
+
+/// SHA-256 of the value's JSON, in hex
+pub fn hash<T: Serialize + ?Sized>(value: &T) -> AppResult<String> {
+    let json = serde_json::to_vec(value).map_err(|e| AppError::Storage(format!("Could not serialize: {}", e)))?;
+    Ok(Sha256::digest(json).iter().map(|b| format!("{:02x}", b)).collect())
+}
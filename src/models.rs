@@ -1,31 +1,91 @@
 use serde::{Deserialize, Serialize};
-use chrono::NaiveDate;
+use chrono::{DateTime, FixedOffset, NaiveDate};
 
-#[cfg(feature = "ai")]
-use chrono::{DateTime, Utc};
+use std::{fmt, str::FromStr};
+
+use crate::error::AppError;
+use crate::clock::clock;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Note {
-    pub when: String, // RFC3339
+    /// When the note was taken, with the UTC offset it was taken at (RFC 3339 on disk)
+    pub when: DateTime<FixedOffset>,
     pub text: String,
     pub tags: Vec<String>, // keep; we'll use later
+    /// Set when the note is a to-do
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task: Option<Task>,
+    /// When the text was last changed; `when` keeps the original time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edited_at: Option<DateTime<FixedOffset>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edited_by: Option<Editor>,
+    /// Earlier versions of the text, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub revisions: Vec<Revision>,
 }
 
 impl Note {
-    #[cfg(feature = "ai")]
+    /// A note stamped with the current time on the user's clock
     pub fn new(text: String) -> Self {
         Self {
-            when: Utc::now().to_rfc3339(),
+            when: clock().now(),
             text,
             tags: Vec::new(),
+            task: None,
+            edited_at: None,
+            edited_by: None,
+            revisions: Vec::new(),
+        }
+    }
+
+    /// An open to-do stamped with the current time
+    pub fn task(text: String, due: Option<NaiveDate>, priority: Option<Priority>) -> Self {
+        Self {
+            task: Some(Task {
+                status: TaskStatus::Open,
+                due,
+                priority,
+                closed_at: None,
+            }),
+            ..Self::new(text)
+        }
+    }
+
+    pub fn is_open_task(&self) -> bool {
+        self.task.as_ref().is_some_and(|t| t.status == TaskStatus::Open)
+    }
+
+    /// Replaces the text, keeping the current one as a revision
+    pub fn revise(&mut self, text: String, editor: Editor) {
+        if text == self.text {
+            return;
         }
+        let previous = std::mem::replace(&mut self.text, text);
+        self.revisions.push(Revision {
+            text: previous,
+            edited_at: self.edited_at.unwrap_or(self.when),
+            editor: self.edited_by,
+        });
+        self.edited_at = Some(clock().now());
+        self.edited_by = Some(editor);
     }
 
+    /// Every version of the text, oldest first, ending with the current one
+    pub fn history(&self) -> Vec<Revision> {
+        let mut history = self.revisions.clone();
+        history.push(Revision {
+            text: self.text.clone(),
+            edited_at: self.edited_at.unwrap_or(self.when),
+            editor: self.edited_by,
+        });
+        history
+    }
+
+    /// The timestamp converted to the user's timezone, for display
     #[cfg(feature = "ai")]
-    pub fn when(&self) -> DateTime<Utc> {
-        DateTime::parse_from_rfc3339(&self.when)
-            .unwrap_or_else(|_| Utc::now().into())
-            .with_timezone(&Utc)
+    pub fn when(&self) -> DateTime<FixedOffset> {
+        clock().local(self.when)
     }
 
     #[cfg(feature = "ai")]
@@ -35,11 +95,103 @@ impl Note {
 
 }
 
+/// One version of a note's text. Stored with short keys, since every edit keeps one.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Revision {
+    pub text: String,
+    /// When this version was written (the note's time for the first one)
+    #[serde(rename = "at")]
+    pub edited_at: DateTime<FixedOffset>,
+    #[serde(rename = "by", default, skip_serializing_if = "Option::is_none")]
+    pub editor: Option<Editor>,
+}
+
+/// Where an edit came from
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Editor {
+    Cli,
+    Agent,
+    Import,
+}
+
+impl fmt::Display for Editor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Editor::Cli => "cli",
+            Editor::Agent => "agent",
+            Editor::Import => "import",
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Task {
+    pub status: TaskStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+    /// When the task was marked done or cancelled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub closed_at: Option<DateTime<FixedOffset>>,
+}
+
+impl Task {
+    /// `[ ]`, `[x]` or `[-]`, plus due date and priority when set
+    pub fn label(&self) -> String {
+        let mut label = match self.status {
+            TaskStatus::Open => "[ ]",
+            TaskStatus::Done => "[x]",
+            TaskStatus::Cancelled => "[-]",
+        }
+        .to_string();
+
+        if let Some(priority) = self.priority {
+            label.push_str(&format!(" !{}", priority));
+        }
+        if let Some(due) = self.due {
+            label.push_str(&format!(" (due {})", due.format("%Y-%m-%d")));
+        }
+        label
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskStatus {
+    Open,
+    Done,
+    Cancelled,
+}
+
+/// Ordered so that `High` sorts last; sort descending for most urgent first
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    Medium,
+    High,
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Priority::Low => "low",
+            Priority::Medium => "medium",
+            Priority::High => "high",
+        })
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct DayLog {
     #[serde(with = "date_format")]
     pub date: NaiveDate,
     pub notes: Vec<Note>,
+    /// Recurring rules already applied to this day, so deleting their notes sticks
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recurring: Vec<String>,
 }
 
 impl DayLog {
@@ -47,6 +199,7 @@ impl DayLog {
         Self {
             date,
             notes: Vec::new(),
+            recurring: Vec::new(),
         }
     }
 
@@ -60,11 +213,55 @@ impl DayLog {
         &self.notes
     }
 
-    #[cfg(feature = "ai")]
-    pub fn notes_mut(&mut self) -> &mut Vec<Note> {
-        &mut self.notes
+
+}
+
+/// A deleted note, kept in the trash until it's restored or purged
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TrashedNote {
+    /// The day it was deleted from
+    #[serde(with = "date_format")]
+    pub date: NaiveDate,
+    /// Its 1-based position on that day, to put it back in place
+    pub position: usize,
+    pub deleted_at: DateTime<FixedOffset>,
+    pub note: Note,
+}
+
+/// Points at a single note: its day plus 1-based position, written `YYYY-MM-DD#N`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NoteRef {
+    pub date: NaiveDate,
+    pub index: usize,
+}
+
+impl NoteRef {
+    pub fn new(date: NaiveDate, index: usize) -> Self {
+        Self { date, index }
     }
+}
 
+impl fmt::Display for NoteRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}#{}", self.date.format("%Y-%m-%d"), self.index)
+    }
+}
+
+impl FromStr for NoteRef {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (date, index) = s
+            .split_once('#')
+            .ok_or_else(|| AppError::InvalidParameters(format!("Invalid note id '{}', expected YYYY-MM-DD#N", s)))?;
+        let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")?;
+        let index = index
+            .parse::<usize>()
+            .ok()
+            .filter(|i| *i > 0)
+            .ok_or_else(|| AppError::InvalidParameters(format!("Invalid note index in '{}'", s)))?;
+        Ok(Self { date, index })
+    }
 }
 
 mod date_format {
@@ -89,3 +286,4 @@ mod date_format {
         NaiveDate::parse_from_str(&s, FORMAT).map_err(serde::de::Error::custom)
     }
 }
+
@@ -0,0 +1,215 @@
+//! Notebooks: separate journals, each with its own notes, agent memory and settings, so
+//! personal and work notes don't mix. The main notebook is the platform data dir (with its
+//! settings in the platform config dir); named ones live in `notebooks/NAME` under both.
+//! With `--data-dir` (or `AIGENDA_HOME`) everything lives in that folder instead, and each
+//! notebook keeps its settings next to its notes.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+
+/// The notebook in the data dir itself
+pub const MAIN: &str = "main";
+
+pub const HOME_ENV: &str = "AIGENDA_HOME";
+pub const NOTEBOOK_ENV: &str = "AIGENDA_NOTEBOOK";
+
+/// Folder of named notebooks, in the data dir and the config dir
+pub const NOTEBOOKS_DIR: &str = "notebooks";
+
+/// Which notebook is used without `--notebook`, in the main config dir
+const DEFAULT_FILE: &str = "notebooks.json";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notebook {
+    pub name: String,
+    /// Notes, the trash, agent memory and the embeddings index
+    pub data_dir: PathBuf,
+    /// `config.json`, templates and tool plugins
+    pub config_dir: PathBuf,
+    /// Where `aigenda backup` puts dated backups, outside the data dir
+    pub backups_dir: PathBuf,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct Defaults {
+    default: Option<String>,
+}
+
+/// Where notebooks live
+#[derive(Debug, Clone)]
+pub struct Home {
+    data_dir: PathBuf,
+    config_dir: PathBuf,
+}
+
+impl Home {
+    /// `dir`, or `AIGENDA_HOME`, for everything; otherwise the platform data and config dirs
+    pub fn new(dir: Option<PathBuf>) -> AppResult<Self> {
+        let dir = dir.or_else(|| std::env::var_os(HOME_ENV).filter(|d| !d.is_empty()).map(PathBuf::from));
+        if let Some(dir) = dir {
+            return Ok(Self { data_dir: dir.clone(), config_dir: dir });
+        }
+
+        let dirs = ProjectDirs::from("com", "example", "aigenda")
+            .ok_or_else(|| AppError::Config("Could not determine data directory".to_string()))?;
+        Ok(Self { data_dir: dirs.data_dir().to_path_buf(), config_dir: dirs.config_dir().to_path_buf() })
+    }
+
+    /// The notebook named `name`, `AIGENDA_NOTEBOOK`, or the default one; it must exist
+    pub fn open(&self, name: Option<&str>) -> AppResult<Notebook> {
+        let name = match name.map(str::to_string).or_else(|| std::env::var(NOTEBOOK_ENV).ok().filter(|n| !n.is_empty())) {
+            Some(name) => name,
+            None => self.default_name()?,
+        };
+        let notebook = self.locate(&name)?;
+        if name != MAIN && !notebook.data_dir.is_dir() {
+            return Err(AppError::NotFound(format!(
+                "No notebook '{}'; create it with `aigenda notebooks create {}`",
+                name, name
+            )));
+        }
+        Ok(notebook)
+    }
+
+    /// Every notebook, main first
+    pub fn list(&self) -> AppResult<Vec<String>> {
+        let mut names = vec![MAIN.to_string()];
+        let dir = self.data_dir.join(NOTEBOOKS_DIR);
+        if dir.is_dir() {
+            let mut named = Vec::new();
+            for entry in fs::read_dir(&dir)
+                .map_err(|e| AppError::Storage(format!("Could not read directory {}: {}", dir.display(), e)))?
+            {
+                let entry = entry?;
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if entry.file_type()?.is_dir() && validate(&name).is_ok() {
+                    named.push(name);
+                }
+            }
+            named.sort();
+            names.extend(named);
+        }
+        Ok(names)
+    }
+
+    pub fn create(&self, name: &str) -> AppResult<Notebook> {
+        validate(name)?;
+        let notebook = self.locate(name)?;
+        if notebook.data_dir.exists() {
+            return Err(AppError::Conflict(format!("Notebook '{}' already exists", name)));
+        }
+        for dir in [&notebook.data_dir, &notebook.config_dir] {
+            fs::create_dir_all(dir)
+                .map_err(|e| AppError::Storage(format!("Could not create {}: {}", dir.display(), e)))?;
+        }
+        self.ignore_in_git()?;
+        Ok(notebook)
+    }
+
+    /// The notebook used without `--notebook`: main unless another was picked
+    pub fn default_name(&self) -> AppResult<String> {
+        let path = self.config_dir.join(DEFAULT_FILE);
+        if !path.exists() {
+            return Ok(MAIN.to_string());
+        }
+        let content = fs::read_to_string(&path)
+            .map_err(|e| AppError::Storage(format!("Could not read file {}: {}", path.display(), e)))?;
+        let defaults: Defaults = serde_json::from_str(&content)
+            .map_err(|e| AppError::Config(format!("Could not parse {}: {}", path.display(), e)))?;
+        Ok(defaults.default.unwrap_or_else(|| MAIN.to_string()))
+    }
+
+    pub fn set_default(&self, name: &str) -> AppResult<Notebook> {
+        let notebook = self.open(Some(name))?;
+        let content = serde_json::to_string_pretty(&Defaults { default: Some(notebook.name.clone()) })?;
+        fs::create_dir_all(&self.config_dir)?;
+        let path = self.config_dir.join(DEFAULT_FILE);
+        fs::write(&path, content + "\n")
+            .map_err(|e| AppError::Storage(format!("Could not write to {}: {}", path.display(), e)))?;
+        Ok(notebook)
+    }
+
+    fn locate(&self, name: &str) -> AppResult<Notebook> {
+        let backups = append(&self.data_dir, "-backups");
+        if name == MAIN {
+            return Ok(Notebook {
+                name: name.to_string(),
+                data_dir: self.data_dir.clone(),
+                config_dir: self.config_dir.clone(),
+                backups_dir: backups,
+            });
+        }
+
+        validate(name)?;
+        Ok(Notebook {
+            name: name.to_string(),
+            data_dir: self.data_dir.join(NOTEBOOKS_DIR).join(name),
+            config_dir: self.config_dir.join(NOTEBOOKS_DIR).join(name),
+            backups_dir: backups.join(name),
+        })
+    }
+
+    /// Keeps named notebooks out of the main notebook's git history; repositories created
+    /// since already ignore them
+    fn ignore_in_git(&self) -> AppResult<()> {
+        if !self.data_dir.join(".git").exists() {
+            return Ok(());
+        }
+        let path = self.data_dir.join(".gitignore");
+        let mut ignore = fs::read_to_string(&path).unwrap_or_default();
+        let entry = format!("{}/", NOTEBOOKS_DIR);
+        if ignore.lines().any(|line| line == entry) {
+            return Ok(());
+        }
+        if !ignore.is_empty() && !ignore.ends_with('\n') {
+            ignore.push('\n');
+        }
+        ignore.push_str(&entry);
+        ignore.push('\n');
+        fs::write(&path, ignore).map_err(|e| AppError::Storage(format!("Could not write .gitignore: {}", e)))
+    }
+}
+
+/// Notebook names are folder names: letters, digits, `-` and `_`
+pub fn validate(name: &str) -> AppResult<()> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(AppError::InvalidParameters(format!(
+            "Invalid notebook name '{}': use letters, digits, - and _",
+            name
+        )));
+    }
+    Ok(())
+}
+
+fn append(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+static CURRENT: OnceLock<Notebook> = OnceLock::new();
+
+/// Picks the notebook this run works in, from `--data-dir` and `--notebook`. Only the
+/// first choice counts: everything after reads the same notebook.
+pub fn select(data_dir: Option<PathBuf>, name: Option<&str>) -> AppResult<&'static Notebook> {
+    if let Some(notebook) = CURRENT.get() {
+        return Ok(notebook);
+    }
+    let notebook = Home::new(data_dir)?.open(name)?;
+    Ok(CURRENT.get_or_init(|| notebook))
+}
+
+/// The selected notebook, or the default one when none was
+pub fn current() -> AppResult<&'static Notebook> {
+    select(None, None)
+}
@@ -0,0 +1,114 @@
+//! Changes to existing notes, shared by `aigenda rm/amend/mv` and the notes tool.
+
+use chrono::NaiveDate;
+
+use crate::{
+    error::{AppError, AppResult},
+    models::{DayLog, Editor, Note, NoteRef},
+    storage::Storage,
+    trash,
+};
+
+/// Moves a note to the trash, returning it; a day left empty is deleted
+pub fn remove_note<S: Storage + ?Sized>(store: &S, note_ref: NoteRef) -> AppResult<Note> {
+    let mut day = store.load_day(note_ref.date)?;
+    let position = position(&day, note_ref)?;
+    let note = day.notes.remove(position);
+    save_or_delete(store, &day)?;
+    trash::discard(store, note_ref.date, note_ref.index, note.clone())?;
+    Ok(note)
+}
+
+/// Replaces a note's text, keeping its timestamp and the previous text as a revision
+pub fn amend_note<S: Storage + ?Sized>(store: &S, note_ref: NoteRef, text: &str, editor: Editor) -> AppResult<Note> {
+    if text.trim().is_empty() {
+        return Err(AppError::InvalidParameters("A note needs some text".to_string()));
+    }
+    revise(store, note_ref, |note| {
+        note.revise(text.trim().to_string(), editor);
+        Ok(())
+    })
+}
+
+/// Brings back the text of revision `rev` (1-based, as shown by `aigenda log`) as a new revision
+pub fn revert_note<S: Storage + ?Sized>(store: &S, note_ref: NoteRef, rev: usize, editor: Editor) -> AppResult<Note> {
+    revise(store, note_ref, |note| {
+        let history = note.history();
+        if rev == history.len() {
+            return Err(AppError::InvalidParameters(format!("Revision {} is already the current text of {}", rev, note_ref)));
+        }
+        let revision = rev
+            .checked_sub(1)
+            .and_then(|i| history.get(i))
+            .ok_or_else(|| AppError::NotFound(format!("Revision {} of {} (it has {})", rev, note_ref, history.len())))?;
+        note.revise(revision.text.clone(), editor);
+        Ok(())
+    })
+}
+
+fn revise<S, F>(store: &S, note_ref: NoteRef, change: F) -> AppResult<Note>
+where
+    S: Storage + ?Sized,
+    F: FnOnce(&mut Note) -> AppResult<()>,
+{
+    let mut day = store.load_day(note_ref.date)?;
+    let position = position(&day, note_ref)?;
+    change(&mut day.notes[position])?;
+
+    let note = day.notes[position].clone();
+    store.save_day(&day)?;
+    Ok(note)
+}
+
+/// A note by id
+pub fn get_note<S: Storage + ?Sized>(store: &S, note_ref: NoteRef) -> AppResult<Note> {
+    let day = store.load_day(note_ref.date)?;
+    let position = position(&day, note_ref)?;
+    Ok(day.notes[position].clone())
+}
+
+/// Moves a note to another day and/or to a 1-based position there (the end by default),
+/// returning where it ended up. The note keeps its timestamp.
+pub fn move_note<S: Storage + ?Sized>(
+    store: &S,
+    from: NoteRef,
+    to_date: NaiveDate,
+    to_position: Option<usize>,
+) -> AppResult<NoteRef> {
+    let mut source = store.load_day(from.date)?;
+    let note = source.notes.remove(position(&source, from)?);
+
+    let mut target = if to_date == from.date { source.clone() } else { store.load_day(to_date)? };
+    let index = to_position.unwrap_or(target.notes.len() + 1);
+    if index == 0 || index > target.notes.len() + 1 {
+        return Err(AppError::InvalidParameters(format!(
+            "Position {} is out of range: {} has {} notes",
+            index,
+            to_date.format("%Y-%m-%d"),
+            target.notes.len() + usize::from(to_date == from.date)
+        )));
+    }
+    target.notes.insert(index - 1, note);
+
+    store.save_day(&target)?;
+    if to_date != from.date {
+        save_or_delete(store, &source)?;
+    }
+    Ok(NoteRef::new(to_date, index))
+}
+
+fn position(day: &DayLog, note_ref: NoteRef) -> AppResult<usize> {
+    note_ref
+        .index
+        .checked_sub(1)
+        .filter(|i| *i < day.notes.len())
+        .ok_or_else(|| AppError::NotFound(format!("Note {}", note_ref)))
+}
+
+fn save_or_delete<S: Storage + ?Sized>(store: &S, day: &DayLog) -> AppResult<()> {
+    if day.notes.is_empty() {
+        store.delete_day(day.date)
+    } else {
+        store.save_day(day)
+    }
+}
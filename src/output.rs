@@ -0,0 +1,21 @@
+use serde::Serialize;
+
+use crate::{cli::OutputFormat, error::AppResult};
+
+/// Prints `value` as a pretty JSON document (`json`) or a single compact line (`ndjson`)
+pub fn print_json<T: Serialize + ?Sized>(format: OutputFormat, value: &T) -> AppResult<()> {
+    let text = match format {
+        OutputFormat::Ndjson => serde_json::to_string(value)?,
+        OutputFormat::Json | OutputFormat::Text => serde_json::to_string_pretty(value)?,
+    };
+    println!("{}", text);
+    Ok(())
+}
+
+/// Prints a collection: one JSON array (`json`) or one compact line per item (`ndjson`)
+pub fn print_json_list<T: Serialize>(format: OutputFormat, items: &[T]) -> AppResult<()> {
+    match format {
+        OutputFormat::Ndjson => items.iter().try_for_each(|item| print_json(format, item)),
+        OutputFormat::Json | OutputFormat::Text => print_json(format, items),
+    }
+}
@@ -0,0 +1,169 @@
+//! Recurring notes and tasks, configured under `recurring` in `config.json`:
+//!
+//! ```json
+//! "recurring": [
+//!   { "every": "weekdays", "template": "standup" },
+//!   { "every": "weekly on fri", "text": "Submit timesheet", "task": true },
+//!   { "every": "monthly on 1", "text": "Pay rent", "task": true, "priority": "high" }
+//! ]
+//! ```
+//!
+//! A rule is applied to a day once; the day remembers it, so deleting its notes sticks.
+
+use std::str::FromStr;
+
+use chrono::{Datelike, Months, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{AppError, AppResult},
+    models::{DayLog, Note, Priority},
+    templates::{fill, Templates},
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Recurrence {
+    /// Identifies the rule in the days it was applied to; defaults to the template or text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// `daily`, `weekdays`, `weekly on mon[,thu...]`, `monthly` or `monthly on 15`
+    pub every: Schedule,
+    /// A note to add; may use `{{date}}` and `{{weekday}}`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// A template to apply instead of `text`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// Add `text` as an open task rather than a plain note
+    #[serde(default)]
+    pub task: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+}
+
+impl Recurrence {
+    pub fn key(&self) -> &str {
+        self.name
+            .as_deref()
+            .or(self.template.as_deref())
+            .or(self.text.as_deref())
+            .unwrap_or_default()
+    }
+
+    /// The notes this rule adds to `date`
+    fn notes(&self, date: NaiveDate, templates: &Templates) -> AppResult<Vec<Note>> {
+        match (&self.template, &self.text) {
+            (Some(name), _) => templates.load(name)?.render(date),
+            (None, Some(text)) if self.task => Ok(vec![Note::task(fill(text, date)?, Some(date), self.priority)]),
+            (None, Some(text)) => Ok(vec![Note::new(fill(text, date)?)]),
+            (None, None) => Err(AppError::Config(format!(
+                "Recurring rule '{}' needs a text or a template", self.every
+            ))),
+        }
+    }
+}
+
+/// Applies every rule due on the day and not yet applied to it; returns whether anything changed.
+/// A rule that can't be applied (a missing template, say) is reported and skipped, so it
+/// doesn't stop the day from loading, and is tried again once it's fixed.
+pub fn materialize(day: &mut DayLog, rules: &[Recurrence], templates: &Templates) -> bool {
+    let mut changed = false;
+    for rule in rules {
+        if !rule.every.matches(day.date) || day.recurring.iter().any(|k| k == rule.key()) {
+            continue;
+        }
+        match rule.notes(day.date, templates) {
+            Ok(notes) => day.notes.extend(notes),
+            Err(e) => {
+                eprintln!("⚠️  Skipping recurring rule '{}': {}", rule.key(), e);
+                continue;
+            }
+        }
+        day.recurring.push(rule.key().to_string());
+        changed = true;
+    }
+    changed
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Schedule {
+    Daily,
+    Weekdays,
+    Weekly(Vec<Weekday>),
+    /// Day of the month; days past the month's end fall on its last day
+    Monthly(u32),
+}
+
+impl Schedule {
+    pub fn matches(&self, date: NaiveDate) -> bool {
+        match self {
+            Schedule::Daily => true,
+            Schedule::Weekdays => !matches!(date.weekday(), Weekday::Sat | Weekday::Sun),
+            Schedule::Weekly(days) => days.contains(&date.weekday()),
+            Schedule::Monthly(day) => date.day() == (*day).min(last_day_of_month(date)),
+        }
+    }
+}
+
+fn last_day_of_month(date: NaiveDate) -> u32 {
+    date.with_day(1)
+        .and_then(|first| first.checked_add_months(Months::new(1)))
+        .and_then(|next| next.pred_opt())
+        .map_or(31, |last| last.day())
+}
+
+impl FromStr for Schedule {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_lowercase();
+        let words: Vec<&str> = lower.split_whitespace().collect();
+
+        let schedule = match words.as_slice() {
+            ["daily"] => Some(Schedule::Daily),
+            ["weekdays"] => Some(Schedule::Weekdays),
+            ["weekly", "on", days @ ..] if !days.is_empty() => days
+                .join(" ")
+                .split(',')
+                .map(|d| d.trim().parse::<Weekday>().ok())
+                .collect::<Option<Vec<_>>>()
+                .map(Schedule::Weekly),
+            ["monthly"] => Some(Schedule::Monthly(1)),
+            ["monthly", "on", day] => day.parse().ok().filter(|d| (1..=31).contains(d)).map(Schedule::Monthly),
+            _ => None,
+        };
+
+        schedule.ok_or_else(|| AppError::Config(format!(
+            "Unrecognized schedule '{}': use daily, weekdays, weekly on mon, monthly or monthly on 15", s.trim()
+        )))
+    }
+}
+
+impl TryFrom<String> for Schedule {
+    type Error = AppError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Schedule> for String {
+    fn from(schedule: Schedule) -> Self {
+        schedule.to_string()
+    }
+}
+
+impl std::fmt::Display for Schedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Schedule::Daily => f.write_str("daily"),
+            Schedule::Weekdays => f.write_str("weekdays"),
+            Schedule::Weekly(days) => {
+                let days: Vec<String> = days.iter().map(|d| d.to_string().to_lowercase()).collect();
+                write!(f, "weekly on {}", days.join(","))
+            }
+            Schedule::Monthly(day) => write!(f, "monthly on {}", day),
+        }
+    }
+}
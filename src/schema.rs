@@ -0,0 +1,173 @@
+//! Versions of the JSON documents aigenda stores: days and the trash. Documents are written
+//! with the current `schema_version`; older ones are upgraded step by step through
+//! [`MIGRATIONS`] when read (and rewritten when next saved, or by `aigenda doctor --migrate`).
+//! Documents from a newer aigenda are refused, so an old build never reads them half-way or
+//! saves them back downgraded.
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    error::{AppError, AppResult},
+    models::{DayLog, TrashedNote},
+};
+
+/// The schema this build reads and writes
+pub const SCHEMA_VERSION: u32 = 2;
+
+const VERSION_KEY: &str = "schema_version";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Document {
+    Day,
+    Trash,
+}
+
+/// One upgrade step, from version `from` to the next
+pub struct Migration {
+    pub document: Document,
+    pub from: u32,
+    pub description: &'static str,
+    apply: fn(&mut Value) -> Result<(), String>,
+}
+
+/// Every upgrade step, oldest first. A document without a step for some version had nothing
+/// to change at that version.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        document: Document::Day,
+        from: 1,
+        description: "notes without tags get an empty list",
+        apply: day_v1_tags,
+    },
+    Migration {
+        document: Document::Trash,
+        from: 1,
+        description: "the list of trashed notes moves into a versioned object",
+        apply: trash_v1_object,
+    },
+];
+
+#[derive(Serialize)]
+struct Versioned<'a, T: ?Sized> {
+    schema_version: u32,
+    #[serde(flatten)]
+    value: &'a T,
+}
+
+#[derive(Serialize)]
+struct TrashDocument<'a> {
+    schema_version: u32,
+    notes: &'a [TrashedNote],
+}
+
+pub fn day_to_json(day: &DayLog, pretty: bool) -> AppResult<String> {
+    to_json(&Versioned { schema_version: SCHEMA_VERSION, value: day }, pretty)
+}
+
+pub fn trash_to_json(trash: &[TrashedNote], pretty: bool) -> AppResult<String> {
+    to_json(&TrashDocument { schema_version: SCHEMA_VERSION, notes: trash }, pretty)
+}
+
+/// Reads a day written with this schema or an older one
+pub fn parse_day(json: &str) -> AppResult<DayLog> {
+    parse(Document::Day, json)
+}
+
+pub fn parse_trash(json: &str) -> AppResult<Vec<TrashedNote>> {
+    parse(Document::Trash, json)
+}
+
+/// The schema a document was written with; documents from before versioning are version 1
+pub fn version_of(document: &Value) -> AppResult<u32> {
+    match document.get(VERSION_KEY) {
+        None => Ok(1),
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| AppError::Corrupt(format!("Invalid {}: {}", VERSION_KEY, version))),
+    }
+}
+
+/// The version of a JSON document, without decoding the rest of it
+pub fn version_of_json(json: &str) -> AppResult<u32> {
+    let document: Value = serde_json::from_str(json).map_err(|e| AppError::Corrupt(format!("Invalid JSON: {}", e)))?;
+    version_of(&document)
+}
+
+/// Adds where a document came from to an error reading it
+pub fn located(error: AppError, source: impl std::fmt::Display) -> AppError {
+    match error {
+        AppError::Corrupt(message) => AppError::Corrupt(format!("{}: {}", source, message)),
+        AppError::Conflict(message) => AppError::Conflict(format!("{}: {}", source, message)),
+        error => error,
+    }
+}
+
+/// Refuses documents newer than this build understands
+pub fn check_version(version: u32) -> AppResult<()> {
+    if version > SCHEMA_VERSION {
+        return Err(AppError::Conflict(format!(
+            "Written with schema version {}, newer than this aigenda reads ({}); upgrade aigenda",
+            version, SCHEMA_VERSION
+        )));
+    }
+    Ok(())
+}
+
+/// Brings a document up to the current schema; returns the version it had when that was older
+pub fn upgrade(document: Document, value: &mut Value) -> AppResult<Option<u32>> {
+    let version = version_of(value)?;
+    check_version(version)?;
+    if version == SCHEMA_VERSION {
+        return Ok(None);
+    }
+
+    for step in version..SCHEMA_VERSION {
+        if let Some(migration) = MIGRATIONS.iter().find(|m| m.document == document && m.from == step) {
+            (migration.apply)(value).map_err(|e| {
+                AppError::Corrupt(format!("Could not upgrade from schema version {}: {}", step, e))
+            })?;
+        }
+    }
+    value
+        .as_object_mut()
+        .ok_or_else(|| AppError::Corrupt("Expected a JSON object".to_string()))?
+        .insert(VERSION_KEY.to_string(), json!(SCHEMA_VERSION));
+    Ok(Some(version))
+}
+
+fn parse<T: DeserializeOwned>(document: Document, json: &str) -> AppResult<T> {
+    let mut value: Value = serde_json::from_str(json).map_err(|e| AppError::Corrupt(format!("Invalid JSON: {}", e)))?;
+    upgrade(document, &mut value)?;
+    if document == Document::Trash {
+        value = value.get_mut("notes").map(Value::take).unwrap_or_else(|| json!([]));
+    }
+
+    serde_json::from_value(value).map_err(|e| AppError::Corrupt(e.to_string()))
+}
+
+fn to_json<T: Serialize>(document: &T, pretty: bool) -> AppResult<String> {
+    let json = if pretty {
+        serde_json::to_string_pretty(document)
+    } else {
+        serde_json::to_string(document)
+    };
+    json.map_err(|e| AppError::Storage(format!("Could not serialize: {}", e)))
+}
+
+fn day_v1_tags(day: &mut Value) -> Result<(), String> {
+    let notes = day.get_mut("notes").and_then(Value::as_array_mut).ok_or("no notes list")?;
+    for note in notes.iter_mut().filter_map(Value::as_object_mut) {
+        note.entry("tags").or_insert_with(|| json!([]));
+    }
+    Ok(())
+}
+
+fn trash_v1_object(trash: &mut Value) -> Result<(), String> {
+    if !trash.is_array() {
+        return Err("expected a list of notes".to_string());
+    }
+    *trash = json!({ "notes": trash.take() });
+    Ok(())
+}
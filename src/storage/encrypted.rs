@@ -0,0 +1,232 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::NaiveDate;
+
+use crate::{
+    error::{AppError, AppResult},
+    keys::{to_hex, DataKey},
+    models::{DayLog, TrashedNote},
+    schema::{self, SCHEMA_VERSION},
+};
+use super::{write_file, Storage};
+
+const EXTENSION: &str = "enc";
+const TRASH_FILE: &str = "trash.enc";
+/// Date to file name, when file names are encrypted
+const NAMES_FILE: &str = "names.enc";
+
+/// Day files sealed with AES-256-GCM, each bound to its date so files can't be swapped.
+/// Files are `YYYY-MM-DD.enc`, or random names listed in an encrypted mapping.
+pub struct EncryptedStorage {
+    data_dir: PathBuf,
+    key: DataKey,
+    /// Also tried when opening files, while a rotation away from it is unfinished
+    previous: Option<DataKey>,
+    encrypt_filenames: bool,
+    /// Serializes mapping updates
+    names: Mutex<()>,
+}
+
+impl EncryptedStorage {
+    pub fn new(data_dir: &Path, key: DataKey, encrypt_filenames: bool) -> AppResult<Self> {
+        fs::create_dir_all(data_dir)
+            .map_err(|e| AppError::Config(format!("Could not create data directory {}: {}", data_dir.display(), e)))?;
+        Ok(Self {
+            data_dir: data_dir.to_path_buf(),
+            key,
+            previous: None,
+            encrypt_filenames,
+            names: Mutex::new(()),
+        })
+    }
+
+    pub fn with_previous(self, previous: Option<DataKey>) -> Self {
+        Self { previous, ..self }
+    }
+
+    /// Re-encrypts everything under `key`, one file at a time. Save `key` first, keeping the
+    /// current one as its previous key: a failure partway then leaves each file readable with
+    /// one or the other, and running it again finishes the job.
+    pub fn rekey(self, key: DataKey) -> AppResult<Self> {
+        if self.previous.is_some() {
+            return Err(AppError::Conflict("An earlier key rotation is unfinished; reseal first".to_string()));
+        }
+        Self { previous: Some(self.key), key, ..self }.reseal()
+    }
+
+    /// Rewrites every file with the current key, so the previous one is no longer needed
+    pub fn reseal(self) -> AppResult<Self> {
+        if self.encrypt_filenames {
+            let _guard = self.names.lock().map_err(|_| AppError::Storage("Name mapping lock poisoned".to_string()))?;
+            self.save_names(&self.load_names()?)?;
+        }
+        for date in self.list_dates()? {
+            self.save_day(&self.load_day(date)?)?;
+        }
+        self.save_trash(&self.load_trash()?)?;
+        Ok(Self { previous: None, ..self })
+    }
+
+    fn seal(&self, aad: &[u8], json: &str) -> AppResult<Vec<u8>> {
+        self.key.seal(aad, json.as_bytes())
+    }
+
+    /// The decrypted contents of `path`, if it exists
+    fn decrypt(&self, path: &Path, aad: &[u8]) -> AppResult<Option<String>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let sealed = fs::read(path).map_err(|e| AppError::Storage(format!("Could not read file {}: {}", path.display(), e)))?;
+        let json = self
+            .key
+            .open(aad, &sealed)
+            .or_else(|e| match &self.previous {
+                Some(previous) => previous.open(aad, &sealed).map_err(|_| e),
+                None => Err(e),
+            })
+            .map_err(|e| AppError::Corrupt(format!("{}: {}", path.display(), e)))?;
+        String::from_utf8(json)
+            .map(Some)
+            .map_err(|_| AppError::Corrupt(format!("{}: not UTF-8", path.display())))
+    }
+
+    fn load_names(&self) -> AppResult<BTreeMap<NaiveDate, String>> {
+        let path = self.data_dir.join(NAMES_FILE);
+        match self.decrypt(&path, b"names")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Corrupt(format!("Could not parse JSON from {}: {}", path.display(), e))),
+            None => Ok(BTreeMap::new()),
+        }
+    }
+
+    fn save_names(&self, names: &BTreeMap<NaiveDate, String>) -> AppResult<()> {
+        write_file(&self.data_dir.join(NAMES_FILE), &self.seal_names(names)?)
+    }
+
+    fn seal_names(&self, names: &BTreeMap<NaiveDate, String>) -> AppResult<Vec<u8>> {
+        let json = serde_json::to_string(names).map_err(|e| AppError::Storage(format!("Could not serialize: {}", e)))?;
+        self.seal(b"names", &json)
+    }
+
+    /// The file for a day; with encrypted names, `None` when it has none yet and `create` is off
+    fn day_path(&self, date: NaiveDate, create: bool) -> AppResult<Option<PathBuf>> {
+        if !self.encrypt_filenames {
+            return Ok(Some(self.data_dir.join(format!("{}.{}", date.format("%Y-%m-%d"), EXTENSION))));
+        }
+
+        let _guard = self.names.lock().map_err(|_| AppError::Storage("Name mapping lock poisoned".to_string()))?;
+        let mut names = self.load_names()?;
+        if let Some(name) = names.get(&date) {
+            return Ok(Some(self.data_dir.join(name)));
+        }
+        if !create {
+            return Ok(None);
+        }
+
+        let mut id = [0u8; 16];
+        getrandom::getrandom(&mut id).map_err(|e| AppError::Storage(format!("No randomness available: {}", e)))?;
+        let name = format!("{}.{}", to_hex(&id), EXTENSION);
+        names.insert(date, name.clone());
+        self.save_names(&names)?;
+        Ok(Some(self.data_dir.join(name)))
+    }
+}
+
+impl Storage for EncryptedStorage {
+    fn load_day(&self, date: NaiveDate) -> AppResult<DayLog> {
+        match self.day_path(date, false)? {
+            Some(path) => match self.decrypt(&path, &day_aad(date))? {
+                Some(json) => schema::parse_day(&json).map_err(|e| schema::located(e, path.display())),
+                None => Ok(DayLog::new(date)),
+            },
+            None => Ok(DayLog::new(date)),
+        }
+    }
+
+    fn save_day(&self, day: &DayLog) -> AppResult<()> {
+        let path = self.day_path(day.date, true)?.ok_or_else(|| AppError::Storage("No file for day".to_string()))?;
+        write_file(&path, &self.seal(&day_aad(day.date), &schema::day_to_json(day, false)?)?)
+    }
+
+    fn list_dates(&self) -> AppResult<Vec<NaiveDate>> {
+        if self.encrypt_filenames {
+            return Ok(self.load_names()?.into_keys().collect());
+        }
+
+        let entries = fs::read_dir(&self.data_dir)
+            .map_err(|e| AppError::Storage(format!("Could not read data directory: {}", e)))?;
+        let mut dates: Vec<NaiveDate> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                let stem = name.strip_suffix(&format!(".{}", EXTENSION))?;
+                NaiveDate::parse_from_str(stem, "%Y-%m-%d").ok()
+            })
+            .collect();
+        dates.sort();
+        Ok(dates)
+    }
+
+    fn delete_day(&self, date: NaiveDate) -> AppResult<()> {
+        let Some(path) = self.day_path(date, false)? else {
+            return Ok(());
+        };
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| AppError::Storage(format!("Could not delete {}: {}", path.display(), e)))?;
+        }
+
+        if self.encrypt_filenames {
+            let _guard = self.names.lock().map_err(|_| AppError::Storage("Name mapping lock poisoned".to_string()))?;
+            let mut names = self.load_names()?;
+            names.remove(&date);
+            self.save_names(&names)?;
+        }
+        Ok(())
+    }
+
+    fn load_trash(&self) -> AppResult<Vec<TrashedNote>> {
+        let path = self.data_dir.join(TRASH_FILE);
+        match self.decrypt(&path, b"trash")? {
+            Some(json) => schema::parse_trash(&json).map_err(|e| schema::located(e, path.display())),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn save_trash(&self, trash: &[TrashedNote]) -> AppResult<()> {
+        let path = self.data_dir.join(TRASH_FILE);
+        if trash.is_empty() {
+            if path.exists() {
+                fs::remove_file(&path).map_err(|e| AppError::Storage(format!("Could not delete {}: {}", path.display(), e)))?;
+            }
+            return Ok(());
+        }
+        write_file(&path, &self.seal(b"trash", &schema::trash_to_json(trash, false)?)?)
+    }
+
+    fn outdated(&self) -> AppResult<Vec<(Option<NaiveDate>, u32)>> {
+        let mut documents = Vec::new();
+        for date in self.list_dates()? {
+            if let Some(path) = self.day_path(date, false)? {
+                documents.push((Some(date), self.decrypt(&path, &day_aad(date))?));
+            }
+        }
+        documents.push((None, self.decrypt(&self.data_dir.join(TRASH_FILE), b"trash")?));
+
+        let mut outdated = Vec::new();
+        for (date, json) in documents {
+            let Some(json) = json else { continue };
+            let version = schema::version_of_json(&json)?;
+            if version < SCHEMA_VERSION {
+                outdated.push((date, version));
+            }
+        }
+        Ok(outdated)
+    }
+}
+
+fn day_aad(date: NaiveDate) -> Vec<u8> {
+    format!("day {}", date.format("%Y-%m-%d")).into_bytes()
+}
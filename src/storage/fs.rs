@@ -1,30 +1,37 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use chrono::NaiveDate;
-use directories::ProjectDirs;
 
 use crate::{
     error::{AppError, AppResult},
-    models::DayLog,
+    models::{DayLog, TrashedNote},
+    notebooks,
+    schema::{self, SCHEMA_VERSION},
 };
-use super::Storage;
+use super::{write_file, Storage};
 
 pub struct FsStorage {
     data_dir: PathBuf,
 }
 
 impl FsStorage {
-    pub fn new() -> AppResult<Self> {
-        let dirs = ProjectDirs::from("com", "example", "aigenda")
-            .ok_or_else(|| AppError::Storage("Could not determine data directory".to_string()))?;
-
-        let data_dir = dirs.data_dir().to_path_buf();
+    /// Stores days in `data_dir`, creating it if needed
+    pub fn with_root(data_dir: PathBuf) -> AppResult<Self> {
         fs::create_dir_all(&data_dir)
-            .map_err(|e| AppError::Storage(format!("Could not create data directory: {}", e)))?;
+            .map_err(|e| AppError::Config(format!("Could not create data directory {}: {}", data_dir.display(), e)))?;
 
         Ok(Self { data_dir })
     }
 
+    /// The data dir of the notebook in use
+    pub fn default_data_dir() -> AppResult<PathBuf> {
+        Ok(notebooks::current()?.data_dir.clone())
+    }
+
+    fn trash_file_path(&self) -> PathBuf {
+        self.data_dir.join("trash.json")
+    }
+
     fn day_file_path(&self, date: NaiveDate) -> PathBuf {
         self.data_dir.join(format!("{}.json", date.format("%Y-%m-%d")))
     }
@@ -38,50 +45,108 @@ impl Storage for FsStorage {
             return Ok(DayLog::new(date));
         }
 
-        let content = fs::read_to_string(&path)
-            .map_err(|e| AppError::Storage(format!("Could not read file {}: {}", path.display(), e)))?;
-
-        serde_json::from_str(&content)
-            .map_err(|e| AppError::Storage(format!("Could not parse JSON from {}: {}", path.display(), e)))
+        let content = read(&path)?;
+        schema::parse_day(&content)
+            .map_err(|e| schema::located(e, path.display()))
     }
 
     fn save_day(&self, day: &DayLog) -> AppResult<()> {
         let path = self.day_file_path(day.date);
 
-        let content = serde_json::to_string_pretty(day)
-            .map_err(|e| AppError::Storage(format!("Could not serialize day log: {}", e)))?;
+        let content = schema::day_to_json(day, true)?;
 
-        fs::write(&path, content)
-            .map_err(|e| AppError::Storage(format!("Could not write to {}: {}", path.display(), e)))
+        write_file(&path, content.as_bytes())
     }
 
-    fn iter_days(&self) -> AppResult<Vec<DayLog>> {
+    /// Only reads the directory listing: `YYYY-MM-DD.json` file names are the dates,
+    /// anything else in the folder is ignored
+    fn list_dates(&self) -> AppResult<Vec<NaiveDate>> {
         let entries = fs::read_dir(&self.data_dir)
             .map_err(|e| AppError::Storage(format!("Could not read data directory: {}", e)))?;
 
-        let mut day_logs = Vec::new();
-
+        let mut dates = Vec::new();
         for entry in entries {
             let entry = entry
                 .map_err(|e| AppError::Storage(format!("Could not read directory entry: {}", e)))?;
 
             let path = entry.path();
-            if path.extension().and_then(|s| s.to_str()) == Some("json") {
-                let content = fs::read_to_string(&path)
-                    .map_err(|e| AppError::Storage(format!("Could not read file {}: {}", path.display(), e)))?;
-
-                match serde_json::from_str::<DayLog>(&content) {
-                    Ok(day_log) => day_logs.push(day_log),
-                    Err(e) => return Err(AppError::Storage(format!(
-                        "Could not parse JSON from {}: {}", path.display(), e
-                    ))),
-                }
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+            if let Some(date) = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(|s| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok())
+            {
+                dates.push(date);
             }
         }
 
-        // Sort by date
-        day_logs.sort_by_key(|day_log| day_log.date);
+        dates.sort();
+        Ok(dates)
+    }
+
+    fn delete_day(&self, date: NaiveDate) -> AppResult<()> {
+        let path = self.day_file_path(date);
+        if !path.exists() {
+            return Ok(());
+        }
+
+        fs::remove_file(&path)
+            .map_err(|e| AppError::Storage(format!("Could not delete {}: {}", path.display(), e)))
+    }
+
+    fn exists(&self, date: NaiveDate) -> AppResult<bool> {
+        Ok(self.day_file_path(date).exists())
+    }
+
+    fn load_trash(&self) -> AppResult<Vec<TrashedNote>> {
+        let path = self.trash_file_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = read(&path)?;
+        schema::parse_trash(&content)
+            .map_err(|e| schema::located(e, path.display()))
+    }
+
+    fn save_trash(&self, trash: &[TrashedNote]) -> AppResult<()> {
+        let path = self.trash_file_path();
+        if trash.is_empty() {
+            return match fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    Err(AppError::Storage(format!("Could not delete {}: {}", path.display(), e)))
+                }
+                _ => Ok(()),
+            };
+        }
 
-        Ok(day_logs)
+        let content = schema::trash_to_json(trash, true)?;
+
+        write_file(&path, content.as_bytes())
+    }
+
+    fn outdated(&self) -> AppResult<Vec<(Option<NaiveDate>, u32)>> {
+        let mut files: Vec<(Option<NaiveDate>, PathBuf)> = self
+            .list_dates()?
+            .into_iter()
+            .map(|date| (Some(date), self.day_file_path(date)))
+            .collect();
+        files.push((None, self.trash_file_path()));
+
+        let mut outdated = Vec::new();
+        for (date, path) in files.into_iter().filter(|(_, path)| path.exists()) {
+            let version = schema::version_of_json(&read(&path)?).map_err(|e| schema::located(e, path.display()))?;
+            if version < SCHEMA_VERSION {
+                outdated.push((date, version));
+            }
+        }
+        Ok(outdated)
     }
-}
\ No newline at end of file
+}
+
+fn read(path: &Path) -> AppResult<String> {
+    fs::read_to_string(path)
+        .map_err(|e| AppError::Storage(format!("Could not read file {}: {}", path.display(), e)))
+}
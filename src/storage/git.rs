@@ -0,0 +1,315 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use chrono::NaiveDate;
+
+use crate::{
+    config::GitConfig,
+    error::{AppError, AppResult},
+    models::{DayLog, Editor, TrashedNote},
+};
+use super::Storage;
+
+/// Descriptions of the changes since the last commit, one per line. Kept in `.git` so
+/// every store writing to the repo (the CLI's and the agent's) batches into one commit.
+const PENDING_FILE: &str = "aigenda-pending";
+
+/// Folders in the data dir that are derived data, not journal
+const IGNORED: &[&str] = &[".embeddings/", "notebooks/"];
+
+/// How git reports a pull or push that can't go through without the user
+const CONFLICTS: &[&str] = &["CONFLICT", "could not apply", "[rejected]", "non-fast-forward"];
+
+/// A git repository run through the `git` executable
+pub struct GitRepo {
+    dir: PathBuf,
+}
+
+impl GitRepo {
+    /// Opens the repository in `dir`, creating it on `branch` if there is none
+    pub fn open_or_init(dir: &Path, branch: &str) -> AppResult<Self> {
+        let repo = Self { dir: dir.to_path_buf() };
+        if !dir.join(".git").exists() {
+            repo.git(&["init", "-q"])?;
+            repo.git(&["symbolic-ref", "HEAD", &format!("refs/heads/{}", branch)])?;
+            let ignore: String = IGNORED.iter().map(|p| format!("{}\n", p)).collect();
+            fs::write(dir.join(".gitignore"), ignore)
+                .map_err(|e| AppError::Storage(format!("Could not write .gitignore: {}", e)))?;
+        }
+        Ok(repo)
+    }
+
+    /// Queues a description for the next commit
+    pub fn record(&self, change: &str) -> AppResult<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.pending_path())
+            .map_err(|e| AppError::Storage(format!("Could not open git change queue: {}", e)))?;
+        writeln!(file, "{}", change)
+            .map_err(|e| AppError::Storage(format!("Could not write git change queue: {}", e)))
+    }
+
+    /// Commits everything changed in the data dir, described by the queued changes;
+    /// returns whether there was anything to commit
+    pub fn commit(&self, editor: Editor) -> AppResult<bool> {
+        let pending = self.pending_path();
+        // Repeated changes are listed once, with a count
+        let mut counted: Vec<(String, usize)> = Vec::new();
+        for line in fs::read_to_string(&pending).unwrap_or_default().lines() {
+            match counted.iter_mut().find(|(c, _)| c == line) {
+                Some((_, count)) => *count += 1,
+                None => counted.push((line.to_string(), 1)),
+            }
+        }
+        let changes: Vec<String> = counted
+            .into_iter()
+            .map(|(c, n)| if n > 1 { format!("{} (x{})", c, n) } else { c })
+            .collect();
+
+        self.git(&["add", "-A"])?;
+        let staged = !self.git_status(&["diff", "--cached", "--quiet"])?;
+        if staged {
+            let message = commit_message(&changes, editor);
+            let mut args = vec!["commit", "-q", "-m", &message];
+            let identity = self.default_identity()?;
+            args.splice(0..0, identity.iter().map(String::as_str));
+            self.git(&args)?;
+        }
+
+        let _ = fs::remove_file(pending);
+        Ok(staged)
+    }
+
+    /// Pulls (rebasing local commits) and pushes `branch`; `remote` is a URL or a remote name
+    pub fn sync(&self, remote: &str, branch: &str) -> AppResult<()> {
+        let remote = self.remote_name(remote)?;
+        let has_branch = self.git_status(&["ls-remote", "--exit-code", "--heads", &remote, branch])?;
+        if has_branch {
+            let identity = self.default_identity()?;
+            let mut args: Vec<&str> = identity.iter().map(String::as_str).collect();
+            args.extend(["pull", "-q", "--rebase", &remote, branch]);
+            self.git(&args)?;
+        }
+        self.git(&["push", "-q", "-u", &remote, &format!("HEAD:refs/heads/{}", branch)])?;
+        Ok(())
+    }
+
+    /// Remote names are used as is; a URL is set up as `origin`
+    fn remote_name(&self, remote: &str) -> AppResult<String> {
+        let remotes = self.git(&["remote"])?;
+        if remotes.lines().any(|r| r == remote) {
+            return Ok(remote.to_string());
+        }
+        if remotes.lines().any(|r| r == "origin") {
+            self.git(&["remote", "set-url", "origin", remote])?;
+        } else {
+            self.git(&["remote", "add", "origin", remote])?;
+        }
+        Ok("origin".to_string())
+    }
+
+    /// `-c` options naming a committer when git has none configured
+    fn default_identity(&self) -> AppResult<Vec<String>> {
+        if self.git_status(&["config", "user.email"])? {
+            return Ok(Vec::new());
+        }
+        Ok(["-c", "user.name=aigenda", "-c", "user.email=aigenda@localhost"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect())
+    }
+
+    fn pending_path(&self) -> PathBuf {
+        self.dir.join(".git").join(PENDING_FILE)
+    }
+
+    fn command(&self, args: &[&str]) -> AppResult<std::process::Output> {
+        Command::new("git")
+            .args(args)
+            .current_dir(&self.dir)
+            .output()
+            .map_err(|e| AppError::Config(format!("Could not run git (is it installed?): {}", e)))
+    }
+
+    fn git(&self, args: &[&str]) -> AppResult<String> {
+        let output = self.command(args)?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let message = format!(
+                "git {} failed: {}",
+                args.iter().find(|a| !a.starts_with('-') && !a.contains('=')).unwrap_or(&""),
+                stderr.trim()
+            );
+            // Diverged histories need the user to step in; anything else is git itself failing
+            if CONFLICTS.iter().any(|marker| stderr.contains(marker)) {
+                return Err(AppError::Conflict(message));
+            }
+            return Err(AppError::Io(std::io::Error::other(message)));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Runs a command whose exit status is the answer
+    fn git_status(&self, args: &[&str]) -> AppResult<bool> {
+        Ok(self.command(args)?.status.success())
+    }
+}
+
+fn commit_message(changes: &[String], editor: Editor) -> String {
+    let prefix = match editor {
+        Editor::Cli => String::new(),
+        other => format!("{}: ", other),
+    };
+    match changes {
+        [] => format!("{}update notes", prefix),
+        [change] => format!("{}{}", prefix, change),
+        [first, rest @ ..] => {
+            let body: Vec<String> = changes.iter().map(|c| format!("- {}", c)).collect();
+            format!("{}{} and {} more\n\n{}", prefix, first, rest.len(), body.join("\n"))
+        }
+    }
+}
+
+/// Commits the data dir to git at every checkpoint, with messages describing what changed.
+/// Without a `git` section in the config it only passes calls through.
+pub struct GitStorage<S: Storage> {
+    inner: S,
+    repo: Option<GitRepo>,
+}
+
+impl<S: Storage> GitStorage<S> {
+    pub fn new(inner: S, repo: Option<GitRepo>) -> Self {
+        Self { inner, repo }
+    }
+
+    /// Versions `data_dir` when the config has a `git` section
+    pub fn from_config(inner: S, config: Option<&GitConfig>, data_dir: &Path) -> AppResult<Self> {
+        let repo = config
+            .map(|git| GitRepo::open_or_init(data_dir, git.branch()))
+            .transpose()?;
+        Ok(Self::new(inner, repo))
+    }
+
+    fn record(&self, change: Option<String>) -> AppResult<()> {
+        match (&self.repo, change) {
+            (Some(repo), Some(change)) => repo.record(&change),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<S: Storage> Storage for GitStorage<S> {
+    fn load_day(&self, date: NaiveDate) -> AppResult<DayLog> {
+        self.inner.load_day(date)
+    }
+
+    fn save_day(&self, day: &DayLog) -> AppResult<()> {
+        let change = match &self.repo {
+            Some(_) => describe(&self.inner.load_day(day.date)?, day),
+            None => None,
+        };
+        self.inner.save_day(day)?;
+        self.record(change)
+    }
+
+    fn list_dates(&self) -> AppResult<Vec<NaiveDate>> {
+        self.inner.list_dates()
+    }
+
+    fn delete_day(&self, date: NaiveDate) -> AppResult<()> {
+        let change = match &self.repo {
+            Some(_) => describe(&self.inner.load_day(date)?, &DayLog::new(date)),
+            None => None,
+        };
+        self.inner.delete_day(date)?;
+        self.record(change)
+    }
+
+    fn exists(&self, date: NaiveDate) -> AppResult<bool> {
+        self.inner.exists(date)
+    }
+
+    fn load_trash(&self) -> AppResult<Vec<TrashedNote>> {
+        self.inner.load_trash()
+    }
+
+    fn save_trash(&self, trash: &[TrashedNote]) -> AppResult<()> {
+        let change = match &self.repo {
+            Some(_) => {
+                let before = self.inner.load_trash()?.len();
+                (trash.len() < before).then(|| plural(before - trash.len(), "remove {} from the trash"))
+            }
+            None => None,
+        };
+        self.inner.save_trash(trash)?;
+        self.record(change)
+    }
+
+    fn outdated(&self) -> AppResult<Vec<(Option<NaiveDate>, u32)>> {
+        self.inner.outdated()
+    }
+
+    fn checkpoint(&self, editor: Editor) -> AppResult<()> {
+        self.inner.checkpoint(editor)?;
+        if let Some(repo) = &self.repo {
+            repo.commit(editor)?;
+        }
+        Ok(())
+    }
+}
+
+/// "add note to 2025-01-15", "update note 3 on 2025-01-15"... Notes are matched by timestamp.
+fn describe(before: &DayLog, after: &DayLog) -> Option<String> {
+    let date = after.date.format("%Y-%m-%d");
+    let position = |day: &DayLog, note: &crate::models::Note| day.notes.iter().position(|n| n.when == note.when);
+
+    let added = after.notes.iter().filter(|n| position(before, n).is_none()).count();
+    let removed: Vec<usize> = before
+        .notes
+        .iter()
+        .enumerate()
+        .filter(|(_, n)| position(after, n).is_none())
+        .map(|(i, _)| i + 1)
+        .collect();
+    let updated: Vec<usize> = after
+        .notes
+        .iter()
+        .enumerate()
+        .filter(|(_, n)| {
+            position(before, n).is_some_and(|i| {
+                let old = &before.notes[i];
+                old.text != n.text || old.task != n.task || old.tags != n.tags
+            })
+        })
+        .map(|(i, _)| i + 1)
+        .collect();
+
+    let mut parts = Vec::new();
+    if added > 0 {
+        parts.push(format!("{} to {}", plural(added, "add {}"), date));
+    }
+    match removed.as_slice() {
+        [] => {}
+        [n] => parts.push(format!("remove note {} from {}", n, date)),
+        many => parts.push(format!("remove {} notes from {}", many.len(), date)),
+    }
+    match updated.as_slice() {
+        [] => {}
+        [n] => parts.push(format!("update note {} on {}", n, date)),
+        many => parts.push(format!("update {} notes on {}", many.len(), date)),
+    }
+    if parts.is_empty() && before.notes.iter().map(|n| n.when).ne(after.notes.iter().map(|n| n.when)) {
+        parts.push(format!("reorder notes on {}", date));
+    }
+
+    (!parts.is_empty()).then(|| parts.join(", "))
+}
+
+/// "add note" / "add 3 notes"
+fn plural(count: usize, template: &str) -> String {
+    let noun = if count == 1 { "note".to_string() } else { format!("{} notes", count) };
+    template.replace("{}", &noun)
+}
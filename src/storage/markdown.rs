@@ -0,0 +1,502 @@
+//! Days as Markdown, one `YYYY-MM-DD.md` per day, readable and editable in Obsidian or vim:
+//!
+//! ```markdown
+//! ---
+//! date: 2025-01-15
+//! schema_version: 2
+//! notes:
+//!   - {"when":"2025-01-15T09:12:41.5+01:00","bullet":"8c259084"}
+//!   - {"when":"2025-01-15T10:30:02+01:00","bullet":"e09a2a63","edited_at":"2025-01-15T11:00:00+01:00","edited_by":"cli"}
+//! ---
+//!
+//! - 09:12 Fixed the login bug #work
+//! - [ ] !high (due 2025-01-20) 10:30 Deploy the release
+//!   with the migration first
+//! ```
+//!
+//! Each bullet is a note: task checkbox, priority and due date, time, text (continued on
+//! indented lines) and tags. The front matter keeps what a bullet can't show: the exact
+//! timestamp, when a task was closed, and the edit history. Its entries are matched to bullets
+//! by a fingerprint of their text and tags, then by time, so bullets can be reordered, retimed,
+//! removed or added (`- 14:00 text`) in an editor.
+//! A note whose bullet wouldn't read back the same (say, text ending in `#word`) also keeps
+//! an exact copy there, which wins over its bullet.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime, TimeZone, Timelike};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    clock::clock,
+    error::{AppError, AppResult},
+    models::{DayLog, Editor, Note, Priority, Revision, Task, TaskStatus, TrashedNote},
+    schema::{self, SCHEMA_VERSION},
+};
+use super::{write_file, Storage};
+
+pub struct MarkdownStorage {
+    data_dir: PathBuf,
+}
+
+impl MarkdownStorage {
+    /// Stores days in `data_dir`, creating it if needed
+    pub fn with_root(data_dir: PathBuf) -> AppResult<Self> {
+        fs::create_dir_all(&data_dir)
+            .map_err(|e| AppError::Config(format!("Could not create data directory {}: {}", data_dir.display(), e)))?;
+
+        Ok(Self { data_dir })
+    }
+
+    fn trash_file_path(&self) -> PathBuf {
+        self.data_dir.join("trash.json")
+    }
+
+    fn day_file_path(&self, date: NaiveDate) -> PathBuf {
+        self.data_dir.join(format!("{}.md", date.format("%Y-%m-%d")))
+    }
+}
+
+impl Storage for MarkdownStorage {
+    fn load_day(&self, date: NaiveDate) -> AppResult<DayLog> {
+        let path = self.day_file_path(date);
+        if !path.exists() {
+            return Ok(DayLog::new(date));
+        }
+
+        let content = read(&path)?;
+        let corrupt = |e| AppError::Corrupt(format!("{}: {}", path.display(), e));
+        schema::check_version(version(&content).map_err(corrupt)?).map_err(|e| schema::located(e, path.display()))?;
+        parse(date, &content).map_err(corrupt)
+    }
+
+    fn save_day(&self, day: &DayLog) -> AppResult<()> {
+        let path = self.day_file_path(day.date);
+        write_file(&path, render(day)?.as_bytes())
+    }
+
+    /// `YYYY-MM-DD.md` file names are the dates; other notes in the folder are left alone
+    fn list_dates(&self) -> AppResult<Vec<NaiveDate>> {
+        let entries = fs::read_dir(&self.data_dir)
+            .map_err(|e| AppError::Storage(format!("Could not read data directory: {}", e)))?;
+
+        let mut dates: Vec<NaiveDate> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                NaiveDate::parse_from_str(name.strip_suffix(".md")?, "%Y-%m-%d").ok()
+            })
+            .collect();
+        dates.sort();
+        Ok(dates)
+    }
+
+    fn delete_day(&self, date: NaiveDate) -> AppResult<()> {
+        let path = self.day_file_path(date);
+        if !path.exists() {
+            return Ok(());
+        }
+
+        fs::remove_file(&path)
+            .map_err(|e| AppError::Storage(format!("Could not delete {}: {}", path.display(), e)))
+    }
+
+    fn exists(&self, date: NaiveDate) -> AppResult<bool> {
+        Ok(self.day_file_path(date).exists())
+    }
+
+    fn load_trash(&self) -> AppResult<Vec<TrashedNote>> {
+        let path = self.trash_file_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        schema::parse_trash(&read(&path)?).map_err(|e| schema::located(e, path.display()))
+    }
+
+    fn save_trash(&self, trash: &[TrashedNote]) -> AppResult<()> {
+        let path = self.trash_file_path();
+        if trash.is_empty() {
+            return match fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    Err(AppError::Storage(format!("Could not delete {}: {}", path.display(), e)))
+                }
+                _ => Ok(()),
+            };
+        }
+
+        write_file(&path, schema::trash_to_json(trash, true)?.as_bytes())
+    }
+
+    fn outdated(&self) -> AppResult<Vec<(Option<NaiveDate>, u32)>> {
+        let mut outdated = Vec::new();
+        for date in self.list_dates()? {
+            let path = self.day_file_path(date);
+            let version = version(&read(&path)?).map_err(|e| AppError::Corrupt(format!("{}: {}", path.display(), e)))?;
+            if version < SCHEMA_VERSION {
+                outdated.push((Some(date), version));
+            }
+        }
+
+        let path = self.trash_file_path();
+        if path.exists() {
+            let version = schema::version_of_json(&read(&path)?).map_err(|e| schema::located(e, path.display()))?;
+            if version < SCHEMA_VERSION {
+                outdated.push((None, version));
+            }
+        }
+        Ok(outdated)
+    }
+}
+
+fn read(path: &Path) -> AppResult<String> {
+    fs::read_to_string(path)
+        .map_err(|e| AppError::Storage(format!("Could not read file {}: {}", path.display(), e)))
+}
+
+/// A note's front matter entry
+#[derive(Serialize, Deserialize, Debug)]
+struct NoteMeta {
+    when: DateTime<FixedOffset>,
+    /// The bullet's fingerprint, to find it again after it's moved or retimed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    bullet: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    closed_at: Option<DateTime<FixedOffset>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    edited_at: Option<DateTime<FixedOffset>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    edited_by: Option<Editor>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    revisions: Vec<Revision>,
+    /// Set, with `tags` and `task`, only when the bullet can't carry the note exactly
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tags: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    task: Option<Task>,
+}
+
+/// What a bullet says about a note
+#[derive(PartialEq, Debug)]
+struct Bullet {
+    status: Option<TaskStatus>,
+    priority: Option<Priority>,
+    due: Option<NaiveDate>,
+    time: Option<NaiveTime>,
+    text: String,
+    tags: Vec<String>,
+}
+
+impl Bullet {
+    fn of(note: &Note) -> Self {
+        Self {
+            status: note.task.as_ref().map(|t| t.status),
+            priority: note.task.as_ref().and_then(|t| t.priority),
+            due: note.task.as_ref().and_then(|t| t.due),
+            time: NaiveTime::parse_from_str(&note.when.format("%H:%M").to_string(), "%H:%M").ok(),
+            text: note.text.clone(),
+            tags: note.tags.clone(),
+        }
+    }
+
+    fn render(&self) -> String {
+        let mut out = String::from("-");
+        if let Some(status) = self.status {
+            let task = Task { status, due: self.due, priority: self.priority, closed_at: None };
+            out.push(' ');
+            out.push_str(&task.label());
+        }
+        if let Some(time) = self.time {
+            out.push_str(&format!(" {}", time.format("%H:%M")));
+        }
+
+        let mut lines = self.text.split('\n');
+        let first = lines.next().unwrap_or_default();
+        if !first.is_empty() {
+            out.push(' ');
+            out.push_str(first);
+        }
+        for tag in &self.tags {
+            out.push_str(&format!(" #{}", tag));
+        }
+        for line in lines {
+            out.push('\n');
+            if !line.is_empty() {
+                out.push_str("  ");
+                out.push_str(line);
+            }
+        }
+        out.push('\n');
+        out
+    }
+
+    /// A short hash of the text and tags, which stay put when a bullet is moved, retimed or ticked
+    fn fingerprint(&self) -> String {
+        let digest = Sha256::new()
+            .chain_update(&self.text)
+            .chain_update([0])
+            .chain_update(self.tags.join(" "))
+            .finalize();
+        digest[..4].iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    fn at(&self, when: &DateTime<FixedOffset>) -> bool {
+        self.time.is_some_and(|t| (t.hour(), t.minute()) == (when.hour(), when.minute()))
+    }
+
+    /// Reads a bullet's first line (after `- `) and its continuation lines
+    fn parse(first: &str, rest: &[&str]) -> Self {
+        let mut words = first;
+        let mut status = None;
+        for candidate in [TaskStatus::Open, TaskStatus::Done, TaskStatus::Cancelled] {
+            let label = Task { status: candidate, due: None, priority: None, closed_at: None }.label();
+            if let Some(after) = strip_word(words, &label).or_else(|| strip_word(words, &label.to_uppercase())) {
+                status = Some(candidate);
+                words = after;
+                break;
+            }
+        }
+
+        let mut priority = None;
+        let mut due = None;
+        if status.is_some() {
+            if let Some((word, after)) = split_word(words) {
+                if let Some(p) = word.strip_prefix('!').and_then(|p| Priority::from_str(p, false).ok()) {
+                    priority = Some(p);
+                    words = after;
+                }
+            }
+            if let Some((date, after)) = words.strip_prefix("(due ").and_then(|w| w.split_once(')')) {
+                if let Ok(date) = NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+                    due = Some(date);
+                    words = after.strip_prefix(' ').unwrap_or(after);
+                }
+            }
+        }
+
+        let mut time = None;
+        if let Some((word, after)) = split_word(words) {
+            if let Ok(t) = NaiveTime::parse_from_str(word, "%H:%M") {
+                time = Some(t);
+                words = after;
+            }
+        }
+
+        let mut tags = Vec::new();
+        while let Some((head, tag)) = words.rsplit_once(' ') {
+            match tag.strip_prefix('#') {
+                Some(tag) if !tag.is_empty() => {
+                    tags.insert(0, tag.to_string());
+                    words = head;
+                }
+                _ => break,
+            }
+        }
+
+        let mut lines = vec![words.to_string()];
+        lines.extend(rest.iter().map(|line| line.strip_prefix("  ").unwrap_or(line).to_string()));
+        while lines.len() > 1 && lines.last().is_some_and(|l| l.is_empty()) {
+            lines.pop();
+        }
+
+        Self { status, priority, due, time, text: lines.join("\n"), tags }
+    }
+}
+
+/// The rest of `s` after the word `word`
+fn strip_word<'a>(s: &'a str, word: &str) -> Option<&'a str> {
+    let after = s.strip_prefix(word)?;
+    if after.is_empty() {
+        Some(after)
+    } else {
+        after.strip_prefix(' ')
+    }
+}
+
+fn split_word(s: &str) -> Option<(&str, &str)> {
+    if s.is_empty() {
+        return None;
+    }
+    Some(s.split_once(' ').unwrap_or((s, "")))
+}
+
+fn render(day: &DayLog) -> AppResult<String> {
+    let mut front = format!("---\ndate: {}\nschema_version: {}\n", day.date.format("%Y-%m-%d"), SCHEMA_VERSION);
+    if !day.recurring.is_empty() {
+        front.push_str(&format!("recurring: {}\n", to_json(&day.recurring)?));
+    }
+    if !day.notes.is_empty() {
+        front.push_str("notes:\n");
+    }
+
+    let mut body = String::new();
+    for note in &day.notes {
+        let bullet = Bullet::of(note);
+        let rendered = bullet.render();
+        // Read back the way `parse` will, which also takes `\r\n` line ends
+        let lines: Vec<&str> = rendered
+            .trim_end_matches('\n')
+            .split('\n')
+            .map(|l| l.strip_suffix('\r').unwrap_or(l))
+            .collect();
+        let read_back = Bullet::parse(lines[0].strip_prefix("- ").unwrap_or_default(), &lines[1..]);
+        let exact = read_back == bullet && !rendered.contains('\r');
+
+        let meta = NoteMeta {
+            when: note.when,
+            bullet: Some(read_back.fingerprint()),
+            closed_at: if exact { note.task.as_ref().and_then(|t| t.closed_at) } else { None },
+            edited_at: note.edited_at,
+            edited_by: note.edited_by,
+            revisions: note.revisions.clone(),
+            text: (!exact).then(|| note.text.clone()),
+            tags: (!exact).then(|| note.tags.clone()),
+            task: if exact { None } else { note.task.clone() },
+        };
+        front.push_str(&format!("  - {}\n", to_json(&meta)?));
+        body.push_str(&rendered);
+    }
+
+    Ok(format!("{}---\n\n{}", front, body))
+}
+
+/// The `schema_version` in the front matter; files written before it was kept are version 1
+fn version(content: &str) -> Result<u32, String> {
+    let mut lines = content.lines();
+    if lines.next() != Some("---") {
+        return Ok(1);
+    }
+    lines
+        .take_while(|line| *line != "---")
+        .find_map(|line| line.strip_prefix("schema_version: "))
+        .map_or(Ok(1), |v| v.trim().parse().map_err(|_| format!("Invalid schema_version: {}", v)))
+}
+
+fn parse(date: NaiveDate, content: &str) -> Result<DayLog, String> {
+    let mut day = DayLog::new(date);
+    let mut metas: Vec<Option<NoteMeta>> = Vec::new();
+
+    let mut lines = content.split('\n').map(|l| l.strip_suffix('\r').unwrap_or(l)).peekable();
+    if lines.peek() == Some(&"---") {
+        lines.next();
+        let mut in_notes = false;
+        for line in lines.by_ref() {
+            if line == "---" {
+                break;
+            }
+            if let Some(entry) = line.strip_prefix("  - ").filter(|_| in_notes) {
+                metas.push(Some(serde_json::from_str(entry).map_err(|e| format!("Invalid note entry: {}", e))?));
+                continue;
+            }
+            in_notes = line == "notes:";
+            if let Some(value) = line.strip_prefix("recurring: ") {
+                day.recurring = serde_json::from_str(value).map_err(|e| format!("Invalid recurring list: {}", e))?;
+            }
+            // Other properties (date, schema_version, anything an editor adds) aren't part of the day
+        }
+    }
+
+    let mut bullets: Vec<(&str, Vec<&str>)> = Vec::new();
+    let mut in_bullet = false;
+    for line in lines {
+        if let Some(first) = line.strip_prefix("- ").or((line == "-").then_some("")) {
+            bullets.push((first, Vec::new()));
+            in_bullet = true;
+        } else if in_bullet && (line.is_empty() || line.starts_with("  ")) {
+            if let Some((_, rest)) = bullets.last_mut() {
+                rest.push(line);
+            }
+        } else {
+            // Any other paragraph ends the bullet, and isn't kept
+            in_bullet = false;
+        }
+    }
+
+    let bullets: Vec<Bullet> = bullets.into_iter().map(|(first, rest)| Bullet::parse(first, &rest)).collect();
+    let mut found: Vec<Option<NoteMeta>> = bullets.iter().map(|_| None).collect();
+    // Unchanged bullets first, wherever they were moved, then retimed ones, then edited ones by
+    // their time (files written before fingerprints were kept only match by time)
+    let passes: [fn(&Bullet, &NoteMeta) -> bool; 3] = [
+        |b, m| m.bullet.as_ref() == Some(&b.fingerprint()) && b.at(&m.when),
+        |b, m| m.bullet.as_ref() == Some(&b.fingerprint()),
+        |b, m| b.at(&m.when),
+    ];
+    for pass in passes {
+        for (bullet, meta) in bullets.iter().zip(found.iter_mut()).filter(|(_, meta)| meta.is_none()) {
+            *meta = take_meta(&mut metas, |m| pass(bullet, m));
+        }
+    }
+
+    for (bullet, meta) in bullets.into_iter().zip(found) {
+        day.notes.push(note(date, bullet, meta)?);
+    }
+    Ok(day)
+}
+
+/// The first unclaimed entry that `matches`
+fn take_meta(metas: &mut [Option<NoteMeta>], matches: impl Fn(&NoteMeta) -> bool) -> Option<NoteMeta> {
+    metas.iter_mut().find(|m| m.as_ref().is_some_and(&matches))?.take()
+}
+
+fn note(date: NaiveDate, bullet: Bullet, meta: Option<NoteMeta>) -> Result<Note, String> {
+    let Some(meta) = meta else {
+        // Written in an editor: the time on the bullet, on our clock
+        let time = bullet.time.unwrap_or(NaiveTime::MIN);
+        let offset = *clock().now().offset();
+        let when = offset
+            .from_local_datetime(&date.and_time(time))
+            .single()
+            .ok_or_else(|| format!("Invalid time {}", time))?;
+        return Ok(Note {
+            when,
+            text: bullet.text,
+            tags: bullet.tags,
+            task: bullet.status.map(|status| Task { status, due: bullet.due, priority: bullet.priority, closed_at: None }),
+            edited_at: None,
+            edited_by: None,
+            revisions: Vec::new(),
+        });
+    };
+
+    // A bullet given another time moves the note there
+    let when = match bullet.time {
+        Some(time) if !bullet.at(&meta.when) => meta
+            .when
+            .offset()
+            .from_local_datetime(&date.and_time(time))
+            .single()
+            .ok_or_else(|| format!("Invalid time {}", time))?,
+        _ => meta.when,
+    };
+    let (text, tags, task) = match meta.text {
+        Some(text) => (text, meta.tags.unwrap_or_default(), meta.task),
+        None => (
+            bullet.text,
+            bullet.tags,
+            bullet.status.map(|status| Task {
+                status,
+                due: bullet.due,
+                priority: bullet.priority,
+                closed_at: meta.closed_at,
+            }),
+        ),
+    };
+    Ok(Note {
+        when,
+        text,
+        tags,
+        task,
+        edited_at: meta.edited_at,
+        edited_by: meta.edited_by,
+        revisions: meta.revisions,
+    })
+}
+
+fn to_json<T: Serialize + ?Sized>(value: &T) -> AppResult<String> {
+    serde_json::to_string(value).map_err(|e| AppError::Storage(format!("Could not serialize day log: {}", e)))
+}
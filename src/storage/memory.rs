@@ -0,0 +1,61 @@
+use std::collections::BTreeMap;
+use std::sync::{Mutex, MutexGuard};
+
+use chrono::NaiveDate;
+
+use crate::{
+    error::{AppError, AppResult},
+    models::{DayLog, TrashedNote},
+};
+use super::Storage;
+
+/// Days kept in memory only, gone when it's dropped: a scratch store, and the reference the
+/// other stores are tested against
+#[derive(Default)]
+pub struct MemoryStorage {
+    days: Mutex<BTreeMap<NaiveDate, DayLog>>,
+    trash: Mutex<Vec<TrashedNote>>,
+}
+
+impl MemoryStorage {
+    fn days(&self) -> AppResult<MutexGuard<'_, BTreeMap<NaiveDate, DayLog>>> {
+        self.days.lock().map_err(|_| AppError::Storage("Memory store lock poisoned".to_string()))
+    }
+
+    fn trash(&self) -> AppResult<MutexGuard<'_, Vec<TrashedNote>>> {
+        self.trash.lock().map_err(|_| AppError::Storage("Memory store lock poisoned".to_string()))
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn load_day(&self, date: NaiveDate) -> AppResult<DayLog> {
+        Ok(self.days()?.get(&date).cloned().unwrap_or_else(|| DayLog::new(date)))
+    }
+
+    fn save_day(&self, day: &DayLog) -> AppResult<()> {
+        self.days()?.insert(day.date, day.clone());
+        Ok(())
+    }
+
+    fn list_dates(&self) -> AppResult<Vec<NaiveDate>> {
+        Ok(self.days()?.keys().copied().collect())
+    }
+
+    fn delete_day(&self, date: NaiveDate) -> AppResult<()> {
+        self.days()?.remove(&date);
+        Ok(())
+    }
+
+    fn exists(&self, date: NaiveDate) -> AppResult<bool> {
+        Ok(self.days()?.contains_key(&date))
+    }
+
+    fn load_trash(&self) -> AppResult<Vec<TrashedNote>> {
+        Ok(self.trash()?.clone())
+    }
+
+    fn save_trash(&self, trash: &[TrashedNote]) -> AppResult<()> {
+        *self.trash()? = trash.to_vec();
+        Ok(())
+    }
+}
@@ -1,10 +1,221 @@
-use crate::{error::AppResult, models::DayLog};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{
+    config::StorageFormat,
+    dates::DateRange,
+    error::{AppError, AppResult},
+    models::{DayLog, Editor, TrashedNote},
+};
 use chrono::NaiveDate;
 
+#[cfg(feature = "encryption")]
+pub mod encrypted;
 pub mod fs;
+pub mod git;
+pub mod markdown;
+pub mod memory;
+pub mod recurring;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+/// Present in the data dir when its days are encrypted
+pub const KEY_FILE: &str = "key.json";
+
+/// Where a store of `format` keeps its days unless told otherwise: the data dir itself, or
+/// a database file in it
+pub fn default_location(format: StorageFormat, data_dir: &Path) -> PathBuf {
+    match format {
+        StorageFormat::Json | StorageFormat::Markdown => data_dir.to_path_buf(),
+        StorageFormat::Sqlite => data_dir.join("aigenda.db"),
+    }
+}
+
+/// The plain days store of `format` at `path` (a folder, or the database file)
+pub fn open_format(format: StorageFormat, path: &Path) -> AppResult<Box<dyn Storage>> {
+    match format {
+        StorageFormat::Json => Ok(Box::new(fs::FsStorage::with_root(path.to_path_buf())?)),
+        StorageFormat::Markdown => Ok(Box::new(markdown::MarkdownStorage::with_root(path.to_path_buf())?)),
+        #[cfg(feature = "sqlite")]
+        StorageFormat::Sqlite => Ok(Box::new(sqlite::SqliteStorage::open(path)?)),
+        #[cfg(not(feature = "sqlite"))]
+        StorageFormat::Sqlite => Err(AppError::Config(
+            "SQLite storage requires the sqlite feature. Rebuild with: cargo build --features sqlite".to_string(),
+        )),
+    }
+}
 
+/// Writes `path` through a temporary file renamed over it, so readers (and other writers)
+/// see either the old contents or the new ones, never a partial file
+pub fn write_file(path: &Path, bytes: &[u8]) -> AppResult<()> {
+    static WRITES: AtomicUsize = AtomicUsize::new(0);
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let tmp = path.with_file_name(format!(
+        ".{}.{}-{}.tmp",
+        name,
+        std::process::id(),
+        WRITES.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let written = std::fs::write(&tmp, bytes).and_then(|()| std::fs::rename(&tmp, path));
+    if written.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    written.map_err(|e| AppError::Storage(format!("Could not write to {}: {}", path.display(), e)))
+}
+
+/// Stored days, loaded one at a time as the iterator advances; `.rev()` walks
+/// from the newest day back
+pub type DayIter<'a> = Box<dyn DoubleEndedIterator<Item = AppResult<DayLog>> + Send + 'a>;
+
+#[allow(dead_code)] // the bin doesn't use every method yet
 pub trait Storage: Send + Sync {
     fn load_day(&self, date: NaiveDate) -> AppResult<DayLog>;
     fn save_day(&self, day: &DayLog) -> AppResult<()>;
-    fn iter_days(&self) -> AppResult<Vec<DayLog>>;
+
+    /// Dates that have a stored day, oldest first, without reading their notes
+    fn list_dates(&self) -> AppResult<Vec<NaiveDate>>;
+
+    /// Removes the stored day; deleting a day that isn't stored is not an error
+    fn delete_day(&self, date: NaiveDate) -> AppResult<()>;
+
+    fn exists(&self, date: NaiveDate) -> AppResult<bool> {
+        Ok(self.list_dates()?.binary_search(&date).is_ok())
+    }
+
+    /// Every stored day, lazily, oldest first
+    fn days(&self) -> AppResult<DayIter<'_>> {
+        let dates = self.list_dates()?;
+        Ok(Box::new(dates.into_iter().map(move |date| self.load_day(date))))
+    }
+
+    /// Stored days within `range`, lazily, oldest first
+    fn days_between(&self, range: DateRange) -> AppResult<DayIter<'_>> {
+        let dates: Vec<NaiveDate> = self
+            .list_dates()?
+            .into_iter()
+            .filter(|date| range.start <= *date && *date <= range.end)
+            .collect();
+        Ok(Box::new(dates.into_iter().map(move |date| self.load_day(date))))
+    }
+
+    /// Deleted notes, in the order they were deleted. Stores without a trash have none.
+    fn load_trash(&self) -> AppResult<Vec<TrashedNote>> {
+        Ok(Vec::new())
+    }
+
+    fn save_trash(&self, _trash: &[TrashedNote]) -> AppResult<()> {
+        Err(AppError::Storage("This store has no trash".to_string()))
+    }
+
+    /// Stored documents written with an older schema: days, or `None` for the trash, with the
+    /// version they have. Stores without versioned documents have none.
+    fn outdated(&self) -> AppResult<Vec<(Option<NaiveDate>, u32)>> {
+        Ok(Vec::new())
+    }
+
+    /// Marks the end of a command or agent run. Stores that keep history record the changes
+    /// made since the last checkpoint as one entry; others have nothing to do.
+    fn checkpoint(&self, _editor: Editor) -> AppResult<()> {
+        Ok(())
+    }
+
+    /// Every stored day, loaded eagerly
+    fn iter_days(&self) -> AppResult<Vec<DayLog>> {
+        self.days()?.collect()
+    }
+}
+
+/// Lets the store chain start from either backend
+impl<T: Storage + ?Sized> Storage for Box<T> {
+    fn load_day(&self, date: NaiveDate) -> AppResult<DayLog> {
+        (**self).load_day(date)
+    }
+
+    fn save_day(&self, day: &DayLog) -> AppResult<()> {
+        (**self).save_day(day)
+    }
+
+    fn list_dates(&self) -> AppResult<Vec<NaiveDate>> {
+        (**self).list_dates()
+    }
+
+    fn delete_day(&self, date: NaiveDate) -> AppResult<()> {
+        (**self).delete_day(date)
+    }
+
+    fn exists(&self, date: NaiveDate) -> AppResult<bool> {
+        (**self).exists(date)
+    }
+
+    fn days(&self) -> AppResult<DayIter<'_>> {
+        (**self).days()
+    }
+
+    fn days_between(&self, range: DateRange) -> AppResult<DayIter<'_>> {
+        (**self).days_between(range)
+    }
+
+    fn load_trash(&self) -> AppResult<Vec<TrashedNote>> {
+        (**self).load_trash()
+    }
+
+    fn save_trash(&self, trash: &[TrashedNote]) -> AppResult<()> {
+        (**self).save_trash(trash)
+    }
+
+    fn outdated(&self) -> AppResult<Vec<(Option<NaiveDate>, u32)>> {
+        (**self).outdated()
+    }
+
+    fn checkpoint(&self, editor: Editor) -> AppResult<()> {
+        (**self).checkpoint(editor)
+    }
+}
+
+/// Lets the App share its store with agent tools
+impl<T: Storage + ?Sized> Storage for std::sync::Arc<T> {
+    fn load_day(&self, date: NaiveDate) -> AppResult<DayLog> {
+        (**self).load_day(date)
+    }
+
+    fn save_day(&self, day: &DayLog) -> AppResult<()> {
+        (**self).save_day(day)
+    }
+
+    fn list_dates(&self) -> AppResult<Vec<NaiveDate>> {
+        (**self).list_dates()
+    }
+
+    fn delete_day(&self, date: NaiveDate) -> AppResult<()> {
+        (**self).delete_day(date)
+    }
+
+    fn exists(&self, date: NaiveDate) -> AppResult<bool> {
+        (**self).exists(date)
+    }
+
+    fn days(&self) -> AppResult<DayIter<'_>> {
+        (**self).days()
+    }
+
+    fn days_between(&self, range: DateRange) -> AppResult<DayIter<'_>> {
+        (**self).days_between(range)
+    }
+
+    fn load_trash(&self) -> AppResult<Vec<TrashedNote>> {
+        (**self).load_trash()
+    }
+
+    fn save_trash(&self, trash: &[TrashedNote]) -> AppResult<()> {
+        (**self).save_trash(trash)
+    }
+
+    fn outdated(&self) -> AppResult<Vec<(Option<NaiveDate>, u32)>> {
+        (**self).outdated()
+    }
+
+    fn checkpoint(&self, editor: Editor) -> AppResult<()> {
+        (**self).checkpoint(editor)
+    }
 }
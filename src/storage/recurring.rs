@@ -0,0 +1,78 @@
+use chrono::NaiveDate;
+
+use crate::{
+    clock::clock,
+    config::Config,
+    error::AppResult,
+    models::{DayLog, Editor, TrashedNote},
+    recurrence::{materialize, Recurrence},
+    templates::Templates,
+};
+use super::Storage;
+
+/// Adds the configured recurring notes and tasks to today's log the first time it's loaded.
+/// Other days are left alone, so browsing history doesn't backfill it.
+pub struct RecurringStorage<S: Storage> {
+    inner: S,
+    rules: Vec<Recurrence>,
+    templates: Templates,
+}
+
+impl<S: Storage> RecurringStorage<S> {
+    pub fn new(inner: S, rules: Vec<Recurrence>, templates: Templates) -> Self {
+        Self { inner, rules, templates }
+    }
+
+    /// Wraps `inner` with the rules from the user config
+    pub fn from_config(inner: S, config: &Config) -> AppResult<Self> {
+        Ok(Self::new(inner, config.recurring.clone(), Templates::open_default()?))
+    }
+}
+
+impl<S: Storage> Storage for RecurringStorage<S> {
+    fn load_day(&self, date: NaiveDate) -> AppResult<DayLog> {
+        let mut day = self.inner.load_day(date)?;
+        if date == clock().today() && materialize(&mut day, &self.rules, &self.templates) {
+            self.inner.save_day(&day)?;
+        }
+        Ok(day)
+    }
+
+    fn save_day(&self, day: &DayLog) -> AppResult<()> {
+        self.inner.save_day(day)
+    }
+
+    fn list_dates(&self) -> AppResult<Vec<NaiveDate>> {
+        self.inner.list_dates()
+    }
+
+    /// Keeps an emptied day that recurring rules were applied to, so they aren't applied again
+    fn delete_day(&self, date: NaiveDate) -> AppResult<()> {
+        let day = self.inner.load_day(date)?;
+        if day.recurring.is_empty() {
+            return self.inner.delete_day(date);
+        }
+        self.inner.save_day(&DayLog { notes: Vec::new(), ..day })
+    }
+
+    fn exists(&self, date: NaiveDate) -> AppResult<bool> {
+        self.inner.exists(date)
+    }
+
+    fn load_trash(&self) -> AppResult<Vec<TrashedNote>> {
+        self.inner.load_trash()
+    }
+
+    fn save_trash(&self, trash: &[TrashedNote]) -> AppResult<()> {
+        self.inner.save_trash(trash)
+    }
+
+    fn outdated(&self) -> AppResult<Vec<(Option<NaiveDate>, u32)>> {
+        self.inner.outdated()
+    }
+
+    fn checkpoint(&self, editor: Editor) -> AppResult<()> {
+        self.inner.checkpoint(editor)
+    }
+
+}
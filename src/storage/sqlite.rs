@@ -0,0 +1,162 @@
+use std::fs;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+use chrono::NaiveDate;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::{
+    error::{AppError, AppResult},
+    models::{DayLog, TrashedNote},
+    schema::{self, SCHEMA_VERSION},
+};
+use super::Storage;
+
+/// Days in a single SQLite database, one row per day holding its JSON
+pub struct SqliteStorage {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStorage {
+    /// Opens the database at `path`, creating it (and its folder) if needed
+    pub fn open(path: &Path) -> AppResult<Self> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)
+                .map_err(|e| AppError::Config(format!("Could not create data directory {}: {}", dir.display(), e)))?;
+        }
+        let conn = Connection::open(path)
+            .map_err(|e| AppError::Storage(format!("Could not open database {}: {}", path.display(), e)))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS days (date TEXT PRIMARY KEY, log TEXT NOT NULL);
+             CREATE TABLE IF NOT EXISTS trash (id INTEGER PRIMARY KEY CHECK (id = 1), notes TEXT NOT NULL);",
+        )
+        .map_err(db_error)?;
+
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    fn conn(&self) -> AppResult<MutexGuard<'_, Connection>> {
+        self.conn.lock().map_err(|_| AppError::Storage("Database lock poisoned".to_string()))
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn load_day(&self, date: NaiveDate) -> AppResult<DayLog> {
+        let key = date.format("%Y-%m-%d").to_string();
+        let log: Option<String> = self
+            .conn()?
+            .query_row("SELECT log FROM days WHERE date = ?1", params![key], |row| row.get(0))
+            .optional()
+            .map_err(db_error)?;
+
+        match log {
+            Some(log) => schema::parse_day(&log).map_err(|e| schema::located(e, format!("The day {} in the database", key))),
+            None => Ok(DayLog::new(date)),
+        }
+    }
+
+    fn save_day(&self, day: &DayLog) -> AppResult<()> {
+        let log = schema::day_to_json(day, false)?;
+        self.conn()?
+            .execute(
+                "INSERT INTO days (date, log) VALUES (?1, ?2) ON CONFLICT (date) DO UPDATE SET log = excluded.log",
+                params![day.date.format("%Y-%m-%d").to_string(), log],
+            )
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    fn list_dates(&self) -> AppResult<Vec<NaiveDate>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare("SELECT date FROM days ORDER BY date").map_err(db_error)?;
+        let keys = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(db_error)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(db_error)?;
+
+        keys.iter()
+            .map(|key| {
+                NaiveDate::parse_from_str(key, "%Y-%m-%d")
+                    .map_err(|_| AppError::Corrupt(format!("Invalid date '{}' in the database", key)))
+            })
+            .collect()
+    }
+
+    fn delete_day(&self, date: NaiveDate) -> AppResult<()> {
+        self.conn()?
+            .execute("DELETE FROM days WHERE date = ?1", params![date.format("%Y-%m-%d").to_string()])
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    fn exists(&self, date: NaiveDate) -> AppResult<bool> {
+        self.conn()?
+            .query_row(
+                "SELECT EXISTS (SELECT 1 FROM days WHERE date = ?1)",
+                params![date.format("%Y-%m-%d").to_string()],
+                |row| row.get(0),
+            )
+            .map_err(db_error)
+    }
+
+    fn load_trash(&self) -> AppResult<Vec<TrashedNote>> {
+        let notes: Option<String> = self
+            .conn()?
+            .query_row("SELECT notes FROM trash WHERE id = 1", [], |row| row.get(0))
+            .optional()
+            .map_err(db_error)?;
+
+        match notes {
+            Some(notes) => schema::parse_trash(&notes).map_err(|e| schema::located(e, "The trash in the database")),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn save_trash(&self, trash: &[TrashedNote]) -> AppResult<()> {
+        let conn = self.conn()?;
+        if trash.is_empty() {
+            conn.execute("DELETE FROM trash", []).map_err(db_error)?;
+            return Ok(());
+        }
+
+        let notes = schema::trash_to_json(trash, false)?;
+        conn.execute(
+            "INSERT INTO trash (id, notes) VALUES (1, ?1) ON CONFLICT (id) DO UPDATE SET notes = excluded.notes",
+            params![notes],
+        )
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    fn outdated(&self) -> AppResult<Vec<(Option<NaiveDate>, u32)>> {
+        let conn = self.conn()?;
+        let mut stmt = conn
+            .prepare("SELECT date, log FROM days UNION ALL SELECT NULL, notes FROM trash")
+            .map_err(db_error)?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, Option<String>>(0)?, row.get::<_, String>(1)?)))
+            .map_err(db_error)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(db_error)?;
+
+        let mut outdated = Vec::new();
+        for (key, json) in rows {
+            let date = key
+                .map(|key| {
+                    NaiveDate::parse_from_str(&key, "%Y-%m-%d")
+                        .map_err(|_| AppError::Corrupt(format!("Invalid date '{}' in the database", key)))
+                })
+                .transpose()?;
+            let version = schema::version_of_json(&json)?;
+            if version < SCHEMA_VERSION {
+                outdated.push((date, version));
+            }
+        }
+        Ok(outdated)
+    }
+}
+
+fn db_error(e: rusqlite::Error) -> AppError {
+    AppError::Storage(format!("Database error: {}", e))
+}
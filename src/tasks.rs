@@ -0,0 +1,146 @@
+//! To-dos are notes carrying a `Task`. These operations are shared by `aigenda todo`
+//! and the notes tool, so both treat tasks the same way.
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::{
+    clock::clock,
+    error::{AppError, AppResult},
+    models::{Note, NoteRef, Priority, TaskStatus},
+    storage::Storage,
+};
+
+/// A task together with where it lives
+#[derive(Serialize, Clone, Debug)]
+pub struct TaskEntry {
+    pub id: String,
+    #[serde(skip)]
+    pub note_ref: NoteRef,
+    #[serde(flatten)]
+    pub note: Note,
+}
+
+impl TaskEntry {
+    fn new(note_ref: NoteRef, note: Note) -> Self {
+        Self { id: note_ref.to_string(), note_ref, note }
+    }
+
+    /// `2025-01-15#2 [ ] !high (due 2025-01-20) text`
+    pub fn line(&self) -> String {
+        let label = self.note.task.as_ref().map(|t| t.label()).unwrap_or_default();
+        format!("{} {} {}", self.id, label, self.note.text)
+    }
+}
+
+/// Adds an open task to `date`
+pub fn add_task<S: Storage + ?Sized>(
+    store: &S,
+    date: NaiveDate,
+    text: &str,
+    due: Option<NaiveDate>,
+    priority: Option<Priority>,
+) -> AppResult<TaskEntry> {
+    if text.trim().is_empty() {
+        return Err(AppError::InvalidParameters("A task needs some text".to_string()));
+    }
+
+    let note = Note::task(text.trim().to_string(), due, priority);
+    let mut day = store.load_day(date)?;
+    day.notes.push(note.clone());
+    store.save_day(&day)?;
+
+    Ok(TaskEntry::new(NoteRef::new(date, day.notes.len()), note))
+}
+
+/// Every task, oldest first; closed ones only with `include_closed`
+pub fn list_tasks<S: Storage + ?Sized>(store: &S, include_closed: bool) -> AppResult<Vec<TaskEntry>> {
+    let mut tasks = Vec::new();
+    for day in store.days()? {
+        let day = day?;
+        for (i, note) in day.notes.into_iter().enumerate() {
+            if note.task.is_some() && (include_closed || note.is_open_task()) {
+                tasks.push(TaskEntry::new(NoteRef::new(day.date, i + 1), note));
+            }
+        }
+    }
+    Ok(tasks)
+}
+
+/// Open tasks from days before `today`, most urgent first: these show up in today's view
+pub fn carried_over<S: Storage + ?Sized>(store: &S, today: NaiveDate) -> AppResult<Vec<TaskEntry>> {
+    let mut tasks: Vec<TaskEntry> = list_tasks(store, false)?
+        .into_iter()
+        .filter(|t| t.note_ref.date < today)
+        .collect();
+    sort_by_urgency(&mut tasks);
+    Ok(tasks)
+}
+
+/// Overdue and soonest due first, then by priority, then oldest
+pub fn sort_by_urgency(tasks: &mut [TaskEntry]) {
+    tasks.sort_by(|a, b| {
+        let (ta, tb) = (a.note.task.as_ref(), b.note.task.as_ref());
+        let due = |t: Option<&crate::models::Task>| t.and_then(|t| t.due).unwrap_or(NaiveDate::MAX);
+        let priority = |t: Option<&crate::models::Task>| t.and_then(|t| t.priority);
+
+        due(ta)
+            .cmp(&due(tb))
+            .then(priority(tb).cmp(&priority(ta)))
+            .then(a.note_ref.date.cmp(&b.note_ref.date))
+            .then(a.note_ref.index.cmp(&b.note_ref.index))
+    });
+}
+
+/// A `YYYY-MM-DD#N` id, or text matching exactly one open task (case-insensitive)
+pub fn resolve_task<S: Storage + ?Sized>(store: &S, query: &str) -> AppResult<NoteRef> {
+    if let Ok(note_ref) = query.trim().parse::<NoteRef>() {
+        return Ok(note_ref);
+    }
+
+    let needle = query.trim().to_lowercase();
+    if needle.is_empty() {
+        return Err(AppError::InvalidParameters("Give a task id (YYYY-MM-DD#N) or some of its text".to_string()));
+    }
+
+    let matches: Vec<TaskEntry> = list_tasks(store, false)?
+        .into_iter()
+        .filter(|t| t.note.text.to_lowercase().contains(&needle))
+        .collect();
+
+    match matches.as_slice() {
+        [] => Err(AppError::NotFound(format!("No open task matches '{}'", query.trim()))),
+        [task] => Ok(task.note_ref),
+        many => Err(AppError::Conflict(format!(
+            "'{}' matches {} open tasks: {}",
+            query.trim(),
+            many.len(),
+            many.iter().map(|t| t.id.as_str()).collect::<Vec<_>>().join(", ")
+        ))),
+    }
+}
+
+/// Marks a task open, done or cancelled
+pub fn set_status<S: Storage + ?Sized>(store: &S, note_ref: NoteRef, status: TaskStatus) -> AppResult<TaskEntry> {
+    let mut day = store.load_day(note_ref.date)?;
+    let note = note_ref
+        .index
+        .checked_sub(1)
+        .and_then(|i| day.notes.get_mut(i))
+        .ok_or_else(|| AppError::NotFound(format!("Note {}", note_ref)))?;
+
+    let task = note
+        .task
+        .as_mut()
+        .ok_or_else(|| AppError::InvalidParameters(format!("Note {} is not a task", note_ref)))?;
+
+    task.status = status;
+    task.closed_at = match status {
+        TaskStatus::Open => None,
+        TaskStatus::Done | TaskStatus::Cancelled => Some(clock().now()),
+    };
+
+    let note = note.clone();
+    store.save_day(&day)?;
+    Ok(TaskEntry::new(note_ref, note))
+}
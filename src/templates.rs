@@ -0,0 +1,130 @@
+//! Note templates: text files in the `templates/` config folder (`standup.md` is applied as
+//! `standup`). Each non-blank line becomes a note; lines starting with `[ ]` (or `- [ ]`)
+//! become open tasks. `{{date}}` and `{{weekday}}` are filled in for the day it's applied to.
+
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::NaiveDate;
+
+use crate::{
+    config::Config,
+    error::{AppError, AppResult},
+    models::{Note, NoteRef},
+    storage::Storage,
+};
+
+const EXTENSION: &str = "md";
+
+pub struct Templates {
+    dir: PathBuf,
+}
+
+impl Templates {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// The `templates/` folder next to `config.json`
+    pub fn open_default() -> AppResult<Self> {
+        Ok(Self::new(Config::config_dir()?.join("templates")))
+    }
+
+    /// Template names, sorted
+    pub fn names(&self) -> AppResult<Vec<String>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let entries = fs::read_dir(&self.dir)
+            .map_err(|e| AppError::Storage(format!("Could not read templates directory {}: {}", self.dir.display(), e)))?;
+
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == EXTENSION))
+            .filter_map(|path| path.file_stem().and_then(|s| s.to_str()).map(str::to_string))
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    pub fn load(&self, name: &str) -> AppResult<Template> {
+        if name.is_empty() || name.contains(['/', '\\']) {
+            return Err(AppError::InvalidParameters(format!("Invalid template name '{}'", name)));
+        }
+
+        let path = self.dir.join(format!("{}.{}", name, EXTENSION));
+        if !path.exists() {
+            return Err(AppError::NotFound(format!("Template '{}' ({})", name, path.display())));
+        }
+
+        let body = fs::read_to_string(&path)
+            .map_err(|e| AppError::Storage(format!("Could not read template {}: {}", path.display(), e)))?;
+        Ok(Template { name: name.to_string(), body })
+    }
+}
+
+pub struct Template {
+    pub name: String,
+    pub body: String,
+}
+
+impl Template {
+    /// The notes this template adds to `date`
+    pub fn render(&self, date: NaiveDate) -> AppResult<Vec<Note>> {
+        let mut notes = Vec::new();
+        for line in self.body.lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let item = line.strip_prefix("- ").unwrap_or(line);
+            let note = match item.strip_prefix("[ ]") {
+                Some(task) => Note::task(fill(task.trim(), date)?, None, None),
+                None => Note::new(fill(item, date)?),
+            };
+            notes.push(note);
+        }
+        Ok(notes)
+    }
+}
+
+/// Replaces `{{date}}` (YYYY-MM-DD) and `{{weekday}}` (Monday); any other placeholder is an error
+pub fn fill(text: &str, date: NaiveDate) -> AppResult<String> {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find("}}")
+            .ok_or_else(|| AppError::InvalidParameters(format!("Unclosed placeholder in '{}'", text)))?;
+
+        match rest[start + 2..start + end].trim() {
+            "date" => out.push_str(&date.format("%Y-%m-%d").to_string()),
+            "weekday" => out.push_str(&date.format("%A").to_string()),
+            other => {
+                return Err(AppError::InvalidParameters(format!(
+                    "Unknown placeholder '{{{{{}}}}}': use {{{{date}}}} or {{{{weekday}}}}", other
+                )))
+            }
+        }
+        rest = &rest[start + end + 2..];
+    }
+
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Adds the template's notes to `date`, returning the ids of the new notes
+pub fn apply<S: Storage + ?Sized>(store: &S, template: &Template, date: NaiveDate) -> AppResult<Vec<NoteRef>> {
+    let notes = template.render(date)?;
+    let mut day = store.load_day(date)?;
+    let first = day.notes.len() + 1;
+    day.notes.extend(notes);
+    store.save_day(&day)?;
+
+    Ok((first..=day.notes.len()).map(|i| NoteRef::new(date, i)).collect())
+}
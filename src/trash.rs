@@ -0,0 +1,109 @@
+//! Deleted notes go to the trash first. Trash ids are 1-based positions in the trash,
+//! oldest deletion first, as shown by `aigenda trash list`.
+
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate};
+
+use crate::{
+    clock::clock,
+    error::{AppError, AppResult},
+    models::{Note, NoteRef, TrashedNote},
+    storage::Storage,
+};
+
+/// Days a deleted note is kept when `trash_retention_days` isn't configured
+pub const DEFAULT_RETENTION_DAYS: u32 = 30;
+
+/// Puts a note deleted from `position` on `date` into the trash
+pub fn discard<S: Storage + ?Sized>(store: &S, date: NaiveDate, position: usize, note: Note) -> AppResult<()> {
+    let mut trash = store.load_trash()?;
+    trash.push(TrashedNote {
+        date,
+        position,
+        deleted_at: clock().now(),
+        note,
+    });
+    store.save_trash(&trash)
+}
+
+/// Puts trashed notes back on their day, at their old position when it still exists.
+/// Returns where each one ended up, in trash order.
+pub fn restore<S: Storage + ?Sized>(store: &S, ids: &[usize]) -> AppResult<Vec<NoteRef>> {
+    let mut trash = store.load_trash()?;
+    let mut positions = positions(&trash, ids)?;
+    positions.sort_unstable();
+
+    // Undo the deletions newest first, so each position means what it did when it was deleted
+    let mut restored: Vec<NoteRef> = Vec::new();
+    for &i in positions.iter().rev() {
+        let entry = &trash[i];
+        let mut day = store.load_day(entry.date)?;
+        let index = entry.position.clamp(1, day.notes.len() + 1);
+        day.notes.insert(index - 1, entry.note.clone());
+        store.save_day(&day)?;
+
+        for earlier in restored.iter_mut().filter(|r| r.date == entry.date && r.index >= index) {
+            earlier.index += 1;
+        }
+        restored.push(NoteRef::new(entry.date, index));
+    }
+    restored.reverse();
+
+    remove_positions(&mut trash, positions);
+    store.save_trash(&trash)?;
+    Ok(restored)
+}
+
+/// Permanently deletes the given trashed notes, or all of them; returns how many
+pub fn purge<S: Storage + ?Sized>(store: &S, ids: Option<&[usize]>) -> AppResult<usize> {
+    let mut trash = store.load_trash()?;
+    let before = trash.len();
+
+    match ids {
+        Some(ids) => {
+            let positions = positions(&trash, ids)?;
+            remove_positions(&mut trash, positions);
+        }
+        None => trash.clear(),
+    }
+
+    store.save_trash(&trash)?;
+    Ok(before - trash.len())
+}
+
+/// Purges notes deleted more than `retention_days` before `now`; 0 keeps them forever
+pub fn purge_expired<S: Storage + ?Sized>(store: &S, retention_days: u32, now: DateTime<FixedOffset>) -> AppResult<usize> {
+    if retention_days == 0 {
+        return Ok(0);
+    }
+
+    let mut trash = store.load_trash()?;
+    let before = trash.len();
+    let cutoff = now - Duration::days(retention_days as i64);
+    trash.retain(|entry| entry.deleted_at > cutoff);
+
+    if trash.len() != before {
+        store.save_trash(&trash)?;
+    }
+    Ok(before - trash.len())
+}
+
+fn positions(trash: &[TrashedNote], ids: &[usize]) -> AppResult<Vec<usize>> {
+    let mut positions = Vec::new();
+    for &id in ids {
+        let i = id
+            .checked_sub(1)
+            .filter(|i| *i < trash.len())
+            .ok_or_else(|| AppError::NotFound(format!("Trash item {} (the trash has {})", id, trash.len())))?;
+        if !positions.contains(&i) {
+            positions.push(i);
+        }
+    }
+    Ok(positions)
+}
+
+fn remove_positions(trash: &mut Vec<TrashedNote>, mut positions: Vec<usize>) {
+    positions.sort_unstable_by(|a, b| b.cmp(a));
+    for i in positions {
+        trash.remove(i);
+    }
+}
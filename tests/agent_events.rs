@@ -0,0 +1,66 @@
+#![cfg(feature = "ai")]
+
+use aigenda::agent::streaming::{AgentEvent, NdjsonStreamingHandler, StreamingHandler};
+use serde_json::{json, Value};
+use std::io::Cursor;
+
+fn lines(out: &[u8]) -> Vec<Value> {
+    String::from_utf8_lossy(out)
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect()
+}
+
+#[test]
+fn emits_each_callback_as_a_typed_line() {
+    let mut out = Vec::new();
+    let mut handler = NdjsonStreamingHandler::new(&mut out, Cursor::new(Vec::new()));
+
+    handler.on_iteration_start(1).unwrap();
+    handler.on_llm_response("reading your notes").unwrap();
+    handler.on_tool_about_to_execute("notes", "read", &json!({ "date": "2025-01-15" })).unwrap();
+    handler.on_tool_executed("notes", "read", "Notes for 2025-01-15", true).unwrap();
+    handler.on_iteration_end(1, "done").unwrap();
+    handler.emit(&AgentEvent::Done { response: "done".to_string() }).unwrap();
+    drop(handler);
+
+    let events = lines(&out);
+    let types: Vec<&str> = events.iter().map(|e| e["type"].as_str().unwrap()).collect();
+    assert_eq!(types, ["iteration_start", "llm_response", "tool_call", "tool_result", "iteration_end", "done"]);
+    assert_eq!(events[2]["parameters"]["date"], "2025-01-15");
+    assert_eq!(events[3]["success"], true);
+
+    let parsed: AgentEvent = serde_json::from_value(events[1].clone()).unwrap();
+    assert_eq!(parsed, AgentEvent::LlmResponse { text: "reading your notes".to_string() });
+}
+
+#[test]
+fn asks_permission_over_stdin() {
+    let input = "\n{\"type\":\"permission_response\",\"id\":1,\"allow\":true}\n\
+                 {\"type\":\"permission_response\",\"id\":2,\"allow\":false}\n";
+    let mut out = Vec::new();
+    let mut handler = NdjsonStreamingHandler::new(&mut out, Cursor::new(input.as_bytes().to_vec()));
+
+    assert!(handler.request_tool_permission("notes", "delete", &json!({})).unwrap());
+    assert!(!handler.request_tool_permission("notes", "delete", &json!({})).unwrap());
+    // End of input denies
+    assert!(!handler.request_tool_permission("notes", "delete", &json!({})).unwrap());
+    drop(handler);
+
+    let events = lines(&out);
+    assert_eq!(events[0]["type"], "permission_request");
+    assert_eq!(events[0]["id"], 1);
+    assert_eq!(events[1], json!({ "type": "permission_resolved", "id": 1, "allowed": true }));
+    assert_eq!(events[3], json!({ "type": "permission_resolved", "id": 2, "allowed": false }));
+    assert_eq!(events[5], json!({ "type": "permission_resolved", "id": 3, "allowed": false }));
+}
+
+#[test]
+fn rejects_a_response_for_another_request() {
+    let input = "{\"type\":\"permission_response\",\"id\":7,\"allow\":true}\n";
+    let mut out = Vec::new();
+    let mut handler = NdjsonStreamingHandler::new(&mut out, Cursor::new(input.as_bytes().to_vec()));
+
+    let error = handler.request_tool_permission("notes", "delete", &json!({})).unwrap_err();
+    assert_eq!(error.kind(), "invalid_parameters");
+}
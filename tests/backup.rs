@@ -0,0 +1,161 @@
+use aigenda::{
+    backup::{self, Archive, Root},
+    error::AppError,
+    models::{DayLog, Note},
+    storage::{fs::FsStorage, Storage},
+};
+use assert_cmd::Command;
+use chrono::{DateTime, Duration, NaiveDate};
+use std::fs;
+use std::path::Path;
+
+fn d(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 1, day).unwrap()
+}
+
+fn note(at: &str, text: &str) -> Note {
+    Note { when: DateTime::parse_from_rfc3339(at).unwrap(), ..Note::new(text.to_string()) }
+}
+
+fn now() -> DateTime<chrono::FixedOffset> {
+    DateTime::parse_from_rfc3339("2025-01-20T12:00:00+01:00").unwrap()
+}
+
+/// A data dir with one day, and a config dir
+fn setup(root: &Path) -> Vec<Root> {
+    let store = FsStorage::with_root(root.join("data")).unwrap();
+    let mut day = DayLog::new(d(15));
+    day.notes.push(note("2025-01-15T09:00:00+01:00", "first"));
+    store.save_day(&day).unwrap();
+    fs::create_dir_all(root.join("config/templates")).unwrap();
+    fs::write(root.join("config/config.json"), "{}").unwrap();
+    fs::write(root.join("config/templates/standup.md"), "- yesterday\n").unwrap();
+    vec![Root::new(backup::DATA, root.join("data")), Root::new("config", root.join("config"))]
+}
+
+#[test]
+fn backups_are_checked_against_their_manifest() {
+    let dir = tempfile::tempdir().unwrap();
+    let roots = setup(dir.path());
+    let out = dir.path().join("backup.tar.gz");
+    let manifest = backup::create(&roots, &out, now()).unwrap();
+    let names: Vec<&str> = manifest.files.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(names, ["data/2025-01-15.json", "config/config.json", "config/templates/standup.md"]);
+    assert_eq!(Archive::open(&out).unwrap().manifest.files, manifest.files);
+
+    // The same manifest over different contents
+    let tampered = dir.path().join("tampered.tar.gz");
+    let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+        fs::File::create(&tampered).unwrap(),
+        flate2::Compression::default(),
+    ));
+    for (name, bytes) in [("manifest.json", serde_json::to_vec(&manifest).unwrap()), ("data/2025-01-15.json", b"{}".to_vec())] {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(bytes.len() as u64);
+        header.set_mode(0o600);
+        builder.append_data(&mut header, name, bytes.as_slice()).unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap();
+    assert!(matches!(Archive::open(&tampered), Err(AppError::Corrupt(_))));
+}
+
+#[test]
+fn replacing_restores_the_archived_files_exactly() {
+    let dir = tempfile::tempdir().unwrap();
+    let roots = setup(dir.path());
+    let out = dir.path().join("backup.tar.gz");
+    backup::create(&roots, &out, now()).unwrap();
+
+    fs::write(dir.path().join("data/2025-01-15.json"), "changed").unwrap();
+    fs::write(dir.path().join("data/2025-01-16.json"), "new").unwrap();
+    fs::remove_file(dir.path().join("config/templates/standup.md")).unwrap();
+    let archive = Archive::open(&out).unwrap();
+
+    let planned = backup::replace(&archive, &roots, true).unwrap();
+    assert_eq!((planned.files, planned.removed), (2, 1));
+    assert!(dir.path().join("data/2025-01-16.json").exists());
+
+    assert_eq!(backup::replace(&archive, &roots, false).unwrap(), planned);
+    assert!(!dir.path().join("data/2025-01-16.json").exists());
+    assert_eq!(fs::read_to_string(dir.path().join("config/templates/standup.md")).unwrap(), "- yesterday\n");
+    let store = FsStorage::with_root(dir.path().join("data")).unwrap();
+    assert_eq!(store.load_day(d(15)).unwrap().notes[0].text, "first");
+}
+
+#[test]
+fn merging_adds_only_missing_notes() {
+    let dir = tempfile::tempdir().unwrap();
+    let roots = setup(dir.path());
+    let out = dir.path().join("backup.tar.gz");
+    backup::create(&roots, &out, now()).unwrap();
+
+    // Since the backup: the first note was edited, and another added before it
+    let store = FsStorage::with_root(dir.path().join("data")).unwrap();
+    let mut day = store.load_day(d(15)).unwrap();
+    day.notes[0].text = "first, edited".to_string();
+    day.notes.insert(0, note("2025-01-15T08:00:00+01:00", "earlier"));
+    store.save_day(&day).unwrap();
+
+    let archive = Archive::open(&out).unwrap();
+    let report = backup::merge(&archive, &store, &roots, false).unwrap();
+    assert_eq!((report.days, report.notes, report.files), (0, 0, 0));
+    let texts: Vec<String> = store.load_day(d(15)).unwrap().notes.into_iter().map(|n| n.text).collect();
+    assert_eq!(texts, ["earlier", "first, edited"]);
+
+    // Into an empty store, everything comes back
+    let current = FsStorage::with_root(dir.path().join("current")).unwrap();
+    let report = backup::merge(&archive, &current, &[], false).unwrap();
+    assert_eq!((report.days, report.notes), (1, 1));
+    assert_eq!(current.load_day(d(15)).unwrap().notes[0].text, "first");
+}
+
+#[test]
+fn rotation_keeps_the_newest_per_day_and_week() {
+    let dir = tempfile::tempdir().unwrap();
+    let start = now();
+    for hours in [0, 2, 24, 48, 24 * 8, 24 * 9, 24 * 20] {
+        let name = backup::file_name(start - Duration::hours(hours));
+        fs::write(dir.path().join(name), "").unwrap();
+    }
+    fs::write(dir.path().join("notes.txt"), "").unwrap();
+
+    let deleted = backup::rotate(dir.path(), 2, 3).unwrap();
+    let mut kept: Vec<String> = fs::read_dir(dir.path())
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    kept.sort();
+    assert_eq!(
+        kept,
+        [
+            "aigenda-2025-01-12-120000.tar.gz",
+            "aigenda-2025-01-19-120000.tar.gz",
+            "aigenda-2025-01-20-120000.tar.gz",
+            "notes.txt",
+        ]
+    );
+    assert_eq!(deleted.len(), 4);
+}
+
+#[test]
+fn a_dry_run_restore_changes_nothing() {
+    let home = tempfile::tempdir().unwrap();
+    fs::write(home.path().join("config.json"), r#"{"recurring": [{"every": "daily", "text": "Standup"}]}"#).unwrap();
+    let today = chrono::Local::now().date_naive();
+    let store = FsStorage::with_root(home.path().to_path_buf()).unwrap();
+    let mut day = DayLog::new(today);
+    day.notes.push(Note::new("before the backup".to_string()));
+    store.save_day(&day).unwrap();
+    let file = home.path().join(format!("{}.json", today.format("%Y-%m-%d")));
+    let before = fs::read_to_string(&file).unwrap();
+
+    let out = tempfile::tempdir().unwrap();
+    let archive = out.path().join("backup.tar.gz");
+    let aigenda = |args: &[&str]| {
+        Command::cargo_bin("aigenda").unwrap().env("AIGENDA_HOME", home.path()).args(args).assert().success();
+    };
+    aigenda(&["backup", "--out", archive.to_str().unwrap()]);
+    aigenda(&["restore", archive.to_str().unwrap(), "--dry-run"]);
+
+    assert_eq!(fs::read_to_string(&file).unwrap(), before);
+}
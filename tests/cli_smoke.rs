@@ -1,10 +1,57 @@
 use assert_cmd::Command;
 
+/// The binary, keeping its notes and settings in `home` rather than the user's
+fn aigenda(home: &tempfile::TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("aigenda").unwrap();
+    cmd.env("AIGENDA_HOME", home.path());
+    cmd
+}
+
 #[test]
 fn runs_add_and_list() {
-    let mut cmd = Command::cargo_bin("aigenda").unwrap();
+    let home = tempfile::tempdir().unwrap();
+    let mut cmd = aigenda(&home);
     cmd.args(["add", "hello"]).assert().success();
 
-    let mut cmd = Command::cargo_bin("aigenda").unwrap();
+    let mut cmd = aigenda(&home);
     cmd.args(["list"]).assert().success();
 }
+
+#[test]
+fn reports_errors_with_exit_code_and_json() {
+    let home = tempfile::tempdir().unwrap();
+    let mut cmd = aigenda(&home);
+    let output = cmd
+        .args(["list", "--date", "not-a-date", "--error-format", "json"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2));
+    let error: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert_eq!(error["error"]["kind"], "invalid_parameters");
+    assert_eq!(error["error"]["code"], 2);
+}
+
+#[test]
+fn add_and_list_emit_json() {
+    let home = tempfile::tempdir().unwrap();
+    let mut cmd = aigenda(&home);
+    let output = cmd.args(["--output", "json", "add", "json", "note"]).output().unwrap();
+    assert!(output.status.success());
+
+    let added: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let date = added["date"].as_str().unwrap();
+    let index = added["index"].as_u64().unwrap();
+    assert_eq!(added["id"], format!("{}#{}", date, index));
+    assert_eq!(added["note"]["text"], "json note");
+
+    let mut cmd = aigenda(&home);
+    let output = cmd.args(["list", "--date", date, "--output", "ndjson"]).output().unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let days: Vec<serde_json::Value> = stdout.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+    assert_eq!(days.len(), 1);
+    assert_eq!(days[0]["date"], date);
+    assert!(days[0]["notes"].as_array().unwrap().len() >= index as usize);
+}
@@ -0,0 +1,45 @@
+use aigenda::{clock::Clock, models::Note};
+use chrono::{DateTime, NaiveDate};
+
+fn at(s: &str) -> DateTime<chrono::FixedOffset> {
+    DateTime::parse_from_rfc3339(s).unwrap()
+}
+
+fn d(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+#[test]
+fn files_notes_under_the_users_day() {
+    let clock = Clock::new(Some("America/New_York"), 0).unwrap();
+
+    // 02:00 UTC is still the previous evening in New York
+    assert_eq!(clock.day_of(at("2025-01-16T02:00:00Z")), d("2025-01-15"));
+    assert_eq!(clock.local(at("2025-01-16T02:00:00Z")).to_rfc3339(), "2025-01-15T21:00:00-05:00");
+}
+
+#[test]
+fn honours_the_rollover_hour() {
+    let clock = Clock::new(Some("Europe/Madrid"), 4).unwrap();
+
+    assert_eq!(clock.day_of(at("2025-01-16T03:59:00+01:00")), d("2025-01-15"));
+    assert_eq!(clock.day_of(at("2025-01-16T04:00:00+01:00")), d("2025-01-16"));
+    // Daylight saving time is applied
+    assert_eq!(clock.local(at("2025-07-01T12:00:00Z")).to_rfc3339(), "2025-07-01T14:00:00+02:00");
+}
+
+#[test]
+fn rejects_bad_settings() {
+    assert_eq!(Clock::new(Some("Mars/Olympus"), 0).unwrap_err().kind(), "config");
+    assert_eq!(Clock::new(None, 24).unwrap_err().kind(), "config");
+}
+
+#[test]
+fn keeps_the_offset_on_disk() {
+    let legacy: Note = serde_json::from_str(r#"{"when":"2025-01-15T10:30:00Z","text":"old","tags":[]}"#).unwrap();
+    assert_eq!(legacy.when, at("2025-01-15T10:30:00+00:00"));
+
+    let note: Note = serde_json::from_str(r#"{"when":"2025-01-15T23:30:00-05:00","text":"late","tags":[]}"#).unwrap();
+    let json = serde_json::to_value(&note).unwrap();
+    assert_eq!(json["when"], "2025-01-15T23:30:00-05:00");
+}
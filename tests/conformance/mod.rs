@@ -0,0 +1,225 @@
+//! The behaviour every `Storage` must share, run against each store with
+//! `storage_conformance!(name, Backend { .. })` from the store's own test file.
+
+use aigenda::{
+    dates::DateRange,
+    error::AppError,
+    models::{DayLog, Editor, Note, Priority, Task, TaskStatus, TrashedNote},
+    storage::Storage,
+};
+use chrono::{DateTime, Duration, NaiveDate};
+use std::path::Path;
+
+/// How to run the suite against one store
+pub struct Backend<S> {
+    /// Opens the store in a fresh folder
+    pub open: fn(&Path) -> S,
+    /// Damages the stored day in that folder; `None` for stores with nothing on disk
+    pub corrupt: Option<fn(&Path, NaiveDate)>,
+}
+
+macro_rules! storage_conformance {
+    ($name:ident, $backend:expr) => {
+        mod $name {
+            use super::*;
+
+            #[test]
+            fn round_trip() {
+                conformance::round_trip(&$backend);
+            }
+
+            #[test]
+            fn missing_day() {
+                conformance::missing_day(&$backend);
+            }
+
+            #[test]
+            fn ordering() {
+                conformance::ordering(&$backend);
+            }
+
+            #[test]
+            fn unicode() {
+                conformance::unicode(&$backend);
+            }
+
+            #[test]
+            fn concurrent_writes() {
+                conformance::concurrent_writes(&$backend);
+            }
+
+            #[test]
+            fn corruption() {
+                conformance::corruption(&$backend);
+            }
+        }
+    };
+}
+
+pub fn d(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 1, day).unwrap()
+}
+
+fn note(date: NaiveDate, minute: i64, text: &str) -> Note {
+    let start = DateTime::parse_from_rfc3339(&format!("{}T09:00:00+01:00", date.format("%Y-%m-%d"))).unwrap();
+    Note { when: start + Duration::minutes(minute), ..Note::new(text.to_string()) }
+}
+
+fn day(date: NaiveDate, texts: &[&str]) -> DayLog {
+    let mut day = DayLog::new(date);
+    day.notes.extend(texts.iter().enumerate().map(|(i, text)| note(date, i as i64, text)));
+    day
+}
+
+fn same(a: &DayLog, b: &DayLog) -> bool {
+    serde_json::to_value(a).unwrap() == serde_json::to_value(b).unwrap()
+}
+
+/// Days and the trash read back exactly as saved, and saving again replaces them
+pub fn round_trip<S: Storage>(backend: &Backend<S>) {
+    let dir = tempfile::tempdir().unwrap();
+    let store = (backend.open)(dir.path());
+
+    let mut saved = day(d(15), &["plain", "tagged", "task"]);
+    saved.recurring.push("standup".to_string());
+    saved.notes[1].tags = vec!["work".to_string(), "auth".to_string()];
+    saved.notes[2].task = Some(Task {
+        status: TaskStatus::Done,
+        due: Some(d(20)),
+        priority: Some(Priority::High),
+        closed_at: Some(saved.notes[2].when + Duration::hours(2)),
+    });
+    saved.notes[2].revise("task, reworded".to_string(), Editor::Agent);
+    store.save_day(&saved).unwrap();
+    assert!(same(&store.load_day(d(15)).unwrap(), &saved));
+
+    saved.notes.remove(0);
+    store.save_day(&saved).unwrap();
+    assert!(same(&store.load_day(d(15)).unwrap(), &saved));
+
+    let trash = vec![TrashedNote { date: d(15), position: 1, deleted_at: saved.notes[0].when, note: note(d(15), 0, "plain") }];
+    store.save_trash(&trash).unwrap();
+    assert_eq!(serde_json::to_value(store.load_trash().unwrap()).unwrap(), serde_json::to_value(&trash).unwrap());
+    store.save_trash(&[]).unwrap();
+    assert!(store.load_trash().unwrap().is_empty());
+}
+
+/// A day that was never saved (or was deleted) reads as empty and isn't listed
+pub fn missing_day<S: Storage>(backend: &Backend<S>) {
+    let dir = tempfile::tempdir().unwrap();
+    let store = (backend.open)(dir.path());
+
+    let empty = store.load_day(d(15)).unwrap();
+    assert_eq!(empty.date, d(15));
+    assert!(empty.notes.is_empty());
+    assert!(!store.exists(d(15)).unwrap());
+    assert!(store.list_dates().unwrap().is_empty());
+    assert!(store.load_trash().unwrap().is_empty());
+    store.delete_day(d(15)).unwrap();
+
+    store.save_day(&day(d(15), &["soon gone"])).unwrap();
+    assert!(store.exists(d(15)).unwrap());
+    store.delete_day(d(15)).unwrap();
+    assert!(!store.exists(d(15)).unwrap());
+    assert!(store.load_day(d(15)).unwrap().notes.is_empty());
+}
+
+/// Dates are listed and walked oldest first whatever order they were saved in, and a day
+/// keeps its notes in the order they were saved, not by time
+pub fn ordering<S: Storage>(backend: &Backend<S>) {
+    let dir = tempfile::tempdir().unwrap();
+    let store = (backend.open)(dir.path());
+    for date in [d(20), d(3), d(11)] {
+        store.save_day(&day(date, &["a"])).unwrap();
+    }
+
+    assert_eq!(store.list_dates().unwrap(), [d(3), d(11), d(20)]);
+    let dates: Vec<NaiveDate> = store.days().unwrap().map(|day| day.unwrap().date).collect();
+    assert_eq!(dates, [d(3), d(11), d(20)]);
+    let newest_first: Vec<NaiveDate> = store.days().unwrap().rev().map(|day| day.unwrap().date).collect();
+    assert_eq!(newest_first, [d(20), d(11), d(3)]);
+    let range = DateRange::new(d(4), d(20)).unwrap();
+    let within: Vec<NaiveDate> = store.days_between(range).unwrap().map(|day| day.unwrap().date).collect();
+    assert_eq!(within, [d(11), d(20)]);
+
+    let mut moved = day(d(11), &["first", "second", "third"]);
+    moved.notes.swap(0, 2);
+    store.save_day(&moved).unwrap();
+    let texts: Vec<String> = store.load_day(d(11)).unwrap().notes.into_iter().map(|n| n.text).collect();
+    assert_eq!(texts, ["third", "second", "first"]);
+}
+
+/// Any text survives: accents, CJK, right-to-left, emoji sequences, combining marks, control
+/// characters and markup
+pub fn unicode<S: Storage>(backend: &Backend<S>) {
+    let dir = tempfile::tempdir().unwrap();
+    let store = (backend.open)(dir.path());
+
+    let mut saved = day(
+        d(15),
+        &[
+            "naïve café, Ærøskøbing",
+            "日本語のメモ",
+            "עברית ومرحبا",
+            "family 👩‍👩‍👧 and flags 🇪🇸",
+            "e\u{301} combined, \u{200b}zero width",
+            "tab\there, \"quotes\", back\\slash, # not a tag",
+            "- [ ] looks like a task\n---\nand front matter",
+        ],
+    );
+    saved.notes[1].tags = vec!["über".to_string(), "日本".to_string()];
+    store.save_day(&saved).unwrap();
+    assert!(same(&store.load_day(d(15)).unwrap(), &saved));
+}
+
+/// Saves from many threads at once all land, and a day saved by several reads back as one of
+/// the saved versions, never a mix, even while it's being written
+pub fn concurrent_writes<S: Storage>(backend: &Backend<S>) {
+    let dir = tempfile::tempdir().unwrap();
+    let store = (backend.open)(dir.path());
+    let threads = 8;
+
+    std::thread::scope(|scope| {
+        let reader = scope.spawn(|| (0..200).try_for_each(|_| store.load_day(d(25)).map(drop)));
+        for t in 0..threads {
+            let store = &store;
+            scope.spawn(move || {
+                for round in 0..10 {
+                    let text = format!("thread {} round {}", t, round);
+                    store.save_day(&day(d(1 + t), &[&text])).unwrap();
+                    // Versions of different lengths, so a torn write shows
+                    let shared: Vec<&str> = std::iter::repeat_n(text.as_str(), t as usize + 1).collect();
+                    store.save_day(&day(d(25), &shared)).unwrap();
+                }
+            });
+        }
+        reader.join().unwrap().unwrap();
+    });
+
+    let mut expected: Vec<NaiveDate> = (1..=threads).map(d).collect();
+    expected.push(d(25));
+    assert_eq!(store.list_dates().unwrap(), expected);
+    for t in 0..threads {
+        assert_eq!(store.load_day(d(1 + t)).unwrap().notes[0].text, format!("thread {} round 9", t));
+    }
+
+    let shared = store.load_day(d(25)).unwrap();
+    let first = &shared.notes[0].text;
+    let t: usize = first.split(' ').nth(1).unwrap().parse().unwrap();
+    assert_eq!(shared.notes.len(), t + 1, "{:?}", shared.notes);
+    assert!(shared.notes.iter().all(|n| &n.text == first));
+}
+
+/// A damaged day is reported as corrupt, and the other days still read
+pub fn corruption<S: Storage>(backend: &Backend<S>) {
+    let Some(corrupt) = backend.corrupt else { return };
+    let dir = tempfile::tempdir().unwrap();
+    let store = (backend.open)(dir.path());
+    store.save_day(&day(d(15), &["will break"])).unwrap();
+    store.save_day(&day(d(16), &["fine"])).unwrap();
+
+    corrupt(dir.path(), d(15));
+    assert!(matches!(store.load_day(d(15)), Err(AppError::Corrupt(_))));
+    assert_eq!(store.load_day(d(16)).unwrap().notes[0].text, "fine");
+    assert_eq!(store.list_dates().unwrap(), [d(15), d(16)]);
+}
@@ -0,0 +1,78 @@
+use aigenda::{
+    cli::{Cli, Commands},
+    commands::list::selected_range,
+    dates::{parse_date_from, DateRange},
+};
+use chrono::NaiveDate;
+use clap::Parser;
+
+fn d(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+// A Wednesday
+const TODAY: &str = "2025-01-15";
+
+#[test]
+fn parses_natural_dates() {
+    let today = d(TODAY);
+    let cases = [
+        ("2024-12-31", "2024-12-31"),
+        ("today", "2025-01-15"),
+        ("Yesterday", "2025-01-14"),
+        ("tomorrow", "2025-01-16"),
+        ("monday", "2025-01-13"),
+        ("friday", "2025-01-17"),
+        ("last monday", "2025-01-13"),
+        ("last wednesday", "2025-01-08"),
+        ("next wednesday", "2025-01-22"),
+        ("last week", "2025-01-08"),
+        ("3 days ago", "2025-01-12"),
+        ("2 weeks ago", "2025-01-01"),
+        ("a month ago", "2024-12-15"),
+        ("in 2 days", "2025-01-17"),
+    ];
+
+    for (expr, expected) in cases {
+        assert_eq!(parse_date_from(expr, today).unwrap(), d(expected), "{}", expr);
+    }
+
+    let error = parse_date_from("the day after", today).unwrap_err();
+    assert_eq!(error.kind(), "invalid_parameters");
+}
+
+#[test]
+fn builds_ranges() {
+    let today = d(TODAY);
+
+    assert_eq!(DateRange::week_of(today), DateRange::new(d("2025-01-13"), d("2025-01-19")).unwrap());
+    assert_eq!(DateRange::month("2024-02", today).unwrap(), DateRange::new(d("2024-02-01"), d("2024-02-29")).unwrap());
+    assert_eq!(DateRange::month("last month", today).unwrap(), DateRange::new(d("2024-12-01"), d("2024-12-31")).unwrap());
+    assert_eq!(DateRange::last("7d", today).unwrap(), DateRange::new(d("2025-01-09"), today).unwrap());
+    assert_eq!(DateRange::last("2 weeks", today).unwrap().start, d("2025-01-02"));
+    assert!(DateRange::last("7", today).is_err());
+    assert!(DateRange::new(today, d("2025-01-01")).is_err());
+    assert!(DateRange::day(today).is_single_day());
+}
+
+#[test]
+fn list_flags_select_a_range() {
+    let today = d(TODAY);
+    let range = |args: &[&str]| {
+        let cli = Cli::try_parse_from([&["aigenda", "list"], args].concat()).unwrap();
+        let Commands::List(list) = cli.command else { panic!("not a list command") };
+        selected_range(&list, today).unwrap()
+    };
+
+    assert_eq!(range(&[]), DateRange::day(today));
+    assert_eq!(range(&["--date", "yesterday"]), DateRange::day(d("2025-01-14")));
+    assert_eq!(range(&["--from", "last monday"]), DateRange::new(d("2025-01-13"), today).unwrap());
+    assert_eq!(range(&["--from", "2025-01-01", "--to", "2025-01-05"]), DateRange::new(d("2025-01-01"), d("2025-01-05")).unwrap());
+    assert_eq!(range(&["--week"]), DateRange::week_of(today));
+    assert_eq!(range(&["--week", "2 weeks ago"]), DateRange::new(d("2024-12-30"), d("2025-01-05")).unwrap());
+    assert_eq!(range(&["--month"]), DateRange::month_of(today));
+    assert_eq!(range(&["--last", "7d"]), DateRange::new(d("2025-01-09"), today).unwrap());
+
+    assert!(Cli::try_parse_from(["aigenda", "list", "--week", "--last", "7d"]).is_err());
+    assert!(Cli::try_parse_from(["aigenda", "list", "--to", "today"]).is_err());
+}
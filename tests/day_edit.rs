@@ -0,0 +1,50 @@
+use aigenda::{
+    day_edit::{apply, render, EditSummary},
+    models::{DayLog, Note, TaskStatus},
+};
+use chrono::NaiveDate;
+
+fn day() -> DayLog {
+    let mut day = DayLog::new(NaiveDate::from_ymd_opt(2025, 1, 15).unwrap());
+    let mut first = Note::new("Fixd the login bug".to_string());
+    first.tags = vec!["work".to_string()];
+    day.notes.push(first);
+    day.notes.push(Note::task("Deploy the release".to_string(), None, None));
+    day.notes.push(Note::new("Lunch with Ana".to_string()));
+    day
+}
+
+#[test]
+fn unchanged_buffer_is_a_no_op() {
+    let day = day();
+    let (edited, summary) = apply(&day, &render(&day, None).unwrap(), None).unwrap();
+    assert!(summary.is_empty());
+    assert_eq!(edited.notes.len(), 3);
+    assert_eq!(edited.notes[0].tags, ["work"]);
+}
+
+#[test]
+fn edits_keep_timestamps_through_reorder_add_and_remove() {
+    let day = day();
+    let buffer = "# 2025-01-15\n\
+                  ## [2] 10:00\n[x] Deploy the release\n\n\
+                  ## [1] 09:00 #work #bugs\nFixed the login bug\n\n\
+                  ## new\nCall the bank\n";
+    let (edited, summary) = apply(&day, buffer, None).unwrap();
+
+    assert_eq!(summary, EditSummary { added: 1, removed: vec![3], changed: 2, reordered: true });
+    let texts: Vec<&str> = edited.notes.iter().map(|n| n.text.as_str()).collect();
+    assert_eq!(texts, ["Deploy the release", "Fixed the login bug", "Call the bank"]);
+    assert_eq!(edited.notes[0].when, day.notes[1].when);
+    assert_eq!(edited.notes[0].task.as_ref().unwrap().status, TaskStatus::Done);
+    assert_eq!(edited.notes[1].tags, ["work", "bugs"]);
+
+    // A single note can be edited, or split, without touching the rest
+    let (edited, _) = apply(&day, "## [3]\nLunch with Ana and Luis\n## new\nBook a table\n", Some(3)).unwrap();
+    assert_eq!(edited.notes.len(), 4);
+    assert_eq!(edited.notes[2].text, "Lunch with Ana and Luis");
+
+    assert!(apply(&day, "## [7]\nNope\n", None).is_err());
+    assert!(apply(&day, "## [1]\nA\n## [1]\nB\n", None).is_err());
+    assert!(apply(&day, "## [1]\n\n", None).is_err());
+}
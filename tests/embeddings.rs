@@ -0,0 +1,55 @@
+#![cfg(feature = "ai")]
+
+use aigenda::{
+    embeddings::{EmbeddingProvider, HashingEmbedder, IndexedStorage, SemanticIndex},
+    models::{DayLog, Note, NoteRef},
+    storage::{memory::MemoryStorage, Storage},
+};
+use chrono::NaiveDate;
+
+fn day(date: &str, texts: &[&str]) -> DayLog {
+    let mut day = DayLog::new(NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap());
+    for text in texts {
+        day.add_note(Note::new(text.to_string()));
+    }
+    day
+}
+
+#[test]
+fn parses_note_ids() {
+    let note: NoteRef = "2025-01-15#3".parse().unwrap();
+    assert_eq!(note.date, NaiveDate::from_ymd_opt(2025, 1, 15).unwrap());
+    assert_eq!(note.index, 3);
+    assert_eq!(note.to_string(), "2025-01-15#3");
+
+    assert!("2025-01-15".parse::<NoteRef>().is_err());
+    assert!("2025-01-15#0".parse::<NoteRef>().is_err());
+}
+
+#[tokio::test]
+async fn finds_related_notes_and_picks_up_new_saves() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = IndexedStorage::new(MemoryStorage::default(), dir.path());
+    store.save_day(&day("2025-01-14", &["deploy api to production", "lunch with sam"])).unwrap();
+    store.save_day(&day("2025-01-15", &["rollback production deploy of the api"])).unwrap();
+
+    let index = SemanticIndex::new(dir.path(), Box::new(HashingEmbedder::default()));
+    let hits = index.search_similar(&store, "2025-01-15#1".parse().unwrap(), 2).await.unwrap();
+    assert_eq!(hits[0].note.to_string(), "2025-01-14#1");
+
+    store.save_day(&day("2025-01-16", &["groceries: sam wants lunch tomorrow"])).unwrap();
+    let hits = index.search_text(&store, "lunch with sam", 1).await.unwrap();
+    assert_eq!(hits.len(), 1);
+    assert!(hits[0].text.contains("lunch"));
+
+    let refreshed = index.refresh(&store, false).await.unwrap();
+    assert_eq!(refreshed.entries.len(), 4);
+}
+
+#[tokio::test]
+async fn hashing_embedder_is_deterministic() {
+    let embedder = HashingEmbedder::default();
+    let texts = vec!["same text".to_string(), "same text".to_string()];
+    let vectors = embedder.embed(&texts).await.unwrap();
+    assert_eq!(vectors[0], vectors[1]);
+}
@@ -0,0 +1,179 @@
+#![cfg(feature = "encryption")]
+
+#[macro_use]
+mod conformance;
+
+use aigenda::{
+    error::AppError,
+    keys::{DataKey, KeyFile, SecretKind},
+    models::{DayLog, Note},
+    storage::{encrypted::EncryptedStorage, Storage},
+};
+use assert_cmd::Command;
+use chrono::NaiveDate;
+use conformance::Backend;
+use std::{fs, path::Path};
+
+fn d(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 1, day).unwrap()
+}
+
+fn save(store: &EncryptedStorage, date: NaiveDate, text: &str) {
+    let mut day = DayLog::new(date);
+    day.notes.push(Note::new(text.to_string()));
+    store.save_day(&day).unwrap();
+}
+
+fn copy(key: &DataKey) -> DataKey {
+    DataKey::from_hex(&key.to_hex()).unwrap()
+}
+
+/// Every file in the data dir, name and contents
+fn files(dir: &Path) -> Vec<(String, Vec<u8>)> {
+    fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap())
+        .map(|e| (e.file_name().into_string().unwrap(), fs::read(e.path()).unwrap()))
+        .collect()
+}
+
+#[test]
+fn days_round_trip_without_plaintext_on_disk() {
+    let data = tempfile::tempdir().unwrap();
+    let store = EncryptedStorage::new(data.path(), DataKey::generate().unwrap(), false).unwrap();
+    save(&store, d(16), "dentist at 9");
+    save(&store, d(15), "buy oat milk");
+
+    assert_eq!(store.list_dates().unwrap(), vec![d(15), d(16)]);
+    assert_eq!(store.load_day(d(16)).unwrap().notes[0].text, "dentist at 9");
+    assert!(store.load_day(d(17)).unwrap().notes.is_empty());
+    for (name, bytes) in files(data.path()) {
+        assert!(!String::from_utf8_lossy(&bytes).contains("oat milk"), "{} is readable", name);
+    }
+
+    store.delete_day(d(15)).unwrap();
+    assert_eq!(store.list_dates().unwrap(), vec![d(16)]);
+}
+
+#[test]
+fn a_wrong_key_or_secret_is_refused() {
+    let data = tempfile::tempdir().unwrap();
+    let key = DataKey::generate().unwrap();
+    let key_file = KeyFile::new(&key, b"correct horse", SecretKind::Passphrase, false, 4).unwrap();
+    save(&EncryptedStorage::new(data.path(), copy(&key), false).unwrap(), d(15), "secret");
+
+    assert_eq!(key_file.unwrap_key(b"correct horse").unwrap().to_hex(), key.to_hex());
+    assert!(matches!(key_file.unwrap_key(b"wrong horse"), Err(AppError::PermissionDenied(_))));
+    assert!(key_file.verify(&DataKey::generate().unwrap()).is_err());
+
+    let other = EncryptedStorage::new(data.path(), DataKey::generate().unwrap(), false).unwrap();
+    assert!(matches!(other.load_day(d(15)), Err(AppError::Corrupt(_))));
+}
+
+#[test]
+fn encrypted_file_names_hide_the_dates() {
+    let data = tempfile::tempdir().unwrap();
+    let store = EncryptedStorage::new(data.path(), DataKey::generate().unwrap(), true).unwrap();
+    save(&store, d(15), "one");
+    save(&store, d(16), "two");
+    save(&store, d(15), "one, again");
+
+    assert!(files(data.path()).iter().all(|(name, _)| !name.contains("2025")));
+    assert_eq!(files(data.path()).len(), 3, "two days and the name mapping");
+    assert_eq!(store.list_dates().unwrap(), vec![d(15), d(16)]);
+    assert_eq!(store.load_day(d(15)).unwrap().notes[0].text, "one, again");
+
+    store.delete_day(d(15)).unwrap();
+    assert_eq!(store.list_dates().unwrap(), vec![d(16)]);
+    assert_eq!(files(data.path()).len(), 2);
+}
+
+#[test]
+fn rekeying_reencrypts_everything() {
+    let data = tempfile::tempdir().unwrap();
+    let old = DataKey::generate().unwrap();
+    let store = EncryptedStorage::new(data.path(), copy(&old), true).unwrap();
+    save(&store, d(15), "keep me");
+
+    let new = DataKey::generate().unwrap();
+    let store = store.rekey(copy(&new)).unwrap();
+    assert_eq!(store.load_day(d(15)).unwrap().notes[0].text, "keep me");
+
+    let stale = EncryptedStorage::new(data.path(), old, true).unwrap();
+    assert!(stale.list_dates().is_err());
+    let fresh = EncryptedStorage::new(data.path(), new, true).unwrap();
+    assert_eq!(fresh.list_dates().unwrap(), vec![d(15)]);
+}
+
+#[test]
+fn an_interrupted_rekey_leaves_every_day_readable() {
+    let data = tempfile::tempdir().unwrap();
+    let old = DataKey::generate().unwrap();
+    let store = EncryptedStorage::new(data.path(), copy(&old), false).unwrap();
+    for day in [14, 15, 16] {
+        save(&store, d(day), &format!("day {}", day));
+    }
+    // The 15th can't be written: its temporary files are taken by folders
+    let blocked: Vec<_> = (0..2000)
+        .map(|n| data.path().join(format!(".2025-01-15.enc.{}-{}.tmp", std::process::id(), n)))
+        .collect();
+    blocked.iter().for_each(|dir| fs::create_dir(dir).unwrap());
+
+    let new = DataKey::generate().unwrap();
+    let mut key_file = KeyFile::new(&new, b"new horse", SecretKind::Passphrase, false, 4).unwrap();
+    key_file.keep_previous(&new, &old).unwrap();
+    assert!(store.rekey(copy(&new)).is_err());
+    assert!(EncryptedStorage::new(data.path(), copy(&old), false).unwrap().load_day(d(14)).is_err());
+
+    let key = key_file.unwrap_key(b"new horse").unwrap();
+    let previous = key_file.previous_key(&key).unwrap();
+    let store = EncryptedStorage::new(data.path(), key, false).unwrap().with_previous(previous);
+    for day in [14, 15, 16] {
+        assert_eq!(store.load_day(d(day)).unwrap().notes[0].text, format!("day {}", day));
+    }
+
+    blocked.iter().for_each(|dir| fs::remove_dir(dir).unwrap());
+    store.reseal().unwrap();
+    let fresh = EncryptedStorage::new(data.path(), new, false).unwrap();
+    assert_eq!(fresh.load_day(d(16)).unwrap().notes[0].text, "day 16");
+}
+
+#[test]
+fn key_init_encrypts_the_configured_format() {
+    let home = tempfile::tempdir().unwrap();
+    fs::write(home.path().join("config.json"), r#"{"storage": "markdown"}"#).unwrap();
+    let aigenda = |args: &[&str]| {
+        let output = Command::cargo_bin("aigenda")
+            .unwrap()
+            .env("AIGENDA_HOME", home.path())
+            .env("AIGENDA_PASSPHRASE", "correct horse")
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    aigenda(&["add", "buy oat milk"]);
+    aigenda(&["key", "init"]);
+    for (name, bytes) in files(home.path()) {
+        assert!(!name.ends_with(".md"), "{} is left in the clear", name);
+        assert!(!String::from_utf8_lossy(&bytes).contains("oat milk"), "{} is readable", name);
+    }
+    assert!(aigenda(&["list"]).contains("buy oat milk"));
+}
+
+fn flip_a_byte(dir: &Path, date: NaiveDate) {
+    let path = dir.join(format!("{}.enc", date.format("%Y-%m-%d")));
+    let mut sealed = fs::read(&path).unwrap();
+    *sealed.last_mut().unwrap() ^= 1;
+    fs::write(path, sealed).unwrap();
+}
+
+storage_conformance!(
+    encrypted_files,
+    Backend {
+        open: |dir| EncryptedStorage::new(dir, DataKey::generate().unwrap(), false).unwrap(),
+        corrupt: Some(flip_a_byte),
+    }
+);
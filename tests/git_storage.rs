@@ -0,0 +1,93 @@
+use aigenda::{
+    config::GitConfig,
+    error::AppError,
+    models::{DayLog, Editor, Note},
+    storage::{
+        fs::FsStorage,
+        git::{GitRepo, GitStorage},
+        Storage,
+    },
+};
+use chrono::NaiveDate;
+use std::{path::Path, process::Command};
+
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git").args(args).current_dir(dir).output().unwrap();
+    assert!(output.status.success(), "git {:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn open(dir: &Path) -> GitStorage<FsStorage> {
+    let inner = FsStorage::with_root(dir.to_path_buf()).unwrap();
+    GitStorage::from_config(inner, Some(&GitConfig::default()), dir).unwrap()
+}
+
+#[test]
+fn changes_are_committed_once_per_checkpoint() {
+    let data = tempfile::tempdir().unwrap();
+    let store = open(data.path());
+    let date = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+
+    let mut day = DayLog::new(date);
+    day.notes.push(Note::new("first".to_string()));
+    store.save_day(&day).unwrap();
+    day.notes.push(Note::new("second".to_string()));
+    store.save_day(&day).unwrap();
+    store.checkpoint(Editor::Cli).unwrap();
+
+    day.notes[1].revise("second, fixed".to_string(), Editor::Agent);
+    store.save_day(&day).unwrap();
+    store.checkpoint(Editor::Agent).unwrap();
+    // Nothing changed: no empty commit
+    store.checkpoint(Editor::Cli).unwrap();
+
+    let log = git(data.path(), &["log", "--format=%s"]);
+    assert_eq!(log.lines().collect::<Vec<_>>(), [
+        "agent: update note 2 on 2025-01-15",
+        "add note to 2025-01-15 (x2)",
+    ]);
+}
+
+#[test]
+fn sync_pushes_to_a_bare_remote() {
+    let data = tempfile::tempdir().unwrap();
+    let remote = tempfile::tempdir().unwrap();
+    git(remote.path(), &["init", "-q", "--bare"]);
+
+    let store = open(data.path());
+    let mut day = DayLog::new(NaiveDate::from_ymd_opt(2025, 1, 15).unwrap());
+    day.notes.push(Note::new("synced".to_string()));
+    store.save_day(&day).unwrap();
+    store.checkpoint(Editor::Cli).unwrap();
+
+    let url = remote.path().to_str().unwrap();
+    let repo = GitRepo::open_or_init(data.path(), "main").unwrap();
+    repo.sync(url, "main").unwrap();
+    // A second sync pulls first; with nothing new it's a no-op
+    repo.sync(url, "main").unwrap();
+
+    let files = git(remote.path(), &["ls-tree", "--name-only", "main"]);
+    assert!(files.lines().any(|f| f == "2025-01-15.json"));
+}
+
+#[test]
+fn sync_reports_diverged_edits_as_a_conflict() {
+    let remote = tempfile::tempdir().unwrap();
+    git(remote.path(), &["init", "-q", "--bare"]);
+    let url = remote.path().to_str().unwrap();
+    let date = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+
+    let mut results = Vec::new();
+    let dirs = [tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap()];
+    for (dir, text) in dirs.iter().zip(["from the laptop", "from the phone"]) {
+        let store = open(dir.path());
+        let mut day = DayLog::new(date);
+        day.notes.push(Note::new(text.to_string()));
+        store.save_day(&day).unwrap();
+        store.checkpoint(Editor::Cli).unwrap();
+        results.push(GitRepo::open_or_init(dir.path(), "main").unwrap().sync(url, "main"));
+    }
+
+    assert!(results[0].is_ok());
+    assert!(matches!(results[1], Err(AppError::Conflict(_))), "{:?}", results[1]);
+}
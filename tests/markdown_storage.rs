@@ -0,0 +1,150 @@
+#[macro_use]
+mod conformance;
+
+use aigenda::{
+    models::{DayLog, Editor, Note, Priority, Task, TaskStatus},
+    storage::{markdown::MarkdownStorage, Storage},
+};
+use chrono::{DateTime, NaiveDate, Timelike};
+use conformance::Backend;
+use std::{fs, path::Path};
+
+fn d(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 1, day).unwrap()
+}
+
+fn note(at: &str, text: &str) -> Note {
+    Note {
+        when: DateTime::parse_from_rfc3339(at).unwrap(),
+        ..Note::new(text.to_string())
+    }
+}
+
+fn sample() -> DayLog {
+    let mut day = DayLog::new(d(15));
+    day.recurring.push("standup".to_string());
+
+    let mut tagged = note("2025-01-15T09:12:41.5+01:00", "Fixed the login bug");
+    tagged.tags = vec!["work".to_string(), "auth".to_string()];
+    day.notes.push(tagged);
+
+    let mut task = note("2025-01-15T10:30:02+01:00", "Deploy the release\n\n  with the migration first");
+    task.task = Some(Task {
+        status: TaskStatus::Done,
+        due: Some(d(20)),
+        priority: Some(Priority::High),
+        closed_at: Some(DateTime::parse_from_rfc3339("2025-01-15T18:00:00+01:00").unwrap()),
+    });
+    task.revise("Deploy the release\n\n  with the migration first!".to_string(), Editor::Agent);
+    day.notes.push(task);
+
+    // Texts a bullet can't carry as is
+    day.notes.push(note("2025-01-15T10:30:59+01:00", "ends like a tag #not"));
+    day.notes.push(note("2025-01-15T11:00:00+01:00", "trailing newline\n"));
+    day.notes.push(note("2025-01-15T11:00:00+01:00", "windows\r\nline"));
+    let mut bang = note("2025-01-15T12:00:00-05:00", "!low is not a priority");
+    bang.task = Some(Task { status: TaskStatus::Open, due: None, priority: None, closed_at: None });
+    day.notes.push(bang);
+    day
+}
+
+#[test]
+fn days_round_trip_losslessly() {
+    let data = tempfile::tempdir().unwrap();
+    let store = MarkdownStorage::with_root(data.path().to_path_buf()).unwrap();
+    let day = sample();
+    store.save_day(&day).unwrap();
+
+    let loaded = store.load_day(d(15)).unwrap();
+    assert_eq!(serde_json::to_value(&loaded).unwrap(), serde_json::to_value(&day).unwrap());
+
+    let file = fs::read_to_string(data.path().join("2025-01-15.md")).unwrap();
+    assert!(file.contains("\n- 09:12 Fixed the login bug #work #auth\n"), "{}", file);
+    assert!(file.contains("\n- [x] !high (due 2025-01-20) 10:30 Deploy the release\n\n    with the"), "{}", file);
+}
+
+#[test]
+fn edits_made_in_an_editor_are_picked_up() {
+    let data = tempfile::tempdir().unwrap();
+    let store = MarkdownStorage::with_root(data.path().to_path_buf()).unwrap();
+    let mut day = DayLog::new(d(15));
+    day.notes.push(note("2025-01-15T08:00:00+01:00", "first"));
+    let mut second = note("2025-01-15T09:00:00+01:00", "second");
+    second.revise("second, fixed".to_string(), Editor::Cli);
+    day.notes.push(second);
+    let mut third = note("2025-01-15T10:00:00+01:00", "call Ana");
+    third.task = Some(Task { status: TaskStatus::Open, due: None, priority: None, closed_at: None });
+    day.notes.push(third);
+    store.save_day(&day).unwrap();
+
+    // Drop the first bullet, tick the task, move it up and add a note
+    let path = data.path().join("2025-01-15.md");
+    let file = fs::read_to_string(&path)
+        .unwrap()
+        .replace("- 08:00 first\n", "")
+        .replace("- [ ] 10:00 call Ana\n", "")
+        .replace("- 09:00 second, fixed\n", "- [x] 10:00 call Ana\n- 09:00 second, fixed\n\nSome prose.\n- 14:30 lunch idea #food\n");
+    fs::write(&path, file).unwrap();
+
+    let loaded = store.load_day(d(15)).unwrap();
+    let texts: Vec<&str> = loaded.notes.iter().map(|n| n.text.as_str()).collect();
+    assert_eq!(texts, ["call Ana", "second, fixed", "lunch idea"]);
+    assert_eq!(loaded.notes[0].task.as_ref().unwrap().status, TaskStatus::Done);
+    assert_eq!(loaded.notes[1].revisions.len(), 1, "metadata follows the moved bullet");
+    assert_eq!(loaded.notes[2].tags, ["food"]);
+    assert_eq!((loaded.notes[2].when.hour(), loaded.notes[2].when.minute()), (14, 30));
+}
+
+#[test]
+fn metadata_follows_bullets_written_in_the_same_minute() {
+    let data = tempfile::tempdir().unwrap();
+    let store = MarkdownStorage::with_root(data.path().to_path_buf()).unwrap();
+    let mut day = DayLog::new(d(15));
+    day.notes.push(note("2025-01-15T09:12:05+01:00", "first"));
+    let mut second = note("2025-01-15T09:12:40+01:00", "second");
+    second.revise("second, fixed".to_string(), Editor::Cli);
+    day.notes.push(second);
+    let mut third = note("2025-01-15T10:00:00+01:00", "call Ana");
+    third.revise("call Ana back".to_string(), Editor::Cli);
+    day.notes.push(third);
+    store.save_day(&day).unwrap();
+
+    // Swap the two 09:12 bullets and move the call to the afternoon
+    let path = data.path().join("2025-01-15.md");
+    let file = fs::read_to_string(&path)
+        .unwrap()
+        .replace("- 09:12 first\n- 09:12 second, fixed\n", "- 09:12 second, fixed\n- 09:12 first\n")
+        .replace("- 10:00 call Ana back\n", "- 15:30 call Ana back\n");
+    fs::write(&path, file).unwrap();
+
+    let loaded = store.load_day(d(15)).unwrap();
+    let texts: Vec<&str> = loaded.notes.iter().map(|n| n.text.as_str()).collect();
+    assert_eq!(texts, ["second, fixed", "first", "call Ana back"]);
+    assert_eq!(loaded.notes[0].when, day.notes[1].when);
+    assert_eq!(loaded.notes[0].revisions.len(), 1);
+    assert_eq!(loaded.notes[1].when, day.notes[0].when);
+    assert!(loaded.notes[1].revisions.is_empty());
+    assert_eq!(loaded.notes[2].revisions.len(), 1, "a retimed bullet keeps its history");
+    assert_eq!((loaded.notes[2].when.hour(), loaded.notes[2].when.minute()), (15, 30));
+}
+
+#[test]
+fn other_markdown_files_are_left_alone() {
+    let data = tempfile::tempdir().unwrap();
+    fs::write(data.path().join("ideas.md"), "- not a day\n").unwrap();
+    fs::write(data.path().join("2025-01-16.md"), "# Thursday\n\n- 07:45 written by hand\n").unwrap();
+    let store = MarkdownStorage::with_root(data.path().to_path_buf()).unwrap();
+
+    assert_eq!(store.list_dates().unwrap(), vec![d(16)]);
+    assert_eq!(store.load_day(d(16)).unwrap().notes[0].text, "written by hand");
+}
+
+fn corrupt_markdown(dir: &Path, date: NaiveDate) {
+    let path = dir.join(format!("{}.md", date.format("%Y-%m-%d")));
+    fs::write(path, "---\nnotes:\n  - {\"when\": oops\n---\n\n- 09:00 broken\n").unwrap();
+}
+
+storage_conformance!(
+    markdown_files,
+    Backend { open: |dir| MarkdownStorage::with_root(dir.to_path_buf()).unwrap(), corrupt: Some(corrupt_markdown) }
+);
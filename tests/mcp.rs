@@ -0,0 +1,219 @@
+#![cfg(feature = "ai")]
+
+use aigenda::{
+    agent::{
+        tools::{ActionSchema, ParameterSchema, ParameterType, ReturnSchema, ToolAction},
+        Tool, ToolCategory, ToolRegistry, ToolSchema,
+    },
+    config::McpServerConfig,
+    error::{AppError, AppResult},
+    mcp::{McpClient, McpServer},
+    models::{DayLog, Note},
+    storage::{memory::MemoryStorage, Storage},
+};
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use serde_json::{json, Value};
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+const AIGENDA: &str = env!("CARGO_BIN_EXE_aigenda");
+
+/// Counts loads, to check listing resources reads no days
+#[derive(Default)]
+struct Counted {
+    store: MemoryStorage,
+    loads: AtomicUsize,
+}
+
+impl Storage for Counted {
+    fn load_day(&self, date: NaiveDate) -> AppResult<DayLog> {
+        self.loads.fetch_add(1, Ordering::Relaxed);
+        self.store.load_day(date)
+    }
+
+    fn save_day(&self, day: &DayLog) -> AppResult<()> {
+        self.store.save_day(day)
+    }
+
+    fn list_dates(&self) -> AppResult<Vec<NaiveDate>> {
+        self.store.list_dates()
+    }
+
+    fn delete_day(&self, date: NaiveDate) -> AppResult<()> {
+        self.store.delete_day(date)
+    }
+}
+
+struct EchoTool;
+
+#[async_trait]
+impl Tool for EchoTool {
+    fn name(&self) -> &str {
+        "echo"
+    }
+
+    fn description(&self) -> &str {
+        "Echoes text"
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Internal
+    }
+
+    fn get_schema(&self) -> ToolSchema {
+        ToolSchema {
+            name: "echo".to_string(),
+            description: "Echoes text".to_string(),
+            category: ToolCategory::Internal,
+            actions: vec![ActionSchema {
+                name: "say".to_string(),
+                description: "Repeat the text".to_string(),
+                parameters: vec![
+                    ParameterSchema {
+                        name: "text".to_string(),
+                        description: "What to say".to_string(),
+                        param_type: ParameterType::String { max_length: Some(10) },
+                        required: true,
+                        default_value: None,
+                        validation: None,
+                    },
+                    ParameterSchema {
+                        name: "when".to_string(),
+                        description: "When it was said".to_string(),
+                        param_type: ParameterType::Date,
+                        required: false,
+                        default_value: None,
+                        validation: None,
+                    },
+                ],
+                returns: ReturnSchema {
+                    description: "The text".to_string(),
+                    return_type: ParameterType::String { max_length: None },
+                    possible_errors: vec![],
+                },
+            }],
+            examples: vec![],
+        }
+    }
+
+    fn actions(&self) -> Vec<ToolAction> {
+        vec![]
+    }
+
+    async fn execute(&self, _action: &str, parameters: &Value) -> AppResult<String> {
+        parameters["text"].as_str()
+            .map(str::to_string)
+            .ok_or_else(|| AppError::InvalidParameters("Missing text parameter".to_string()))
+    }
+}
+
+fn server<S: Storage>(store: &S) -> McpServer<'_, S> {
+    let mut registry = ToolRegistry::new();
+    registry.register_tool(Arc::new(EchoTool));
+    McpServer::new(registry, store)
+}
+
+async fn call<S: Storage>(server: &McpServer<'_, S>, method: &str, params: Value) -> Value {
+    server
+        .handle_message(json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))
+        .await
+        .expect("requests get a response")
+}
+
+#[tokio::test]
+async fn initializes_and_lists_tools() {
+    let store = MemoryStorage::default();
+    let server = server(&store);
+
+    let init = call(&server, "initialize", json!({ "protocolVersion": "2024-11-05" })).await;
+    assert_eq!(init["result"]["protocolVersion"], "2024-11-05");
+    assert_eq!(init["result"]["serverInfo"]["name"], "aigenda");
+
+    let initialized = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+    assert!(server.handle_message(initialized).await.is_none());
+
+    let tools = call(&server, "tools/list", json!({})).await;
+    let tool = &tools["result"]["tools"][0];
+    assert_eq!(tool["name"], "echo_say");
+    assert_eq!(tool["inputSchema"]["properties"]["text"]["maxLength"], 10);
+    assert_eq!(tool["inputSchema"]["required"], json!(["text"]));
+    // Date expressions like "yesterday" aren't ISO dates, so no `format` to trip validators
+    let when = &tool["inputSchema"]["properties"]["when"];
+    assert!(when.get("format").is_none());
+    assert!(when["description"].as_str().unwrap().contains("yesterday"), "{}", when);
+}
+
+#[tokio::test]
+async fn calls_tools_and_reports_failures_as_results() {
+    let store = MemoryStorage::default();
+    let server = server(&store);
+
+    let ok = call(&server, "tools/call", json!({ "name": "echo_say", "arguments": { "text": "hi" } })).await;
+    assert_eq!(ok["result"]["content"][0]["text"], "hi");
+    assert_eq!(ok["result"]["isError"], false);
+
+    let failed = call(&server, "tools/call", json!({ "name": "echo_say", "arguments": {} })).await;
+    assert_eq!(failed["result"]["isError"], true);
+
+    let unknown = call(&server, "tools/call", json!({ "name": "nope" })).await;
+    assert_eq!(unknown["error"]["code"], -32602);
+
+    let missing = call(&server, "does/not/exist", json!({})).await;
+    assert_eq!(missing["error"]["code"], -32601);
+}
+
+#[tokio::test]
+async fn exposes_days_as_resources() {
+    let store = Counted::default();
+    let mut day = DayLog::new(NaiveDate::from_ymd_opt(2025, 1, 15).unwrap());
+    day.add_note(Note::new("hello".to_string()));
+    store.save_day(&day).unwrap();
+    let server = server(&store);
+
+    let list = call(&server, "resources/list", json!({})).await;
+    assert_eq!(list["result"]["resources"][0]["uri"], "aigenda://notes/2025-01-15");
+    assert_eq!(store.loads.load(Ordering::Relaxed), 0, "listing loads no days");
+
+    let read = call(&server, "resources/read", json!({ "uri": "aigenda://notes/2025-01-15" })).await;
+    let content: Value = serde_json::from_str(read["result"]["contents"][0]["text"].as_str().unwrap()).unwrap();
+    assert_eq!(content["notes"][0]["text"], "hello");
+}
+
+fn server_config(args: &[&str]) -> McpServerConfig {
+    McpServerConfig {
+        command: AIGENDA.to_string(),
+        args: args.iter().map(|a| a.to_string()).collect(),
+        env: BTreeMap::new(),
+        timeout_secs: Some(10),
+    }
+}
+
+#[tokio::test]
+async fn client_talks_to_our_own_server() {
+    let client = McpClient::spawn("self", &server_config(&["mcp", "serve"])).await.unwrap();
+
+    let tools = client.list_tools().await.unwrap();
+    assert!(tools.iter().any(|t| t["name"] == "notes_read"));
+
+    let result = client.call_tool("notes_read", &json!({ "date": "1999-01-01" })).await.unwrap();
+    assert_eq!(result, "No notes found for 1999-01-01");
+
+    let failed = client.call_tool("notes_read", &json!({ "date": "not a date" })).await;
+    assert!(matches!(failed, Err(AppError::Api { .. })), "{:?}", failed);
+    let unknown = client.call_tool("no_such_tool", &json!({})).await;
+    assert!(matches!(unknown, Err(AppError::ToolNotFound(_))), "{:?}", unknown);
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn client_reports_servers_that_do_not_speak_mcp() {
+    let result = McpClient::spawn("broken", &server_config(&["--version"])).await;
+    assert!(result.is_err());
+}
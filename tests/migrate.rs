@@ -0,0 +1,75 @@
+use aigenda::{
+    config::StorageFormat,
+    error::AppError,
+    migrate::{migrate, Location},
+    models::{DayLog, Note, TrashedNote},
+    storage::{fs::FsStorage, markdown::MarkdownStorage, Storage},
+};
+use chrono::NaiveDate;
+use std::path::Path;
+
+fn d(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 1, day).unwrap()
+}
+
+fn day(date: NaiveDate, texts: &[&str]) -> DayLog {
+    let mut day = DayLog::new(date);
+    day.notes.extend(texts.iter().map(|t| Note::new(t.to_string())));
+    day
+}
+
+fn source(dir: &Path) -> FsStorage {
+    let store = FsStorage::with_root(dir.to_path_buf()).unwrap();
+    store.save_day(&day(d(14), &["first"])).unwrap();
+    store.save_day(&day(d(15), &["second", "third #work"])).unwrap();
+    store.save_day(&day(d(16), &["multi\nline"])).unwrap();
+    store
+        .save_trash(&[TrashedNote {
+            date: d(14),
+            position: 2,
+            deleted_at: Note::new(String::new()).when,
+            note: Note::new("gone".to_string()),
+        }])
+        .unwrap();
+    store
+}
+
+#[test]
+fn every_day_and_the_trash_are_copied_and_verified() {
+    let (from, to) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+    let source = source(from.path());
+    let target = MarkdownStorage::with_root(to.path().to_path_buf()).unwrap();
+
+    let report = migrate(&source, &target, false).unwrap();
+    assert_eq!((report.days, report.copied, report.skipped, report.trashed), (3, 3, 0, 1));
+    assert_eq!(target.list_dates().unwrap(), vec![d(14), d(15), d(16)]);
+    assert_eq!(target.load_day(d(16)).unwrap().notes[0].text, "multi\nline");
+    assert_eq!(target.load_trash().unwrap()[0].note.text, "gone");
+}
+
+#[test]
+fn an_interrupted_migration_resumes() {
+    let (from, to) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+    let source = source(from.path());
+    let target = MarkdownStorage::with_root(to.path().to_path_buf()).unwrap();
+    // Stopped after the first day, halfway through the second
+    target.save_day(&source.load_day(d(14)).unwrap()).unwrap();
+    target.save_day(&day(d(15), &["second"])).unwrap();
+
+    assert!(matches!(migrate(&source, &target, false), Err(AppError::Conflict(_))));
+    let report = migrate(&source, &target, true).unwrap();
+    assert_eq!((report.copied, report.skipped), (2, 1));
+    assert_eq!(target.load_day(d(15)).unwrap().notes.len(), 2);
+}
+
+#[test]
+fn locations_name_a_format_and_a_path() {
+    let data = Path::new("/data");
+    assert_eq!(
+        Location::parse("fs", data).unwrap(),
+        Location { format: StorageFormat::Json, path: data.to_path_buf() }
+    );
+    assert_eq!(Location::parse("sqlite", data).unwrap().path, data.join("aigenda.db"));
+    assert_eq!(Location::parse("md:/vault/journal", data).unwrap().to_string(), "markdown:/vault/journal");
+    assert!(Location::parse("csv:/x", data).is_err());
+}
@@ -0,0 +1,56 @@
+use aigenda::{
+    error::AppError,
+    notebooks::{Home, MAIN},
+};
+use assert_cmd::Command;
+use std::fs;
+
+#[test]
+fn notebooks_are_created_listed_and_picked() {
+    let dir = tempfile::tempdir().unwrap();
+    let home = Home::new(Some(dir.path().to_path_buf())).unwrap();
+    assert_eq!(home.list().unwrap(), [MAIN]);
+    assert!(matches!(home.open(Some("work")), Err(AppError::NotFound(_))));
+    assert!(matches!(home.create("../escape"), Err(AppError::InvalidParameters(_))));
+
+    let work = home.create("work").unwrap();
+    assert_eq!(work.data_dir, dir.path().join("notebooks").join("work"));
+    assert_eq!(work.config_dir, work.data_dir);
+    assert!(matches!(home.create("work"), Err(AppError::Conflict(_))));
+    home.create("personal").unwrap();
+    assert_eq!(home.list().unwrap(), [MAIN, "personal", "work"]);
+
+    assert_eq!(home.open(None).unwrap().name, MAIN);
+    home.set_default("work").unwrap();
+    assert_eq!(home.open(None).unwrap(), work);
+    assert_eq!(home.open(Some(MAIN)).unwrap().data_dir, dir.path());
+}
+
+#[test]
+fn notebooks_keep_their_notes_and_settings_apart() {
+    let dir = tempfile::tempdir().unwrap();
+    let aigenda = |args: &[&str]| {
+        let output = Command::cargo_bin("aigenda").unwrap().arg("--data-dir").arg(dir.path()).args(args).output().unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    aigenda(&["notebooks", "create", "work"]);
+    fs::write(dir.path().join("notebooks/work/config.json"), r#"{"storage": "markdown"}"#).unwrap();
+    aigenda(&["add", "personal", "note"]);
+    aigenda(&["--notebook", "work", "add", "work", "note"]);
+
+    assert!(aigenda(&["list"]).contains("personal note"));
+    assert!(!aigenda(&["list"]).contains("work note"));
+    let work = aigenda(&["--notebook", "work", "list"]);
+    assert!(work.contains("work note") && !work.contains("personal note"), "{}", work);
+    let files: Vec<String> = fs::read_dir(dir.path().join("notebooks/work"))
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    assert!(files.iter().any(|f| f.ends_with(".md")), "{:?}", files);
+
+    aigenda(&["notebooks", "default", "work"]);
+    assert!(aigenda(&["list"]).contains("work note"));
+    assert_eq!(aigenda(&["notebooks", "list"]), "  main\n* work\n");
+}
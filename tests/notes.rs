@@ -0,0 +1,109 @@
+use aigenda::{
+    error::AppError,
+    models::{DayLog, Editor, Note, NoteRef},
+    notes,
+    storage::{memory::MemoryStorage, Storage},
+    trash,
+};
+use chrono::NaiveDate;
+
+fn d(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+fn store_with(date: &str, texts: &[&str]) -> MemoryStorage {
+    let store = MemoryStorage::default();
+    let mut day = DayLog::new(d(date));
+    day.notes.extend(texts.iter().map(|t| Note::new(t.to_string())));
+    store.save_day(&day).unwrap();
+    store
+}
+
+fn texts(store: &MemoryStorage, date: &str) -> Vec<String> {
+    store.load_day(d(date)).unwrap().notes.into_iter().map(|n| n.text).collect()
+}
+
+#[test]
+fn amend_keeps_the_original_time() {
+    let store = store_with("2025-01-15", &["Fixd the bug"]);
+    let before = store.load_day(d("2025-01-15")).unwrap().notes[0].when;
+
+    let note = notes::amend_note(&store, NoteRef::new(d("2025-01-15"), 1), "Fixed the bug", Editor::Cli).unwrap();
+    assert_eq!(note.when, before);
+    assert!(note.edited_at.is_some());
+    assert_eq!(texts(&store, "2025-01-15"), ["Fixed the bug"]);
+
+    let missing = notes::amend_note(&store, NoteRef::new(d("2025-01-15"), 2), "Nope", Editor::Cli);
+    assert!(matches!(missing, Err(AppError::NotFound(_))));
+}
+
+#[test]
+fn move_reorders_and_moves_between_days() {
+    let store = store_with("2025-01-15", &["a", "b", "c"]);
+
+    let moved = notes::move_note(&store, NoteRef::new(d("2025-01-15"), 3), d("2025-01-15"), Some(1)).unwrap();
+    assert_eq!(moved, NoteRef::new(d("2025-01-15"), 1));
+    assert_eq!(texts(&store, "2025-01-15"), ["c", "a", "b"]);
+
+    let moved = notes::move_note(&store, NoteRef::new(d("2025-01-15"), 2), d("2025-01-16"), None).unwrap();
+    assert_eq!(moved, NoteRef::new(d("2025-01-16"), 1));
+    assert_eq!(texts(&store, "2025-01-15"), ["c", "b"]);
+    assert_eq!(texts(&store, "2025-01-16"), ["a"]);
+
+    assert!(notes::move_note(&store, NoteRef::new(d("2025-01-15"), 1), d("2025-01-16"), Some(5)).is_err());
+}
+
+#[test]
+fn removing_the_last_note_deletes_the_day() {
+    let store = store_with("2025-01-15", &["only"]);
+    let note = notes::remove_note(&store, NoteRef::new(d("2025-01-15"), 1)).unwrap();
+    assert_eq!(note.text, "only");
+    assert!(!store.exists(d("2025-01-15")).unwrap());
+}
+
+#[test]
+fn removed_notes_go_to_the_trash_until_restored_or_purged() {
+    let store = store_with("2025-01-15", &["a", "b", "c"]);
+    notes::remove_note(&store, NoteRef::new(d("2025-01-15"), 2)).unwrap();
+    notes::remove_note(&store, NoteRef::new(d("2025-01-15"), 2)).unwrap();
+    assert_eq!(texts(&store, "2025-01-15"), ["a"]);
+    assert_eq!(store.load_trash().unwrap().len(), 2);
+
+    // "b" goes back to its old place; "c"'s old position is past the end, so it's appended
+    let restored = trash::restore(&store, &[1, 2]).unwrap();
+    assert_eq!(restored, [NoteRef::new(d("2025-01-15"), 2), NoteRef::new(d("2025-01-15"), 3)]);
+    assert_eq!(texts(&store, "2025-01-15"), ["a", "b", "c"]);
+    assert!(store.load_trash().unwrap().is_empty());
+
+    notes::remove_note(&store, NoteRef::new(d("2025-01-15"), 1)).unwrap();
+    let now = store.load_trash().unwrap()[0].deleted_at;
+    assert_eq!(trash::purge_expired(&store, 30, now + chrono::Duration::days(29)).unwrap(), 0);
+    assert_eq!(trash::purge_expired(&store, 30, now + chrono::Duration::days(31)).unwrap(), 1);
+    assert!(matches!(trash::restore(&store, &[1]), Err(AppError::NotFound(_))));
+}
+
+#[test]
+fn edits_keep_revisions_and_revert_adds_one() {
+    let store = store_with("2025-01-15", &["v1"]);
+    let id = NoteRef::new(d("2025-01-15"), 1);
+    notes::amend_note(&store, id, "v2", Editor::Cli).unwrap();
+    notes::amend_note(&store, id, "v3", Editor::Agent).unwrap();
+
+    let note = notes::revert_note(&store, id, 1, Editor::Cli).unwrap();
+    assert_eq!(note.text, "v1");
+    let history: Vec<(String, Option<Editor>)> = note.history().into_iter().map(|r| (r.text, r.editor)).collect();
+    assert_eq!(history, [
+        ("v1".to_string(), None),
+        ("v2".to_string(), Some(Editor::Cli)),
+        ("v3".to_string(), Some(Editor::Agent)),
+        ("v1".to_string(), Some(Editor::Cli)),
+    ]);
+
+    // Revisions use short keys on disk
+    let json = serde_json::to_value(&note).unwrap();
+    assert_eq!(json["revisions"][1]["by"], "cli");
+    assert!(json["revisions"][0].get("by").is_none());
+
+    assert!(matches!(notes::revert_note(&store, id, 4, Editor::Cli), Err(AppError::InvalidParameters(_))));
+    assert!(matches!(notes::revert_note(&store, id, 9, Editor::Cli), Err(AppError::NotFound(_))));
+}
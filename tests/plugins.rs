@@ -0,0 +1,88 @@
+#![cfg(all(unix, feature = "ai"))]
+
+use aigenda::{
+    agent::{tools::external::plugin::{discover_plugins, PluginTool}, Tool},
+    config::PluginConfig,
+    error::AppError,
+};
+use serde_json::json;
+use std::{collections::BTreeMap, fs, os::unix::fs::PermissionsExt, path::Path};
+
+const SCRIPT: &str = r#"#!/bin/sh
+if [ "$1" = "--schema" ]; then
+  action() { printf '{"name":"%s","description":"%s","parameters":[],"returns":{"description":"text","return_type":{"String":{"max_length":null}},"possible_errors":[]}}' "$1" "$1"; }
+  printf '{"name":"shout","description":"Test plugin","category":"External","actions":[%s,%s,%s,%s],"examples":[]}' \
+    "$(action upper)" "$(action env)" "$(action sleep)" "$(action crash)"
+  exit 0
+fi
+req=$(cat)
+case "$req" in
+  *'"upper"'*) echo '{"result":"HELLO"}' ;;
+  *'"env"'*) echo "{\"result\":\"home=${HOME:-unset} greeting=${GREETING}\"}" ;;
+  *'"sleep"'*) sleep 5 ;;
+  *'"crash"'*) echo "boom" >&2; exit 3 ;;
+esac
+"#;
+
+fn write_plugin(dir: &Path, name: &str, mode: u32) {
+    let path = dir.join(name);
+    fs::write(&path, SCRIPT).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+}
+
+async fn load(dir: &Path) -> PluginTool {
+    let config = PluginConfig {
+        timeout_secs: Some(1),
+        env: BTreeMap::from([("GREETING".to_string(), "hi".to_string())]),
+        disabled: false,
+    };
+    PluginTool::load(&dir.join("shout"), &config).await.unwrap()
+}
+
+#[test]
+fn discovers_only_visible_executables() {
+    let dir = tempfile::tempdir().unwrap();
+    write_plugin(dir.path(), "shout", 0o755);
+    write_plugin(dir.path(), "notes.txt", 0o644);
+    write_plugin(dir.path(), ".hidden", 0o755);
+
+    let found = discover_plugins(dir.path());
+    assert_eq!(found, vec![dir.path().join("shout")]);
+}
+
+#[tokio::test]
+async fn loads_schema_and_executes_actions() {
+    let dir = tempfile::tempdir().unwrap();
+    write_plugin(dir.path(), "shout", 0o755);
+    let plugin = load(dir.path()).await;
+
+    assert_eq!(plugin.name(), "shout");
+    assert_eq!(plugin.get_schema().actions.len(), 4);
+    assert_eq!(plugin.execute("upper", &json!({ "text": "hello" })).await.unwrap(), "HELLO");
+    assert!(plugin.execute("missing", &json!({})).await.is_err());
+}
+
+#[tokio::test]
+async fn isolates_environment() {
+    let dir = tempfile::tempdir().unwrap();
+    write_plugin(dir.path(), "shout", 0o755);
+    let plugin = load(dir.path()).await;
+
+    let result = plugin.execute("env", &json!({})).await.unwrap();
+    assert_eq!(result, "home=unset greeting=hi");
+}
+
+#[tokio::test]
+async fn reports_timeouts_and_stderr() {
+    let dir = tempfile::tempdir().unwrap();
+    write_plugin(dir.path(), "shout", 0o755);
+    let plugin = load(dir.path()).await;
+
+    let timed_out = plugin.execute("sleep", &json!({})).await.unwrap_err();
+    assert!(timed_out.to_string().contains("timed out"));
+    assert!(matches!(timed_out, AppError::Network(_)), "{:?}", timed_out);
+
+    let crashed = plugin.execute("crash", &json!({})).await.unwrap_err();
+    assert!(crashed.to_string().contains("boom"));
+    assert!(matches!(crashed, AppError::Api { .. }), "{:?}", crashed);
+}
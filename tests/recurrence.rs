@@ -0,0 +1,74 @@
+use aigenda::{
+    dates,
+    recurrence::{Recurrence, Schedule},
+    storage::{memory::MemoryStorage, recurring::RecurringStorage, Storage},
+    templates::{fill, Templates},
+};
+use chrono::NaiveDate;
+use std::fs;
+
+fn d(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+#[test]
+fn schedules_parse_and_match() {
+    let weekdays: Schedule = "weekdays".parse().unwrap();
+    assert!(weekdays.matches(d("2025-01-17"))); // Friday
+    assert!(!weekdays.matches(d("2025-01-18")));
+
+    let weekly: Schedule = "weekly on mon,thu".parse().unwrap();
+    assert!(weekly.matches(d("2025-01-13")) && weekly.matches(d("2025-01-16")));
+    assert!(!weekly.matches(d("2025-01-14")));
+
+    // The 31st falls on the last day of shorter months
+    let monthly: Schedule = "monthly on 31".parse().unwrap();
+    assert!(monthly.matches(d("2025-02-28")));
+    assert!(!monthly.matches(d("2025-03-30")));
+
+    assert!("fortnightly".parse::<Schedule>().is_err());
+    assert_eq!(fill("Standup {{date}} ({{ weekday }})", d("2025-01-13")).unwrap(), "Standup 2025-01-13 (Monday)");
+    assert!(fill("{{unknown}}", d("2025-01-13")).is_err());
+}
+
+#[test]
+fn recurring_items_are_added_to_today_once() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("standup.md"), "Standup {{weekday}}\n\n- [ ] Update the board\n").unwrap();
+
+    let rules: Vec<Recurrence> = serde_json::from_str(r#"[
+        { "every": "daily", "template": "standup" },
+        { "every": "daily", "text": "Water plants", "task": true }
+    ]"#).unwrap();
+    let store = RecurringStorage::new(MemoryStorage::default(), rules, Templates::new(dir.path().to_path_buf()));
+    let today = dates::today();
+
+    let day = store.load_day(today).unwrap();
+    assert_eq!(day.notes.len(), 3);
+    assert!(day.notes[1].is_open_task() && day.notes[2].is_open_task());
+    assert_eq!(store.load_day(today).unwrap().notes.len(), 3);
+
+    // Past days aren't backfilled, and clearing today doesn't bring the items back
+    assert!(store.load_day(today.pred_opt().unwrap()).unwrap().notes.is_empty());
+    store.delete_day(today).unwrap();
+    assert!(store.load_day(today).unwrap().notes.is_empty());
+}
+
+#[test]
+fn a_broken_rule_is_skipped_without_failing_the_day() {
+    let dir = tempfile::tempdir().unwrap();
+    let rules: Vec<Recurrence> = serde_json::from_str(r#"[
+        { "every": "daily", "template": "missing" },
+        { "every": "daily", "text": "Water plants" }
+    ]"#).unwrap();
+    let store = RecurringStorage::new(MemoryStorage::default(), rules, Templates::new(dir.path().to_path_buf()));
+    let today = dates::today();
+
+    let day = store.load_day(today).unwrap();
+    assert_eq!(day.notes.len(), 1);
+    assert_eq!(day.recurring, ["Water plants"]);
+
+    // Once the template exists, the rule applies
+    fs::write(dir.path().join("missing.md"), "Found it\n").unwrap();
+    assert_eq!(store.load_day(today).unwrap().notes.len(), 2);
+}
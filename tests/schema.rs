@@ -0,0 +1,85 @@
+use aigenda::{
+    error::AppError,
+    schema::SCHEMA_VERSION,
+    storage::{fs::FsStorage, markdown::MarkdownStorage, Storage},
+};
+use assert_cmd::Command;
+use chrono::NaiveDate;
+use std::fs;
+
+fn d(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 1, day).unwrap()
+}
+
+const V1_DAY: &str = r#"{"date":"2025-01-15","notes":[{"when":"2025-01-15T09:00:00+01:00","text":"before tags"}]}"#;
+
+const V1_TRASH: &str = r#"[{"date":"2025-01-14","position":1,"deleted_at":"2025-01-15T10:00:00+01:00",
+    "note":{"when":"2025-01-14T09:00:00+01:00","text":"gone","tags":[]}}]"#;
+
+#[test]
+fn old_documents_are_upgraded_when_read_and_by_resaving() {
+    let data = tempfile::tempdir().unwrap();
+    fs::write(data.path().join("2025-01-15.json"), V1_DAY).unwrap();
+    fs::write(data.path().join("trash.json"), V1_TRASH).unwrap();
+    let store = FsStorage::with_root(data.path().to_path_buf()).unwrap();
+
+    let day = store.load_day(d(15)).unwrap();
+    assert_eq!(day.notes[0].text, "before tags");
+    assert!(day.notes[0].tags.is_empty());
+    let trash = store.load_trash().unwrap();
+    assert_eq!(trash[0].note.text, "gone");
+    assert_eq!(store.outdated().unwrap(), vec![(Some(d(15)), 1), (None, 1)]);
+
+    store.save_day(&day).unwrap();
+    store.save_trash(&trash).unwrap();
+    assert!(store.outdated().unwrap().is_empty());
+    let saved: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(data.path().join("trash.json")).unwrap()).unwrap();
+    assert_eq!(saved["schema_version"], SCHEMA_VERSION);
+    assert_eq!(saved["notes"][0]["note"]["text"], "gone");
+}
+
+#[test]
+fn documents_from_a_newer_schema_are_refused() {
+    let data = tempfile::tempdir().unwrap();
+    let newer = r#"{"schema_version":99,"date":"2025-01-15","notes":[]}"#;
+    fs::write(data.path().join("2025-01-15.json"), newer).unwrap();
+    let store = FsStorage::with_root(data.path().to_path_buf()).unwrap();
+    assert!(matches!(store.load_day(d(15)), Err(AppError::Conflict(_))));
+
+    let markdown = MarkdownStorage::with_root(data.path().to_path_buf()).unwrap();
+    fs::write(data.path().join("2025-01-15.md"), "---\ndate: 2025-01-15\nschema_version: 99\n---\n\n- 09:00 hi\n").unwrap();
+    assert!(matches!(markdown.load_day(d(15)), Err(AppError::Conflict(_))));
+}
+
+#[test]
+fn markdown_files_without_a_version_are_outdated() {
+    let data = tempfile::tempdir().unwrap();
+    fs::write(data.path().join("2025-01-15.md"), "- 09:00 written by hand\n").unwrap();
+    let store = MarkdownStorage::with_root(data.path().to_path_buf()).unwrap();
+    assert_eq!(store.outdated().unwrap(), vec![(Some(d(15)), 1)]);
+
+    store.save_day(&store.load_day(d(15)).unwrap()).unwrap();
+    assert!(store.outdated().unwrap().is_empty());
+    assert_eq!(store.load_day(d(15)).unwrap().notes[0].text, "written by hand");
+}
+
+#[test]
+fn doctor_migrates_without_adding_recurring_notes() {
+    let home = tempfile::tempdir().unwrap();
+    fs::write(home.path().join("config.json"), r#"{"recurring": [{"every": "daily", "text": "Standup"}]}"#).unwrap();
+    let today = chrono::Local::now().date_naive().format("%Y-%m-%d").to_string();
+    let file = home.path().join(format!("{}.json", today));
+    fs::write(&file, V1_DAY.replace("2025-01-15", &today)).unwrap();
+
+    Command::cargo_bin("aigenda")
+        .unwrap()
+        .env("AIGENDA_HOME", home.path())
+        .args(["doctor", "--migrate"])
+        .assert()
+        .success();
+
+    let saved: serde_json::Value = serde_json::from_str(&fs::read_to_string(&file).unwrap()).unwrap();
+    assert_eq!(saved["schema_version"], SCHEMA_VERSION);
+    assert_eq!(saved["notes"].as_array().unwrap().len(), 1, "{}", saved);
+}
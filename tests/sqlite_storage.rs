@@ -0,0 +1,67 @@
+#![cfg(feature = "sqlite")]
+
+#[macro_use]
+mod conformance;
+
+use aigenda::{
+    migrate::migrate,
+    models::{DayLog, Note, TrashedNote},
+    storage::{fs::FsStorage, sqlite::SqliteStorage, Storage},
+};
+use chrono::NaiveDate;
+use conformance::Backend;
+use std::path::Path;
+
+fn d(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 1, day).unwrap()
+}
+
+#[test]
+fn days_and_trash_round_trip() {
+    let data = tempfile::tempdir().unwrap();
+    let store = SqliteStorage::open(&data.path().join("notes").join("aigenda.db")).unwrap();
+    let mut day = DayLog::new(d(15));
+    day.notes.push(Note::new("ünïcode ✓".to_string()));
+    store.save_day(&day).unwrap();
+    day.notes.push(Note::new("second".to_string()));
+    store.save_day(&day).unwrap();
+    store.save_day(&DayLog::new(d(14))).unwrap();
+
+    assert_eq!(store.list_dates().unwrap(), vec![d(14), d(15)]);
+    assert!(store.exists(d(15)).unwrap());
+    assert_eq!(store.load_day(d(15)).unwrap().notes[0].text, "ünïcode ✓");
+    store.delete_day(d(14)).unwrap();
+    assert!(!store.exists(d(14)).unwrap());
+
+    let trashed = TrashedNote { date: d(15), position: 1, deleted_at: day.notes[0].when, note: day.notes[0].clone() };
+    store.save_trash(&[trashed]).unwrap();
+    assert_eq!(store.load_trash().unwrap().len(), 1);
+    store.save_trash(&[]).unwrap();
+    assert!(store.load_trash().unwrap().is_empty());
+}
+
+#[test]
+fn json_files_migrate_into_the_database() {
+    let data = tempfile::tempdir().unwrap();
+    let files = FsStorage::with_root(data.path().to_path_buf()).unwrap();
+    for date in [d(14), d(15)] {
+        let mut day = DayLog::new(date);
+        day.notes.push(Note::new(format!("note on {}", date)));
+        files.save_day(&day).unwrap();
+    }
+    let db = SqliteStorage::open(&data.path().join("aigenda.db")).unwrap();
+
+    let report = migrate(&files, &db, false).unwrap();
+    assert_eq!((report.days, report.copied), (2, 2));
+    assert_eq!(db.load_day(d(15)).unwrap().notes[0].text, "note on 2025-01-15");
+}
+
+fn corrupt_row(dir: &Path, date: NaiveDate) {
+    let db = rusqlite::Connection::open(dir.join("aigenda.db")).unwrap();
+    db.execute("UPDATE days SET log = '{\"date\":' WHERE date = ?1", [date.format("%Y-%m-%d").to_string()]).unwrap();
+}
+
+storage_conformance!(
+    database,
+    Backend { open: |dir| SqliteStorage::open(&dir.join("aigenda.db")).unwrap(), corrupt: Some(corrupt_row) }
+);
@@ -0,0 +1,84 @@
+use aigenda::{
+    dates::DateRange,
+    error::AppResult,
+    models::{DayLog, Note},
+    storage::{memory::MemoryStorage, Storage},
+};
+use chrono::NaiveDate;
+use std::sync::Mutex;
+
+/// Counts loads, to check iteration stays lazy
+#[derive(Default)]
+struct Counted {
+    store: MemoryStorage,
+    loads: Mutex<usize>,
+}
+
+impl Storage for Counted {
+    fn load_day(&self, date: NaiveDate) -> AppResult<DayLog> {
+        *self.loads.lock().unwrap() += 1;
+        self.store.load_day(date)
+    }
+
+    fn save_day(&self, day: &DayLog) -> AppResult<()> {
+        self.store.save_day(day)
+    }
+
+    fn list_dates(&self) -> AppResult<Vec<NaiveDate>> {
+        self.store.list_dates()
+    }
+
+    fn delete_day(&self, date: NaiveDate) -> AppResult<()> {
+        self.store.delete_day(date)
+    }
+}
+
+fn d(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+fn store_with(dates: &[&str]) -> Counted {
+    let store = Counted::default();
+    for date in dates {
+        let mut day = DayLog::new(d(date));
+        day.notes.push(Note::new(date.to_string()));
+        store.save_day(&day).unwrap();
+    }
+    store
+}
+
+fn dates_of(days: impl Iterator<Item = AppResult<DayLog>>) -> Vec<NaiveDate> {
+    days.map(|day| day.unwrap().date).collect()
+}
+
+#[test]
+fn iterates_ranges_in_both_directions() {
+    let store = store_with(&["2025-01-01", "2025-01-03", "2025-01-05", "2025-02-01"]);
+    let january = DateRange::new(d("2025-01-02"), d("2025-01-31")).unwrap();
+
+    assert_eq!(dates_of(store.days_between(january).unwrap()), [d("2025-01-03"), d("2025-01-05")]);
+    assert_eq!(dates_of(store.days_between(january).unwrap().rev()), [d("2025-01-05"), d("2025-01-03")]);
+    assert_eq!(store.list_dates().unwrap().len(), 4);
+    assert_eq!(store.iter_days().unwrap().len(), 4);
+}
+
+#[test]
+fn loads_only_what_is_consumed() {
+    let store = store_with(&["2025-01-01", "2025-01-02", "2025-01-03", "2025-01-04"]);
+    *store.loads.lock().unwrap() = 0;
+
+    let newest: Vec<_> = dates_of(store.days().unwrap().rev().take(2));
+    assert_eq!(newest, [d("2025-01-04"), d("2025-01-03")]);
+    assert_eq!(*store.loads.lock().unwrap(), 2);
+}
+
+#[test]
+fn deletes_and_checks_existence() {
+    let store = store_with(&["2025-01-01", "2025-01-02"]);
+
+    assert!(store.exists(d("2025-01-01")).unwrap());
+    store.delete_day(d("2025-01-01")).unwrap();
+    store.delete_day(d("2025-01-01")).unwrap();
+    assert!(!store.exists(d("2025-01-01")).unwrap());
+    assert_eq!(store.list_dates().unwrap(), [d("2025-01-02")]);
+}
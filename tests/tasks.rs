@@ -0,0 +1,46 @@
+use aigenda::{
+    error::AppError,
+    models::{NoteRef, Priority, TaskStatus},
+    storage::memory::MemoryStorage,
+    tasks,
+};
+use chrono::NaiveDate;
+
+fn d(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+#[test]
+fn open_tasks_carry_over_most_urgent_first() {
+    let store = MemoryStorage::default();
+    tasks::add_task(&store, d("2025-01-10"), "write report", None, Some(Priority::Low)).unwrap();
+    tasks::add_task(&store, d("2025-01-11"), "deploy release", Some(d("2025-01-14")), None).unwrap();
+    tasks::add_task(&store, d("2025-01-12"), "call bank", None, Some(Priority::High)).unwrap();
+    let done = tasks::add_task(&store, d("2025-01-12"), "buy milk", None, None).unwrap();
+    tasks::add_task(&store, d("2025-01-15"), "today's task", None, None).unwrap();
+    tasks::set_status(&store, done.note_ref, TaskStatus::Done).unwrap();
+
+    let carried: Vec<String> = tasks::carried_over(&store, d("2025-01-15"))
+        .unwrap()
+        .into_iter()
+        .map(|t| t.note.text)
+        .collect();
+    assert_eq!(carried, ["deploy release", "call bank", "write report"]);
+}
+
+#[test]
+fn tasks_resolve_by_id_or_unique_text() {
+    let store = MemoryStorage::default();
+    tasks::add_task(&store, d("2025-01-10"), "Deploy the API", None, None).unwrap();
+    tasks::add_task(&store, d("2025-01-10"), "Review API docs", None, None).unwrap();
+
+    assert_eq!(tasks::resolve_task(&store, "deploy").unwrap(), NoteRef::new(d("2025-01-10"), 1));
+    assert_eq!(tasks::resolve_task(&store, "2025-01-10#2").unwrap(), NoteRef::new(d("2025-01-10"), 2));
+    assert!(matches!(tasks::resolve_task(&store, "api"), Err(AppError::Conflict(_))));
+    assert!(matches!(tasks::resolve_task(&store, "groceries"), Err(AppError::NotFound(_))));
+
+    let task = tasks::set_status(&store, NoteRef::new(d("2025-01-10"), 1), TaskStatus::Done).unwrap();
+    assert!(task.note.task.as_ref().unwrap().closed_at.is_some());
+    // Closed tasks no longer match by text
+    assert!(matches!(tasks::resolve_task(&store, "deploy"), Err(AppError::NotFound(_))));
+}
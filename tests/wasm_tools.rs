@@ -0,0 +1,102 @@
+#![cfg(feature = "wasm")]
+
+use aigenda::{
+    agent::{
+        tools::external::wasm::{Capability, WasmManifest, WasmTool},
+        Tool,
+    },
+    error::AppError,
+    models::{DayLog, Note},
+    storage::{memory::MemoryStorage, Storage},
+};
+use chrono::NaiveDate;
+use serde_json::json;
+use std::sync::Arc;
+
+const SCHEMA: &str = r#"{"name":"sandboxed","description":"Test tool","category":"External","actions":[{"name":"run","description":"Run","parameters":[],"returns":{"description":"text","return_type":"Boolean","possible_errors":[]}}],"examples":[]}"#;
+
+fn wat_string(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// A module whose `execute` runs `body`, with `extra` available at offset 2048
+fn module(imports: &str, extra: &str, body: &str) -> Vec<u8> {
+    let wat = format!(
+        r#"(module
+            {imports}
+            (memory (export "memory") 1)
+            (global $heap (mut i32) (i32.const 8192))
+            (func (export "alloc") (param $len i32) (result i32)
+                (local $p i32)
+                (local.set $p (global.get $heap))
+                (global.set $heap (i32.add (global.get $heap) (local.get $len)))
+                (local.get $p))
+            (data (i32.const 0) "{schema}")
+            (data (i32.const 2048) "{extra}")
+            (func (export "schema") (result i64) (i64.const {schema_len}))
+            (func (export "execute") (param $ptr i32) (param $len i32) (result i64) {body}))"#,
+        schema = wat_string(SCHEMA),
+        schema_len = SCHEMA.len(),
+        extra = wat_string(extra),
+    );
+    wat::parse_str(wat).unwrap()
+}
+
+fn packed_extra(len: usize) -> String {
+    format!("(i64.or (i64.shl (i64.const 2048) (i64.const 32)) (i64.const {}))", len)
+}
+
+fn manifest(capabilities: Vec<Capability>) -> WasmManifest {
+    WasmManifest { capabilities, fuel: Some(1_000_000), max_memory_mb: Some(4) }
+}
+
+#[tokio::test]
+async fn runs_a_module_without_capabilities() {
+    let reply = r#"{"result":"hello from wasm"}"#;
+    let bytes = module("", reply, &packed_extra(reply.len()));
+    let tool = WasmTool::from_bytes(&bytes, manifest(vec![]), Arc::new(MemoryStorage::default())).unwrap();
+
+    assert_eq!(tool.name(), "sandboxed");
+    assert_eq!(tool.execute("run", &json!({})).await.unwrap(), "hello from wasm");
+    assert!(tool.execute("other", &json!({})).await.is_err());
+}
+
+#[test]
+fn refuses_modules_importing_ungranted_capabilities() {
+    let imports = r#"(import "aigenda" "notes_read" (func $notes_read (param i32 i32) (result i64)))"#;
+    let bytes = module(imports, "", "(i64.const 0)");
+
+    let denied = WasmTool::from_bytes(&bytes, manifest(vec![]), Arc::new(MemoryStorage::default()));
+    assert!(denied.is_err());
+
+    let wasi = r#"(import "wasi_snapshot_preview1" "fd_write" (func (param i32 i32 i32 i32) (result i32)))"#;
+    let bytes = module(wasi, "", "(i64.const 0)");
+    assert!(WasmTool::from_bytes(&bytes, manifest(vec![Capability::NotesRead]), Arc::new(MemoryStorage::default())).is_err());
+}
+
+#[tokio::test]
+async fn reads_notes_through_granted_capability() {
+    let storage = Arc::new(MemoryStorage::default());
+    let mut day = DayLog::new(NaiveDate::from_ymd_opt(2025, 1, 15).unwrap());
+    day.add_note(Note::new("from the host".to_string()));
+    storage.save_day(&day).unwrap();
+
+    let imports = r#"(import "aigenda" "notes_read" (func $notes_read (param i32 i32) (result i64)))"#;
+    let bytes = module(imports, "2025-01-15", "(call $notes_read (i32.const 2048) (i32.const 10))");
+    let tool = WasmTool::from_bytes(&bytes, manifest(vec![Capability::NotesRead]), storage).unwrap();
+
+    let result = tool.execute("run", &json!({})).await.unwrap();
+    assert!(result.contains("from the host"));
+}
+
+#[tokio::test]
+async fn stops_runaway_modules_with_fuel() {
+    let imports = r#"(import "aigenda" "log" (func $log (param i32 i32)))"#;
+    let body = "(call $log (i32.const 2048) (i32.const 8)) (loop $forever (br $forever)) (i64.const 0)";
+    let bytes = module(imports, "spinning", body);
+    let tool = WasmTool::from_bytes(&bytes, manifest(vec![]), Arc::new(MemoryStorage::default())).unwrap();
+
+    let error = tool.execute("run", &json!({})).await.unwrap_err();
+    assert!(matches!(error, AppError::Api { .. }), "{:?}", error);
+    assert!(error.to_string().contains("spinning"), "{}", error);
+}